@@ -23,4 +23,421 @@ pub enum SolTradeError {
     // state: AssetItemType
     #[msg("Invalid AssetItemType")]
     InvalidAssetItemType,
+
+    // state: Authority
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Signer is not the admin")]
+    AdminUnauthorized,
+    #[msg("Signer is not the pending admin")]
+    AdminInvalidPendingUser,
+    #[msg("Signer is not the pauser")]
+    PauserUnauthorized,
+    #[msg("Program data account does not belong to this program")]
+    UpgradeAuthorityInvalidProgramData,
+    #[msg("Signer is not the program upgrade authority")]
+    UpgradeAuthorityUnauthorized,
+
+    // state: MintRegistry
+    #[msg("Invalid MintRegistryMode")]
+    MintRegistryInvalidMode,
+    #[msg("Invalid MintRegistryStatus")]
+    MintRegistryInvalidStatus,
+    #[msg("Mint registry entry account is required for SPL assets")]
+    MintRegistryEntryMissing,
+    #[msg("Mint registry entry does not match the mint")]
+    MintRegistryInvalidEntry,
+    #[msg("Mint is not on the allowlist")]
+    MintNotAllowlisted,
+    #[msg("Mint is on the denylist")]
+    MintDenylisted,
+
+    // instructions: exchange
+    #[msg("Offer is not the accepted offer of the trade")]
+    ExchangeOfferNotAccepted,
+    #[msg("Signer is not the owner of the side receiving the asset item")]
+    ExchangeInvalidUser,
+    #[msg("Asset item does not belong to the trade or offer")]
+    ExchangeInvalidFrom,
+    #[msg("Asset item already exchanged")]
+    ExchangeAssetItemAlreadyExchanged,
+    #[msg("Asset item account is required for the asset type")]
+    ExchangeMissingAssetItem,
+
+    // instructions: cancel_trade / refund_asset_item
+    #[msg("Trade is cancelled")]
+    TradeCancelled,
+    #[msg("Trade already has an accepted offer")]
+    CancelTradeOfferAccepted,
+    #[msg("Signer is not the owner of the trade")]
+    CancelTradeUnauthorized,
+    #[msg("Asset item can not be refunded yet")]
+    RefundAssetItemNotRefundable,
+    #[msg("Asset item already released from escrow")]
+    RefundAssetItemAlreadyReleased,
+    #[msg("Asset item does not belong to the trade or offer")]
+    RefundAssetItemInvalidFrom,
+    #[msg("Receiver is not the owner of the asset item")]
+    RefundAssetItemInvalidReceiver,
+
+    // instructions: accept_offer
+    #[msg("Offer does not belong to the trade")]
+    OfferInvalidTrade,
+    #[msg("Trade already has an accepted offer")]
+    OfferAlreadyAccepted,
+    #[msg("Signer is not the owner of the trade")]
+    AcceptOfferUnauthorized,
+    #[msg("Offer round does not match")]
+    AcceptOfferInvalidRound,
+    #[msg("Offer has a pending counter offer")]
+    AcceptOfferCounterPending,
+
+    // instructions: counter_offer
+    #[msg("Signer is not the owner of the trade")]
+    CounterOfferUnauthorized,
+    #[msg("Signer is not the owner of the offer")]
+    CounterOfferNotTaker,
+    #[msg("Counter offer does not request any modification")]
+    CounterOfferEmpty,
+    #[msg("Too many asset items to drop")]
+    CounterOfferTooManyDropAssetItems,
+    #[msg("Counter offer round does not match")]
+    CounterOfferInvalidRound,
+    #[msg("Offer has no pending counter offer")]
+    CounterOfferNotPending,
+    #[msg("Asset items to drop do not match the counter offer")]
+    CounterOfferInvalidDropAssetItems,
+    #[msg("Asset item account is required for the requested lamports")]
+    CounterOfferMissingAssetItem,
+    #[msg("Asset item to drop is already released")]
+    CounterOfferAssetItemAlreadyReleased,
+    #[msg("Asset item to drop was not deposited by the taker")]
+    CounterOfferInvalidDropAssetItemUser,
+    #[msg("Asset item to drop is listed twice")]
+    CounterOfferDuplicateDropAssetItem,
+
+    // state: WantedAssetType
+    #[msg("Invalid WantedAssetType")]
+    InvalidWantedAssetType,
+
+    // instructions: counter_offer, accept_counter_offer, requested assets
+    #[msg("Counter offer can request at most 5 assets")]
+    CounterOfferTooManyRequestedAssets,
+    #[msg("Requested asset needs a mint for spl or collections, and an amount for sol")]
+    CounterOfferInvalidRequestedAsset,
+    #[msg("Every asset item of the offer must be passed, in order, after the dropped ones")]
+    CounterOfferInvalidAssetItems,
+    #[msg("Offer does not hold the assets requested by the counter offer")]
+    CounterOfferRequestedAssetsNotMet,
+
+    // instructions: add_asset_item / accept_offer, content hash
+    #[msg("Assets are frozen once an offer is accepted")]
+    AddAssetItemFrozen,
+    #[msg("Offer content changed since it was reviewed")]
+    AcceptOfferContentMismatch,
+
+    // instructions: add_asset_item, every variant
+    #[msg("Signer is not the owner of the trade or offer")]
+    AddAssetItemUnauthorized,
+
+
+    // state: TradeMode
+    #[msg("Invalid TradeMode")]
+    InvalidTradeMode,
+    #[msg("Instruction is not available in this trade mode")]
+    TradeInvalidMode,
+
+    // instructions: start_auction / create_offer / finalize_auction
+    #[msg("Signer is not the owner of the trade")]
+    AuctionUnauthorized,
+    #[msg("Auction can only start before any offer")]
+    AuctionHasOffers,
+    #[msg("Auction end time must be in the future")]
+    AuctionInvalidEndTime,
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+    #[msg("Bid is below the reserve price or the best bid plus the minimum increment")]
+    AuctionBidTooLow,
+    #[msg("Bid amount is only allowed on auctions")]
+    AuctionUnexpectedBid,
+    #[msg("Accounts for the bid are missing or invalid")]
+    AuctionInvalidBidAccounts,
+    #[msg("Accounts of the outbid offer are missing or invalid")]
+    AuctionInvalidPreviousBidAccounts,
+    #[msg("Auction has bids")]
+    AuctionHasBids,
+    #[msg("Every asset item of the trade must be passed, in order")]
+    LotInvalidAssetItems,
+    #[msg("Accounts of the winning offer are missing or invalid")]
+    AuctionInvalidWinnerAccounts,
+
+    // instructions: withdraw_bid
+    #[msg("Signer is not the owner of the offer")]
+    WithdrawBidUnauthorized,
+    #[msg("Offer has no bid in tokens left to withdraw")]
+    WithdrawBidNothingToWithdraw,
+    #[msg("Leading bid can't be withdrawn")]
+    WithdrawBidLeading,
+    #[msg("Accounts for the withdrawal are missing or invalid")]
+    WithdrawBidInvalidAccounts,
+
+
+    // state: DutchAuctionDecay
+    #[msg("Invalid DutchAuctionDecay")]
+    InvalidDutchAuctionDecay,
+
+    // instructions: start_dutch_auction / buy_dutch_auction
+    #[msg("Start price must not be below the end price")]
+    DutchAuctionInvalidPrices,
+    #[msg("Dutch auction must end after it starts, in steps that fit its duration")]
+    DutchAuctionInvalidTimes,
+    #[msg("Dutch auction has not started yet")]
+    DutchAuctionNotStarted,
+    #[msg("Current price is above the maximum price of the buyer")]
+    DutchAuctionPriceAboveMax,
+    #[msg("Accounts for the payment are missing or invalid")]
+    InvalidPaymentAccounts,
+    #[msg("Trade is already sold")]
+    TradeSold,
+
+    // instructions: list_fixed_price / buy
+    #[msg("Price must be greater than zero")]
+    FixedPriceInvalidPrice,
+    #[msg("User is not in the allowed users of the trade")]
+    TradeUserNotAllowed,
+
+    // instructions: create_trade / add_asset_item, wanted assets
+    #[msg("Too many wanted assets")]
+    CreateTradeTooManyWantedAssets,
+    #[msg("Wanted asset needs a mint for spl or collections, and an amount for sol")]
+    CreateTradeInvalidWantedAsset,
+    #[msg("Strict mode needs at least one wanted asset")]
+    CreateTradeStrictWithoutWantedAssets,
+    #[msg("Trade of the offer is missing or invalid")]
+    AddAssetItemInvalidTrade,
+    #[msg("Asset item is not wanted by the trade")]
+    AddAssetItemNotWanted,
+
+    // instructions: accept_offer, strict trades
+    #[msg("Every asset item of the offer must be passed, in order")]
+    AcceptOfferInvalidAssetItems,
+    #[msg("Offer does not meet the wanted assets of the strict trade")]
+    AcceptOfferWantedAssetsNotMet,
+
+
+    // state: AutoAcceptRule
+    #[msg("Invalid AutoAcceptRule")]
+    InvalidAutoAcceptRule,
+
+    // instructions: set_auto_accept / try_auto_accept
+    #[msg("Signer is not the owner of the trade")]
+    AutoAcceptUnauthorized,
+    #[msg(
+        "Auto accept rule needs an amount, a mint for MinTokens and wanted assets for WantedAssets"
+    )]
+    AutoAcceptInvalidRule,
+    #[msg("Auto accept is disabled on this trade")]
+    AutoAcceptDisabled,
+    #[msg("Offer does not match the auto accept rule")]
+    AutoAcceptNotMatched,
+    #[msg("Every asset item of the offer then of the trade must be passed, in order")]
+    AutoAcceptInvalidAssetItems,
+    #[msg("Maker or taker account is invalid")]
+    AutoAcceptInvalidUsers,
+
+    // instructions: create_collection_offer / fill_collection_offer / cancel_collection_offer
+    #[msg("Collection offer needs a collection, a price and a quantity")]
+    CollectionOfferInvalidParams,
+    #[msg("Collection offer is fully filled")]
+    CollectionOfferFilled,
+    #[msg("Mint is not an nft, supply must be 1 and decimals 0")]
+    NftInvalidMint,
+    #[msg("Metadata account does not belong to the mint")]
+    NftInvalidMetadata,
+    #[msg("Nft is not a verified item of the collection")]
+    NftCollectionMismatch,
+    #[msg("Signer is not the owner of the collection offer")]
+    CollectionOfferUnauthorized,
+    #[msg("Buyer does not match the collection offer")]
+    CollectionOfferInvalidBuyer,
+
+    // instructions: add_asset_item / settlements, nft royalties
+    #[msg("Nft accounts are missing or invalid")]
+    AddAssetItemInvalidNft,
+    #[msg("Creator accounts of the nfts are missing or invalid")]
+    RoyaltyInvalidCreators,
+    #[msg("Every nft asset item of the receiving side must be passed, in order")]
+    RoyaltyInvalidAssetItems,
+
+    // instructions: add_asset_item_pnft / claim_pnft
+    #[msg("Programmable nfts are deposited in escrow with add_asset_item_pnft")]
+    PnftInvalidDeposit,
+    #[msg("Nft is not a programmable nft")]
+    PnftInvalidTokenStandard,
+    #[msg("Asset item is not a programmable nft in escrow")]
+    PnftNotEscrowed,
+    #[msg("Asset item is not released yet")]
+    PnftNotReleased,
+    #[msg("Receiver is not the one the asset item was released to")]
+    PnftInvalidReceiver,
+
+    // instructions: add_asset_item_cnft / claim_cnft
+    #[msg("Merkle tree is not the one of the compressed nft")]
+    CnftInvalidTree,
+    #[msg("Compressed nft is not in escrow")]
+    CnftNotEscrowed,
+    #[msg("Compressed nft is not released yet")]
+    CnftNotReleased,
+    #[msg("Receiver is not the one the compressed nft was released to")]
+    CnftInvalidReceiver,
+
+    // instructions: add_asset_item / claim_spl, token 2022
+    #[msg("Spl accounts are missing or invalid")]
+    AddAssetItemInvalidSpl,
+    #[msg("Non transferable tokens can't be traded")]
+    SplNonTransferableUnsupported,
+    #[msg("Confidential transfers are not supported")]
+    SplConfidentialTransferUnsupported,
+    #[msg("Tokens with a permanent delegate can't be held in escrow")]
+    SplPermanentDelegateUnsupported,
+    #[msg("Asset item is not spl tokens in escrow")]
+    SplNotEscrowed,
+    #[msg("Asset item is not released yet")]
+    SplNotReleased,
+    #[msg("Token account is not the one of the receiver")]
+    SplInvalidReceiver,
+
+    // instructions: add_asset_item_core / claim_core, metaplex core
+    #[msg("Account is not a metaplex core asset")]
+    CoreInvalidAsset,
+    #[msg("Core asset is not owned by the user")]
+    CoreInvalidOwner,
+    #[msg("Collection account is not the core collection of the asset")]
+    CoreInvalidCollection,
+    #[msg("Frozen core assets can't be traded")]
+    CoreFrozen,
+    #[msg("Core assets with a permanent delegate can't be held in escrow")]
+    CorePermanentDelegateUnsupported,
+    #[msg("Royalties of the core asset have too many creators")]
+    CoreTooManyCreators,
+    #[msg("Core asset is not in escrow")]
+    CoreNotEscrowed,
+    #[msg("Core asset is not released yet")]
+    CoreNotReleased,
+    #[msg("Receiver is not the one the core asset was released to")]
+    CoreInvalidReceiver,
+
+    // instructions: add_asset_item_stake / claim_stake, native stake
+    #[msg("Invalid StakeActivation")]
+    InvalidStakeActivation,
+    #[msg("Stake account is not initialized")]
+    StakeInvalidAccount,
+    #[msg("User is not the staker and the withdrawer of the stake account")]
+    StakeInvalidAuthority,
+    #[msg("Stake account is locked up")]
+    StakeLockupInForce,
+    #[msg("Stake account is not in escrow")]
+    StakeNotEscrowed,
+    #[msg("Stake account is not released yet")]
+    StakeNotReleased,
+    #[msg("Receiver is not the one the stake account was released to")]
+    StakeInvalidReceiver,
+
+    // instructions: add_asset_item_authority / claim_authority, mint and upgrade authorities
+    #[msg("Invalid AuthorityKind")]
+    InvalidAuthorityKind,
+    #[msg("Target is not an spl mint or an upgradeable program")]
+    AuthorityInvalidTarget,
+    #[msg("Program data does not belong to the program")]
+    AuthorityInvalidProgramData,
+    #[msg("User does not hold the authority")]
+    AuthorityNotHeld,
+    #[msg("Authority is not in escrow")]
+    AuthorityNotEscrowed,
+    #[msg("Authority is not released yet")]
+    AuthorityNotReleased,
+    #[msg("Receiver is not the one the authority was released to")]
+    AuthorityInvalidReceiver,
+
+    // instructions: add_asset_item, spl token account
+    #[msg("Token account does not hold the declared mint")]
+    AddAssetItemSplMintMismatch,
+    #[msg("Token account is not owned by the user")]
+    AddAssetItemSplInvalidOwner,
+
+    // instructions: add_asset_item / settlements, wrapped SOL
+    #[msg("Wrapped SOL accounts are missing or invalid")]
+    WsolInvalidAccounts,
+    #[msg("Wrapped SOL account of the recipient is missing or invalid")]
+    SolPayoutInvalidWsolAccount,
+
+    // instructions: create_ring / add_ring_asset_item / confirm_ring / withdraw_ring / claim_ring_asset_item
+    #[msg("Ring needs 3 to 8 distinct participants, the creator included")]
+    RingInvalidParticipants,
+    #[msg("User is not a participant of the ring")]
+    RingNotParticipant,
+    #[msg("Recipient must be another participant of the ring")]
+    RingInvalidRecipient,
+    #[msg("Ring is already settled or cancelled")]
+    RingClosed,
+    #[msg("Only SOL and SPL asset items can go in a ring")]
+    RingInvalidAssetType,
+    #[msg("Amount must be greater than 0")]
+    RingInvalidAmount,
+    #[msg("SPL accounts are missing or invalid")]
+    RingInvalidSpl,
+    #[msg("Participant must escrow an asset item before confirming")]
+    RingNothingDeposited,
+    #[msg("Ring is neither settled nor cancelled")]
+    RingNotReleased,
+    #[msg("Asset item is not in escrow")]
+    RingNotEscrowed,
+    #[msg("Receiver is not the one the asset item goes to")]
+    RingInvalidReceiver,
+
+    // instructions: add_asset_items
+    #[msg("Batch must hold 1 to 10 asset items")]
+    AddAssetItemsInvalidCount,
+    #[msg("Remaining accounts are missing or do not match the asset items")]
+    AddAssetItemsInvalidAccounts,
+
+    // instructions: direct_swap
+    #[msg("Maker and taker must be different users")]
+    DirectSwapSameUser,
+    #[msg("Swap must hold 1 to 16 assets")]
+    DirectSwapInvalidCount,
+    #[msg("Maker and taker must both give an asset")]
+    DirectSwapOneSided,
+    #[msg("Amount must be greater than 0")]
+    DirectSwapInvalidAmount,
+    #[msg("Only SOL, SPL and non programmable NFT assets can be swapped directly")]
+    DirectSwapInvalidAssetType,
+    #[msg("Remaining accounts are missing or do not match the assets")]
+    DirectSwapInvalidAccounts,
+
+    // instructions: fill_signed_offer / cancel_signed_offer
+    #[msg("Ed25519 program instruction must come right before")]
+    Ed25519MissingInstruction,
+    #[msg("Ed25519 instruction does not verify the signature of the offer by the taker")]
+    Ed25519InvalidSignature,
+    #[msg("Signed offer is for another program, trade or taker")]
+    SignedOfferMismatch,
+    #[msg("Signed offer has expired")]
+    SignedOfferExpired,
+    #[msg("Signed offer must give 1 to 8 assets, each with an amount")]
+    SignedOfferInvalidAssets,
+    #[msg("Remaining accounts are missing or do not match the signed offer")]
+    SignedOfferInvalidAccounts,
+
+    // instructions: add_asset_item / claim_nft, nft escrow
+    #[msg("Nft token account must be the one of the options, of the user and holding the nft")]
+    AddAssetItemNftInvalidToken,
+    #[msg("Asset item is not an nft in escrow")]
+    NftNotEscrowed,
+    #[msg("Asset item is not released yet")]
+    NftNotReleased,
+    #[msg("Receiver is not the one the asset item was released to")]
+    NftInvalidReceiver,
 }
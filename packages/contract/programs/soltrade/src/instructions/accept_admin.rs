@@ -0,0 +1,39 @@
+use {
+    crate::{error::SolTradeError, state::Authority},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct AcceptAdminContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user: proposed admin
+    pub user: Signer<'info>,
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdminContext>) -> Result<()> {
+    let authority = &mut ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-signer must be the proposed admin
+    require!(
+        authority.pending_user != Pubkey::default() && authority.pending_user == user.key(),
+        SolTradeError::AdminInvalidPendingUser
+    );
+
+    // * hand over admin
+    authority.user = user.key();
+    authority.pending_user = Pubkey::default();
+
+    Ok(())
+}
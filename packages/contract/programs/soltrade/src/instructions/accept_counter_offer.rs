@@ -0,0 +1,235 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{
+            AssetItem, AssetItemSOL, AssetItemType, Authority, CounterOffer, Offer, Trade,
+            WantedAsset,
+        },
+        utils::{
+            assert_not_paused, assert_offer_of_trade, lot_asset_item, release_asset_item,
+            update_asset_item, AssetItemRelease,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct AcceptCounterOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the offer
+    pub trade: Account<'info, Trade>,
+
+    // ? offer: countered offer
+    #[account(mut)]
+    pub offer: Account<'info, Offer>,
+
+    // ? counter_offer: pending counter offer, rent goes back to the maker
+    #[account(
+        mut,
+        close = maker,
+        seeds = [
+            offer.key().as_ref(),
+            CounterOffer::PREFIX.as_bytes(),
+        ],
+        bump,
+    )]
+    pub counter_offer: Account<'info, CounterOffer>,
+
+    // ? asset_item_sol: new asset item for the requested lamports, only when the counter offer asks for lamports
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemSOL::SIZE,
+        seeds = [
+            offer.key().as_ref(),
+            AssetItemSOL::PREFIX.as_bytes(),
+            offer.asset_sol_count.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_sol: Option<Account<'info, AssetItemSOL>>,
+
+    // ? maker: owner of the trade, gets the counter offer rent back
+    #[account(mut)]
+    /// CHECK: checked against the trade user in runtime
+    pub maker: AccountInfo<'info>,
+
+    // ? user: owner of the offer
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: asset items to drop, in the order of counter_offer.drop_asset_items,
+    // ? then when the counter offer requests assets, every asset item of the offer ordered by index
+}
+
+pub fn accept_counter_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptCounterOfferContext<'info>>,
+    round: u32,
+) -> Result<()> {
+    let trade = &ctx.accounts.trade;
+    let offer = &mut ctx.accounts.offer;
+    let counter_offer = &ctx.accounts.counter_offer;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner of the offer can accept the counter offer
+    require!(
+        offer.user == user.key(),
+        SolTradeError::CounterOfferNotTaker
+    );
+    require!(
+        trade.user == ctx.accounts.maker.key(),
+        SolTradeError::CounterOfferUnauthorized
+    );
+    // |-offer must belong to an open trade
+    assert_offer_of_trade(offer, &trade.key())?;
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    // |-bind to the counter offer the taker has seen
+    require!(offer.counter_pending, SolTradeError::CounterOfferNotPending);
+    require!(
+        counter_offer.round == round && offer.round == round,
+        SolTradeError::CounterOfferInvalidRound
+    );
+    // |-every dropped asset item must be passed, in order
+    require!(
+        ctx.remaining_accounts.len() >= counter_offer.drop_asset_items.len(),
+        SolTradeError::CounterOfferInvalidDropAssetItems
+    );
+    let (drop_asset_items, offer_asset_items) = ctx
+        .remaining_accounts
+        .split_at(counter_offer.drop_asset_items.len());
+    // |-requested assets are checked against every asset item of the offer
+    let asset_item_count = if counter_offer.requested_assets.is_empty() {
+        0
+    } else {
+        offer.asset_count as usize
+    };
+    require!(
+        offer_asset_items.len() == asset_item_count,
+        SolTradeError::CounterOfferInvalidAssetItems
+    );
+
+    // * drop asset items, they go back to the taker
+    let offer_account_info = offer.to_account_info();
+    for (asset_item, drop_asset_item_key) in drop_asset_items
+        .iter()
+        .zip(counter_offer.drop_asset_items.iter())
+    {
+        require_keys_eq!(
+            asset_item.key(),
+            *drop_asset_item_key,
+            SolTradeError::CounterOfferInvalidDropAssetItems
+        );
+        let content = drop_asset_item(asset_item, &offer_account_info, &user.to_account_info())?;
+
+        // ? a dropped asset item is released, same as an exchanged one
+        offer.exchanged_count = offer.exchanged_count.checked_add(1).unwrap();
+        offer.update_content_hash(Offer::CONTENT_DROP, &asset_item.key(), &content);
+    }
+
+    // * requested lamports, added as a new asset item of the offer
+    if counter_offer.sol_amount > 0 {
+        let asset_item_sol = ctx
+            .accounts
+            .asset_item_sol
+            .as_mut()
+            .ok_or(SolTradeError::CounterOfferMissingAssetItem)?;
+
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &user.key(),
+            &offer.key(),
+            counter_offer.sol_amount,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[user.to_account_info(), offer.to_account_info()],
+        )?;
+
+        asset_item_sol.index = offer.asset_count;
+        asset_item_sol.type_index = offer.asset_sol_count;
+        asset_item_sol.from = offer.key();
+        asset_item_sol.user = user.key();
+        asset_item_sol.asset_type = AssetItemType::SOL.to_code();
+        asset_item_sol.amount = counter_offer.sol_amount;
+
+        offer.update_content_hash(
+            Offer::CONTENT_ADD,
+            &asset_item_sol.key(),
+            &asset_item_sol.content(),
+        );
+        offer.asset_sol_count = offer.asset_sol_count.checked_add(1).unwrap();
+        offer.asset_count = offer.asset_count.checked_add(1).unwrap();
+    }
+
+    // * the offer must hold the requested assets, dropped asset items are released and skipped
+    if !counter_offer.requested_assets.is_empty() {
+        let mut offer_assets = Vec::new();
+        for (index, asset_item) in offer_asset_items.iter().enumerate() {
+            if let Some(read) = lot_asset_item(asset_item, index as u32, &offer.key(), None)? {
+                offer_assets.push((read.asset_type, read.mint, read.amount, read.collection));
+            }
+        }
+        if counter_offer.sol_amount > 0 {
+            offer_assets.push((
+                AssetItemType::SOL,
+                Pubkey::default(),
+                counter_offer.sol_amount,
+                None,
+            ));
+        }
+        require!(
+            WantedAsset::all_met(&counter_offer.requested_assets, &offer_assets)?,
+            SolTradeError::CounterOfferRequestedAssetsNotMet
+        );
+    }
+
+    // * settled, the maker can accept this round now
+    offer.counter_pending = false;
+
+    Ok(())
+}
+
+// ? releases a dropped asset item to the taker, lamports go back right away. returns the content
+// ? of the asset item
+fn drop_asset_item<'info>(
+    asset_item: &AccountInfo<'info>,
+    offer: &AccountInfo<'info>,
+    taker: &AccountInfo<'info>,
+) -> Result<Vec<u8>> {
+    let (content, lamports) = update_asset_item(asset_item, |item| {
+        // ! @check
+        // |-asset item must be in escrow of the offer, and belong to the taker
+        release_asset_item(
+            item,
+            AssetItemRelease::CounterOfferDrop,
+            offer.key,
+            Some(taker.key),
+            taker.key,
+        )?;
+        Ok((item.content(), item.released().lamports()))
+    })?;
+
+    // * give the lamports back
+    **offer.try_borrow_mut_lamports()? -= lamports;
+    **taker.try_borrow_mut_lamports()? += lamports;
+
+    Ok(content)
+}
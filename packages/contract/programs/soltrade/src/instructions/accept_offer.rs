@@ -1,10 +1,24 @@
 use {
-    crate::state::{Offer, Trade},
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Offer, Trade, TradeMode},
+        utils::{assert_not_paused, assert_offer_of_trade, lot_asset_item},
+    },
     anchor_lang::prelude::*,
 };
 
 #[derive(Accounts)]
 pub struct AcceptOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
     // ? offer: account to create
     #[account(mut)]
     pub offer: Account<'info, Offer>,
@@ -20,19 +34,68 @@ pub struct AcceptOfferContext<'info> {
 
     // ? system_program: system program
     pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: strict trades only, every asset item of the offer, ordered by index
 }
 
-pub fn accept_offer(ctx: Context<AcceptOfferContext>) -> Result<()> {
-    // let user = &mut ctx.accounts.user;
+pub fn accept_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptOfferContext<'info>>,
+    round: u32,
+    content_hash: [u8; 32],
+) -> Result<()> {
+    let user = &ctx.accounts.user;
     let offer = &mut ctx.accounts.offer;
     let trade = &mut ctx.accounts.trade;
 
     // ! @check
-    // |-index should be same as current asset count in trade
-    // require!(
-    //     offer.asset_count == index,
-    //     SolTradeError::AcceptOfferInvalidIndex
-    // );
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-trade must not be cancelled
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-auctions are settled by finalize_auction
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    // |-only the owner of the trade can accept
+    require!(
+        trade.user == user.key(),
+        SolTradeError::AcceptOfferUnauthorized
+    );
+    // |-offer must belong to the trade, and only one offer can be accepted
+    assert_offer_of_trade(offer, &trade.key())?;
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    // |-bind to the round the maker has seen, a pending counter offer must be settled first
+    require!(offer.round == round, SolTradeError::AcceptOfferInvalidRound);
+    require!(
+        !offer.counter_pending,
+        SolTradeError::AcceptOfferCounterPending
+    );
+    // |-bind to the assets the maker has seen, they can change up to the same slot
+    require!(
+        offer.content_hash == content_hash,
+        SolTradeError::AcceptOfferContentMismatch
+    );
+    // |-a strict trade takes the wanted amounts, asset items only match them one by one
+    if trade.strict {
+        require!(
+            ctx.remaining_accounts.len() == offer.asset_count as usize,
+            SolTradeError::AcceptOfferInvalidAssetItems
+        );
+        let mut offer_assets = Vec::new();
+        for (index, asset_item) in ctx.remaining_accounts.iter().enumerate() {
+            if let Some(read) = lot_asset_item(asset_item, index as u32, &offer.key(), None)? {
+                offer_assets.push((read.asset_type, read.mint, read.amount, read.collection));
+            }
+        }
+        require!(
+            trade.wanted_assets_met(&offer_assets)?,
+            SolTradeError::AcceptOfferWantedAssetsNotMet
+        );
+    }
 
     trade.accepted_offer = offer.key();
     offer.accepted = true;
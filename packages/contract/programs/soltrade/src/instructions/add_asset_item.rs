@@ -1,11 +1,27 @@
 use {
     crate::{
         error::SolTradeError,
-        state::{AssetItemSOL, AssetItemSPL, AssetItemType, Offer, Trade},
+        state::{
+            AssetItem, AssetItemNFT, AssetItemSOL, AssetItemSPL, AssetItemType, Authority, Offer,
+            Trade, TradeMode,
+        },
+        utils::{
+            assert_mint_allowed, assert_mint_supported, assert_nft, assert_not_paused,
+            assert_offer_of_trade, is_programmable, royalty_creators, token_amount, transfer_spl,
+            unwrap_sol, verified_collection, UnwrapSolAccounts,
+        },
     },
     anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::{
+            self, get_associated_token_address, get_associated_token_address_with_program_id,
+            AssociatedToken,
+        },
+        metadata::MetadataAccount,
+        token::{self as spl_token, Mint, TokenAccount},
+        token_interface::{self, TokenInterface},
+    },
     borsh::BorshDeserialize,
-    std::str::FromStr,
 };
 
 // ======================================================================
@@ -18,14 +34,20 @@ pub struct OptionsAssetItemSOL {
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct OptionsAssetItemSPL {
-    pub token: String,
-    pub mint: String,
+    pub token: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OptionsAssetItemNFT {
+    pub token: Pubkey,
 }
 
 // ======================================================================
 // =======================[ TRADE OR OFFER ]=============================
 // ======================================================================
-trait TradeOrOffer {
+pub(crate) trait TradeOrOffer {
     fn get_asset_count(&self) -> u32;
     fn set_asset_count(&mut self, count: u32);
 
@@ -35,6 +57,21 @@ trait TradeOrOffer {
     fn get_spl_count(&self) -> u32;
     fn set_spl_count(&mut self, count: u32);
 
+    fn get_nft_count(&self) -> u32;
+    fn set_nft_count(&mut self, count: u32);
+
+    fn get_cnft_count(&self) -> u32;
+    fn set_cnft_count(&mut self, count: u32);
+
+    fn get_stake_count(&self) -> u32;
+    fn set_stake_count(&mut self, count: u32);
+
+    fn get_authority_count(&self) -> u32;
+    fn set_authority_count(&mut self, count: u32);
+
+    fn is_frozen(&self) -> bool;
+    fn on_asset_added(&mut self, asset_item: &Pubkey, content: &[u8]);
+
     fn ex(&self);
 }
 impl<'info> TradeOrOffer for Account<'info, Trade> {
@@ -62,6 +99,46 @@ impl<'info> TradeOrOffer for Account<'info, Trade> {
         self.asset_spl_count = count;
     }
 
+    fn get_nft_count(&self) -> u32 {
+        self.asset_nft_count
+    }
+
+    fn set_nft_count(&mut self, count: u32) {
+        self.asset_nft_count = count;
+    }
+
+    fn get_cnft_count(&self) -> u32 {
+        self.asset_cnft_count
+    }
+
+    fn set_cnft_count(&mut self, count: u32) {
+        self.asset_cnft_count = count;
+    }
+
+    fn get_stake_count(&self) -> u32 {
+        self.asset_stake_count
+    }
+
+    fn set_stake_count(&mut self, count: u32) {
+        self.asset_stake_count = count;
+    }
+
+    fn get_authority_count(&self) -> u32 {
+        self.asset_authority_count
+    }
+
+    fn set_authority_count(&mut self, count: u32) {
+        self.asset_authority_count = count;
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.accepted_offer != Pubkey::default() || self.mode != TradeMode::Offer.to_code()
+    }
+
+    fn on_asset_added(&mut self, _asset_item: &Pubkey, _content: &[u8]) {
+        // ? only offers are bound by a content hash
+    }
+
     fn ex(&self) {
         self.exit(&crate::id()).unwrap();
     }
@@ -91,10 +168,83 @@ impl<'info> TradeOrOffer for Account<'info, Offer> {
         self.asset_spl_count = count;
     }
 
+    fn get_nft_count(&self) -> u32 {
+        self.asset_nft_count
+    }
+
+    fn set_nft_count(&mut self, count: u32) {
+        self.asset_nft_count = count;
+    }
+
+    fn get_cnft_count(&self) -> u32 {
+        self.asset_cnft_count
+    }
+
+    fn set_cnft_count(&mut self, count: u32) {
+        self.asset_cnft_count = count;
+    }
+
+    fn get_stake_count(&self) -> u32 {
+        self.asset_stake_count
+    }
+
+    fn set_stake_count(&mut self, count: u32) {
+        self.asset_stake_count = count;
+    }
+
+    fn get_authority_count(&self) -> u32 {
+        self.asset_authority_count
+    }
+
+    fn set_authority_count(&mut self, count: u32) {
+        self.asset_authority_count = count;
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.accepted || self.bid_amount > 0
+    }
+
+    fn on_asset_added(&mut self, asset_item: &Pubkey, content: &[u8]) {
+        self.update_content_hash(Offer::CONTENT_ADD, asset_item, content);
+    }
+
     fn ex(&self) {
         self.exit(&crate::id()).unwrap();
     }
 }
+
+// ? shared by every add_asset_item instruction: loads the trade (from_type 1) or the offer
+// ? (from_type 2) of user the asset item goes to, runs process on it and saves it. an offer must
+// ? belong to trade, which process gets when it is strict
+pub(crate) fn process_trade_or_offer<'info>(
+    from_type: u8,
+    trade_or_offer: &AccountInfo<'info>,
+    trade: Option<&Account<'info, Trade>>,
+    user: &Pubkey,
+    process: impl FnOnce(&mut dyn TradeOrOffer, Option<&Trade>) -> Result<()>,
+) -> Result<()> {
+    if from_type == 1 {
+        let mut trade: Account<Trade> = Account::try_from(trade_or_offer)?;
+        require_keys_eq!(trade.user, *user, SolTradeError::AddAssetItemUnauthorized);
+        require!(!trade.cancelled, SolTradeError::TradeCancelled);
+        process(&mut trade, None)?;
+        trade.ex();
+    } else if from_type == 2 {
+        let mut offer: Account<Offer> = Account::try_from(trade_or_offer)?;
+        require_keys_eq!(offer.user, *user, SolTradeError::AddAssetItemUnauthorized);
+        let trade = trade.ok_or(SolTradeError::AddAssetItemInvalidTrade)?;
+        assert_offer_of_trade(&offer, &trade.key())
+            .map_err(|_| SolTradeError::AddAssetItemInvalidTrade)?;
+        require!(!trade.cancelled, SolTradeError::TradeCancelled);
+        process(&mut offer, Some(&**trade).filter(|trade| trade.strict))?;
+        offer.ex();
+    } else {
+        return Err(SolTradeError::InvalidAssetItemType.into());
+    }
+
+    Ok(())
+}
+
 // fn calculate_asset_item_space(asset_type: u8) -> usize {
 //     // Determine the space required for AssetItem based on asset_type
 //     match AssetItemType::from_code(asset_type).unwrap() {
@@ -110,6 +260,16 @@ impl<'info> TradeOrOffer for Account<'info, Offer> {
 #[derive(Accounts)]
 #[instruction(from_type: u8, _index: u32, asset_type_index: u32, asset_type: u8)]
 pub struct AddAssetItemContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
     // ? trade: trade or offer account to add asset item
     // #[account(mut, seeds = [Trade::PREFIX.as_bytes()], bump)]
     #[account(mut)]
@@ -144,16 +304,125 @@ pub struct AddAssetItemContext<'info> {
     )]
     pub asset_item_spl: Option<Account<'info, AssetItemSPL>>,
 
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemNFT::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemNFT::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_nft: Option<Box<Account<'info, AssetItemNFT>>>,
+
+    // ? nft_mint: mint of the nft, required for nfts
+    pub nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    // ? nft_metadata: metaplex metadata of the nft, required for nfts
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    // ? spl_mint: mint of the tokens, of the token program or token 2022, required for spl.
+    // ? the native mint for SOL deposited from wrapped SOL
+    pub spl_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    // ? spl_token: token account of the user the tokens come from, required for spl and nfts.
+    // ? the wrapped SOL account of the user for SOL deposited from wrapped SOL
+    #[account(mut)]
+    pub spl_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    // ? spl_vault: associated token account of the trade or offer, created if needed, required
+    // ? for spl and nfts
+    #[account(mut)]
+    /// CHECK: the address is checked against the mint in runtime
+    pub spl_vault: Option<UncheckedAccount<'info>>,
+
+    // ? token_program: token program of the mint, required for spl and nfts
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // ? associated_token_program: creates the vault, required for spl and nfts
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    // ? mint_registry_entry: registry entry of the mint, required for spl and nfts unless the registry
+    // ? is open
+    /// CHECK: the address is checked against the mint in runtime, the entry may not exist
+    pub mint_registry_entry: Option<UncheckedAccount<'info>>,
+
+    // ? wsol_unwrap: pda of the asset item unwrapping the wrapped SOL, required for SOL deposited
+    // ? from wrapped SOL
+    #[account(mut)]
+    /// CHECK: the address is checked against the asset item in runtime, created and closed
+    pub wsol_unwrap: Option<UncheckedAccount<'info>>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
     // ? user: who is creating the trade
     #[account(mut)]
     pub user: Signer<'info>,
 
     // ? system_program: system program
     pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: for spl, the accounts of the transfer hook of the mint
+}
+
+// ? tokens deposited in the vault of the trade or offer
+pub(crate) struct SplEscrow<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, token_interface::Mint>,
+    pub token: &'a InterfaceAccount<'info, token_interface::TokenAccount>,
+    pub vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub extra_accounts: &'a [AccountInfo<'info>],
+}
+
+// ? wrapped SOL unwrapped into the lamport escrow of a SOL asset item
+pub(crate) struct WsolDeposit<'a, 'info> {
+    token: &'a AccountInfo<'info>,
+    mint: &'a AccountInfo<'info>,
+    unwrap: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+// ? nft deposited in the associated token account of the trade or offer
+pub(crate) struct NftEscrow<'a, 'info> {
+    pub token: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+// ? one asset item to add to trade_or_offer, the accounts of other types are none
+pub(crate) struct AssetItemDeposit<'a, 'info> {
+    pub from_key: &'a Pubkey,
+    pub from_account_info: AccountInfo<'info>,
+    pub asset_item_type: AssetItemType,
+    pub asset_index: u32,
+    pub asset_type_index: u32,
+    pub asset_type: u8,
+    pub options: &'a [u8],
+    pub user: &'a Signer<'info>,
+    pub authority: &'a Authority,
+    pub mint_registry_entry: Option<&'a AccountInfo<'info>>,
+    // trade of the offer, when strict
+    pub strict_trade: Option<&'a Trade>,
+    pub asset_item_sol: &'a mut Option<Account<'info, AssetItemSOL>>,
+    pub asset_item_spl: &'a mut Option<Account<'info, AssetItemSPL>>,
+    pub asset_item_nft: &'a mut Option<Box<Account<'info, AssetItemNFT>>>,
+    pub nft_mint: Option<&'a Account<'info, Mint>>,
+    pub nft_metadata: Option<&'a Account<'info, MetadataAccount>>,
+    pub nft_escrow: Option<NftEscrow<'a, 'info>>,
+    pub spl_escrow: Option<SplEscrow<'a, 'info>>,
+    pub wsol_deposit: Option<WsolDeposit<'a, 'info>>,
 }
 
-pub fn add_asset_item(
-    ctx: Context<AddAssetItemContext>,
+pub fn add_asset_item<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddAssetItemContext<'info>>,
     from_type: u8,
     asset_index: u32,
     asset_type_index: u32,
@@ -166,59 +435,157 @@ pub fn add_asset_item(
     msg!("Received asset_type: {}", asset_type);
     msg!("Received options: {:?}", options);
 
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
     let asset_item_type = AssetItemType::from_code(asset_type)?;
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let spl_escrow = match (
+        ctx.accounts.spl_mint.as_deref(),
+        ctx.accounts.spl_token.as_deref(),
+        ctx.accounts.spl_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.associated_token_program.as_ref(),
+    ) {
+        (Some(mint), Some(token), Some(vault), Some(token_program), Some(associated)) => {
+            Some(SplEscrow {
+                mint,
+                token,
+                vault: vault.as_ref(),
+                token_program: token_program.as_ref(),
+                associated_token_program: associated.as_ref(),
+                system_program: &system_program,
+                extra_accounts: ctx.remaining_accounts,
+            })
+        }
+        _ => None,
+    };
+    let token_info = ctx
+        .accounts
+        .spl_token
+        .as_ref()
+        .map(|token| token.to_account_info());
+    let wsol_mint = ctx
+        .accounts
+        .spl_mint
+        .as_ref()
+        .map(|mint| mint.to_account_info());
+    let wsol_deposit = match (
+        token_info.as_ref(),
+        wsol_mint.as_ref(),
+        ctx.accounts.wsol_unwrap.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    ) {
+        (Some(token), Some(mint), Some(unwrap), Some(token_program))
+            if asset_item_type == AssetItemType::SOL =>
+        {
+            Some(WsolDeposit {
+                token,
+                mint,
+                unwrap: unwrap.as_ref(),
+                token_program: token_program.as_ref(),
+                system_program: &system_program,
+            })
+        }
+        _ => None,
+    };
+    let nft_escrow = match (
+        token_info.as_ref(),
+        ctx.accounts.spl_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.associated_token_program.as_ref(),
+    ) {
+        (Some(token), Some(vault), Some(token_program), Some(associated))
+            if asset_item_type == AssetItemType::NFT =>
+        {
+            Some(NftEscrow {
+                token,
+                vault: vault.as_ref(),
+                token_program: token_program.as_ref(),
+                associated_token_program: associated.as_ref(),
+                system_program: &system_program,
+            })
+        }
+        _ => None,
+    };
 
     // ? Check if the trade is owned by the user
-    if from_type == 1 {
-        let trade: &mut Account<Trade> =
-            &mut Account::try_from(&mut ctx.accounts.trade_or_offer.to_account_info())?;
-        process_asset_item(
-            trade,
-            &trade.key(),
-            ctx.accounts.trade_or_offer.to_account_info(),
-            asset_item_type,
-            asset_index,
-            asset_type_index,
-            asset_type,
-            &options,
-            &ctx.accounts.user,
-            &mut ctx.accounts.asset_item_sol,
-            &mut ctx.accounts.asset_item_spl,
-        )?;
-    } else if from_type == 2 {
-        let offer: &mut Account<Offer> =
-            &mut Account::try_from(&mut ctx.accounts.trade_or_offer.to_account_info())?;
-        process_asset_item(
-            offer,
-            &offer.key(),
-            ctx.accounts.trade_or_offer.to_account_info(),
-            asset_item_type,
-            asset_index,
-            asset_type_index,
-            asset_type,
-            &options,
-            &ctx.accounts.user,
-            &mut ctx.accounts.asset_item_sol,
-            &mut ctx.accounts.asset_item_spl,
-        )?;
-    }
+    let from_key = ctx.accounts.trade_or_offer.key();
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer,
+        ctx.accounts.trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item(
+                trade_or_offer,
+                AssetItemDeposit {
+                    from_key: &from_key,
+                    from_account_info: ctx.accounts.trade_or_offer.to_account_info(),
+                    asset_item_type,
+                    asset_index,
+                    asset_type_index,
+                    asset_type,
+                    options: &options,
+                    user: &ctx.accounts.user,
+                    authority: &ctx.accounts.authority,
+                    mint_registry_entry: ctx
+                        .accounts
+                        .mint_registry_entry
+                        .as_ref()
+                        .map(|entry| entry.as_ref()),
+                    strict_trade,
+                    asset_item_sol: &mut ctx.accounts.asset_item_sol,
+                    asset_item_spl: &mut ctx.accounts.asset_item_spl,
+                    asset_item_nft: &mut ctx.accounts.asset_item_nft,
+                    nft_mint: ctx.accounts.nft_mint.as_deref(),
+                    nft_metadata: ctx.accounts.nft_metadata.as_deref(),
+                    nft_escrow,
+                    spl_escrow,
+                    wsol_deposit,
+                },
+            )
+        },
+    )?;
 
     Ok(())
 }
 
-fn process_asset_item<'info, T: TradeOrOffer>(
-    trade_or_offer: &mut T,
-    from_key: &Pubkey,
-    from_account_info: AccountInfo<'info>,
-    asset_item_type: AssetItemType,
-    asset_index: u32,
-    asset_type_index: u32,
-    asset_type: u8,
-    options: &[u8],
-    user: &Signer<'info>,
-    asset_item_sol: &mut Option<Account<'info, AssetItemSOL>>,
-    asset_item_spl: &mut Option<Account<'info, AssetItemSPL>>,
+// ? checks and escrows one asset item, the counters are updated on trade_or_offer and saved by
+// ? the caller
+pub(crate) fn process_asset_item(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    deposit: AssetItemDeposit,
 ) -> Result<()> {
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    let AssetItemDeposit {
+        from_key,
+        from_account_info,
+        asset_item_type,
+        asset_index,
+        asset_type_index,
+        asset_type,
+        options,
+        user,
+        authority,
+        mint_registry_entry,
+        strict_trade,
+        asset_item_sol,
+        asset_item_spl,
+        asset_item_nft,
+        nft_mint,
+        nft_metadata,
+        nft_escrow,
+        spl_escrow,
+        wsol_deposit,
+    } = deposit;
+
     match asset_item_type {
         AssetItemType::SOL => {
             if let Some(asset_item_sol) = asset_item_sol {
@@ -228,6 +595,13 @@ fn process_asset_item<'info, T: TradeOrOffer>(
                     trade_or_offer.get_sol_count() == asset_type_index,
                     SolTradeError::AddAssetItemInvalidIndex
                 );
+                // |- offers of a strict trade only hold wanted assets
+                if let Some(trade) = strict_trade {
+                    require!(
+                        trade.wants(AssetItemType::SOL, &Pubkey::default(), None),
+                        SolTradeError::AddAssetItemNotWanted
+                    );
+                }
                 msg!("asset_item_sol count: {:?}", trade_or_offer.get_sol_count());
 
                 let options: OptionsAssetItemSOL = OptionsAssetItemSOL::try_from_slice(options)
@@ -240,6 +614,26 @@ fn process_asset_item<'info, T: TradeOrOffer>(
                 );
                 msg!("user lamports : {:?}", user.to_account_info().lamports());
 
+                // * wrapped SOL is unwrapped to the user first, then deposited as lamports
+                if let Some(wsol_deposit) = wsol_deposit {
+                    let asset_item_sol_key = asset_item_sol.key();
+                    unwrap_sol(
+                        UnwrapSolAccounts {
+                            wsol_token: wsol_deposit.token,
+                            native_mint: wsol_deposit.mint,
+                            unwrap: wsol_deposit.unwrap,
+                            user: &user.to_account_info(),
+                            token_program: wsol_deposit.token_program,
+                            system_program: wsol_deposit.system_program,
+                        },
+                        options.amount,
+                        &[
+                            asset_item_sol_key.as_ref(),
+                            AssetItemSOL::WSOL_UNWRAP_PREFIX.as_bytes(),
+                        ],
+                    )?;
+                }
+
                 msg!("transfer lamport {:?}", options.amount);
                 let ix = anchor_lang::solana_program::system_instruction::transfer(
                     &user.key(),
@@ -279,8 +673,15 @@ fn process_asset_item<'info, T: TradeOrOffer>(
                     user.to_account_info().lamports()
                 );
 
+                trade_or_offer.on_asset_added(&asset_item_sol.key(), &asset_item_sol.content());
+
+                msg!("asset_item_sol increase");
+                trade_or_offer
+                    .set_sol_count(trade_or_offer.get_sol_count().checked_add(1).unwrap());
+                trade_or_offer
+                    .set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
                 msg!("asset_item_sol count: {:?}", trade_or_offer.get_sol_count());
-                trade_or_offer.ex();
             }
             Ok(())
         }
@@ -296,6 +697,78 @@ fn process_asset_item<'info, T: TradeOrOffer>(
 
                 let options: OptionsAssetItemSPL = OptionsAssetItemSPL::try_from_slice(options)
                     .map_err(|_| SolTradeError::AddAssetItemInvalidOptions)?;
+                let mint = options.mint;
+                let token = options.token;
+
+                // |- mint must pass the mint registry
+                assert_mint_allowed(authority, &mint, mint_registry_entry)?;
+                // |- offers of a strict trade only hold wanted assets
+                if let Some(trade) = strict_trade {
+                    require!(
+                        trade.wants(AssetItemType::SPL, &mint, None),
+                        SolTradeError::AddAssetItemNotWanted
+                    );
+                }
+
+                // |- the tokens come from the token account of the user, for the mint
+                let spl_escrow = spl_escrow.ok_or(SolTradeError::AddAssetItemInvalidSpl)?;
+                require!(
+                    spl_escrow.mint.key() == mint
+                        && spl_escrow.token.key() == token
+                        && *spl_escrow.mint.to_account_info().owner
+                            == spl_escrow.token_program.key(),
+                    SolTradeError::AddAssetItemInvalidSpl
+                );
+                require_keys_eq!(
+                    spl_escrow.token.mint,
+                    mint,
+                    SolTradeError::AddAssetItemSplMintMismatch
+                );
+                require_keys_eq!(
+                    spl_escrow.token.owner,
+                    user.key(),
+                    SolTradeError::AddAssetItemSplInvalidOwner
+                );
+                require_keys_eq!(
+                    spl_escrow.vault.key(),
+                    get_associated_token_address_with_program_id(
+                        from_key,
+                        &mint,
+                        &spl_escrow.token_program.key(),
+                    ),
+                    SolTradeError::AddAssetItemInvalidSpl
+                );
+                // |- token 2022 extensions the escrow can't hold
+                assert_mint_supported(&spl_escrow.mint.to_account_info())?;
+
+                // * move the tokens to the vault, the amount is what the vault received after
+                // * the transfer fees of the mint
+                associated_token::create_idempotent(CpiContext::new(
+                    spl_escrow.associated_token_program.clone(),
+                    associated_token::Create {
+                        payer: user.to_account_info(),
+                        associated_token: spl_escrow.vault.clone(),
+                        authority: from_account_info.clone(),
+                        mint: spl_escrow.mint.to_account_info(),
+                        system_program: spl_escrow.system_program.clone(),
+                        token_program: spl_escrow.token_program.clone(),
+                    },
+                ))?;
+                let vault_before = token_amount(spl_escrow.vault)?;
+                transfer_spl(
+                    spl_escrow.token_program,
+                    &spl_escrow.token.to_account_info(),
+                    &spl_escrow.mint.to_account_info(),
+                    spl_escrow.vault,
+                    &user.to_account_info(),
+                    options.amount,
+                    spl_escrow.mint.decimals,
+                    spl_escrow.extra_accounts,
+                    &[],
+                )?;
+                let received = token_amount(spl_escrow.vault)?
+                    .checked_sub(vault_before)
+                    .unwrap();
 
                 msg!("asset_item_spl init");
                 asset_item_spl.index = asset_index;
@@ -303,8 +776,16 @@ fn process_asset_item<'info, T: TradeOrOffer>(
                 asset_item_spl.from = *from_key;
                 asset_item_spl.user = user.key();
                 asset_item_spl.asset_type = asset_type;
-                asset_item_spl.token = Pubkey::from_str(&options.token).unwrap();
-                asset_item_spl.mint = Pubkey::from_str(&options.mint).unwrap();
+                asset_item_spl.token = token;
+                asset_item_spl.mint = mint;
+                asset_item_spl.amount = received;
+                asset_item_spl.decimals = spl_escrow.mint.decimals;
+                asset_item_spl.vault = spl_escrow.vault.key();
+                asset_item_spl.token_program = spl_escrow.token_program.key();
+                asset_item_spl.escrowed = true;
+                asset_item_spl.receiver = Pubkey::default();
+
+                trade_or_offer.on_asset_added(&asset_item_spl.key(), &asset_item_spl.content());
 
                 msg!("asset_item_spl increase");
                 trade_or_offer
@@ -313,11 +794,120 @@ fn process_asset_item<'info, T: TradeOrOffer>(
                     .set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
 
                 msg!("asset_item_spl count: {:?}", trade_or_offer.get_spl_count());
-                trade_or_offer.ex();
             }
 
             Ok(())
         }
+        AssetItemType::NFT => {
+            let asset_item_nft = asset_item_nft
+                .as_mut()
+                .ok_or(SolTradeError::AddAssetItemInvalidNft)?;
+            // ! check
+            // |- check if the asset item index is same as the asset type index
+            require!(
+                trade_or_offer.get_nft_count() == asset_type_index,
+                SolTradeError::AddAssetItemInvalidIndex
+            );
+            msg!("asset_item_nft count: {:?}", trade_or_offer.get_nft_count());
+
+            let options: OptionsAssetItemNFT = OptionsAssetItemNFT::try_from_slice(options)
+                .map_err(|_| SolTradeError::AddAssetItemInvalidOptions)?;
+            let token = options.token;
+
+            // |- a metaplex nft, with its own metadata
+            let nft_mint = nft_mint.ok_or(SolTradeError::AddAssetItemInvalidNft)?;
+            let nft_metadata = nft_metadata.ok_or(SolTradeError::AddAssetItemInvalidNft)?;
+            assert_nft(nft_mint, nft_metadata)?;
+            // |- mint must pass the mint registry
+            assert_mint_allowed(authority, &nft_mint.key(), mint_registry_entry)?;
+            // |- programmable nfts can't be moved by a plain transfer, they go through
+            // |- add_asset_item_pnft
+            require!(
+                !is_programmable(nft_metadata),
+                SolTradeError::PnftInvalidDeposit
+            );
+            let collection = verified_collection(nft_metadata);
+            // |- offers of a strict trade only hold wanted assets
+            if let Some(trade) = strict_trade {
+                require!(
+                    trade.wants(AssetItemType::NFT, &nft_mint.key(), collection.as_ref()),
+                    SolTradeError::AddAssetItemNotWanted
+                );
+            }
+
+            // |- the nft comes from a token account of the user holding it
+            let nft_escrow = nft_escrow.ok_or(SolTradeError::AddAssetItemInvalidNft)?;
+            require_keys_eq!(
+                nft_escrow.token.key(),
+                token,
+                SolTradeError::AddAssetItemNftInvalidToken
+            );
+            let nft_token: Account<TokenAccount> = Account::try_from(nft_escrow.token)?;
+            require!(
+                nft_token.owner == user.key()
+                    && nft_token.mint == nft_mint.key()
+                    && nft_token.amount == 1,
+                SolTradeError::AddAssetItemNftInvalidToken
+            );
+            // |- into the associated token account of the trade or offer
+            require!(
+                nft_escrow.token_program.key() == spl_token::ID
+                    && nft_escrow.vault.key()
+                        == get_associated_token_address(from_key, &nft_mint.key()),
+                SolTradeError::AddAssetItemInvalidNft
+            );
+
+            // * move the nft into the escrow of the trade or offer, the receiver claims it
+            associated_token::create_idempotent(CpiContext::new(
+                nft_escrow.associated_token_program.clone(),
+                associated_token::Create {
+                    payer: user.to_account_info(),
+                    associated_token: nft_escrow.vault.clone(),
+                    authority: from_account_info.clone(),
+                    mint: nft_mint.to_account_info(),
+                    system_program: nft_escrow.system_program.clone(),
+                    token_program: nft_escrow.token_program.clone(),
+                },
+            ))?;
+            transfer_spl(
+                nft_escrow.token_program,
+                nft_escrow.token,
+                &nft_mint.to_account_info(),
+                nft_escrow.vault,
+                &user.to_account_info(),
+                1,
+                nft_mint.decimals,
+                &[],
+                &[],
+            )?;
+
+            msg!("asset_item_nft init");
+            asset_item_nft.index = asset_index;
+            asset_item_nft.type_index = asset_type_index;
+            asset_item_nft.from = *from_key;
+            asset_item_nft.user = user.key();
+            asset_item_nft.asset_type = asset_type;
+            asset_item_nft.token = nft_escrow.vault.key();
+            asset_item_nft.mint = nft_mint.key();
+            asset_item_nft.collection = collection.unwrap_or_default();
+            asset_item_nft.seller_fee_basis_points = nft_metadata.data.seller_fee_basis_points;
+            asset_item_nft.creators = royalty_creators(nft_metadata);
+            asset_item_nft.royalty_paid = 0;
+            asset_item_nft.programmable = false;
+            asset_item_nft.escrowed = true;
+            asset_item_nft.receiver = Pubkey::default();
+
+            trade_or_offer.on_asset_added(&asset_item_nft.key(), &asset_item_nft.content());
+
+            msg!("asset_item_nft increase");
+            trade_or_offer.set_nft_count(trade_or_offer.get_nft_count().checked_add(1).unwrap());
+            trade_or_offer
+                .set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+            msg!("asset_item_nft count: {:?}", trade_or_offer.get_nft_count());
+
+            Ok(())
+        }
         _ => Err(SolTradeError::InvalidAssetItemType.into()),
     }
 }
@@ -0,0 +1,192 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{process_trade_or_offer, TradeOrOffer},
+        state::{AssetItem, AssetItemAuthority, AssetItemType, Authority, AuthorityKind, Trade},
+        utils::{
+            assert_not_paused, read_held_authority, set_held_authority, SetHeldAuthorityAccounts,
+        },
+    },
+    anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable},
+    anchor_spl::token_interface::TokenInterface,
+};
+
+#[derive(Accounts)]
+#[instruction(from_type: u8, _index: u32, asset_type_index: u32)]
+pub struct AddAssetItemAuthorityContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the authority, new holder of the authority
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemAuthority::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemAuthority::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_authority: Box<Account<'info, AssetItemAuthority>>,
+
+    // ? target: spl mint, or bpf upgradeable program
+    #[account(mut)]
+    /// CHECK: read as a mint or a program in runtime
+    pub target: UncheckedAccount<'info>,
+
+    // ? program_data: program data account of the target, required for the upgrade authority
+    #[account(mut)]
+    /// CHECK: checked against the target in runtime, updated by the upgradeable loader
+    pub program_data: Option<UncheckedAccount<'info>>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? user: current holder of the authority
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // ? token_program: token program of the mint, required for the mint and freeze authorities
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // ? bpf_loader_upgradeable_program: required for the upgrade authority
+    /// CHECK: address checked
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable_program: Option<UncheckedAccount<'info>>,
+}
+
+// ? the trade or offer holds the authority while in escrow, and hands it to the receiver once
+// ? released
+pub fn add_asset_item_authority(
+    ctx: Context<AddAssetItemAuthorityContext>,
+    from_type: u8,
+    asset_index: u32,
+    asset_type_index: u32,
+    kind: u8,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    let kind = AuthorityKind::from_code(kind)?;
+
+    // * the trade of an offer is only read, it is taken out of the accounts written by process
+    let trade = ctx.accounts.trade.take();
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer.to_account_info(),
+        trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item_authority(
+                trade_or_offer,
+                ctx.accounts,
+                strict_trade,
+                asset_index,
+                asset_type_index,
+                kind,
+            )
+        },
+    )?;
+    ctx.accounts.trade = trade;
+
+    Ok(())
+}
+
+fn process_asset_item_authority(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    accounts: &mut AddAssetItemAuthorityContext,
+    strict_trade: Option<&Trade>,
+    asset_index: u32,
+    asset_type_index: u32,
+    kind: AuthorityKind,
+) -> Result<()> {
+    let target = accounts.target.to_account_info();
+    let program_data = accounts
+        .program_data
+        .as_ref()
+        .map(|program_data| program_data.to_account_info());
+    let held_authority = read_held_authority(kind, &target, program_data.as_ref())?;
+
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    // |-asset item index must be the next authority index
+    require!(
+        trade_or_offer.get_authority_count() == asset_type_index,
+        SolTradeError::AddAssetItemInvalidIndex
+    );
+    // |-the user holds the authority
+    require!(
+        held_authority == Some(accounts.user.key()),
+        SolTradeError::AuthorityNotHeld
+    );
+    // |-offers of a strict trade only hold wanted assets
+    if let Some(trade) = strict_trade {
+        require!(
+            trade.wants(AssetItemType::AUTHORITY, &accounts.target.key(), None),
+            SolTradeError::AddAssetItemNotWanted
+        );
+    }
+
+    // * the trade or offer becomes the holder of the authority
+    let program = match kind {
+        AuthorityKind::ProgramUpgrade => accounts
+            .bpf_loader_upgradeable_program
+            .as_ref()
+            .map(|program| program.to_account_info()),
+        _ => accounts
+            .token_program
+            .as_ref()
+            .filter(|token_program| token_program.key() == *target.owner)
+            .map(|token_program| token_program.to_account_info()),
+    }
+    .ok_or(SolTradeError::AuthorityInvalidTarget)?;
+    set_held_authority(
+        kind,
+        SetHeldAuthorityAccounts {
+            target: &target,
+            program_data: program_data.as_ref(),
+            authority: &accounts.user.to_account_info(),
+            new_authority: &accounts.trade_or_offer,
+            program: &program,
+        },
+        &[],
+    )?;
+
+    let asset_item_authority = &mut accounts.asset_item_authority;
+    asset_item_authority.index = asset_index;
+    asset_item_authority.type_index = asset_type_index;
+    asset_item_authority.from = accounts.trade_or_offer.key();
+    asset_item_authority.user = accounts.user.key();
+    asset_item_authority.asset_type = AssetItemType::AUTHORITY.to_code();
+    asset_item_authority.kind = kind.to_code();
+    asset_item_authority.target = accounts.target.key();
+    asset_item_authority.escrowed = true;
+    asset_item_authority.receiver = Pubkey::default();
+
+    trade_or_offer.on_asset_added(&asset_item_authority.key(), &asset_item_authority.content());
+
+    trade_or_offer
+        .set_authority_count(trade_or_offer.get_authority_count().checked_add(1).unwrap());
+    trade_or_offer.set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+    Ok(())
+}
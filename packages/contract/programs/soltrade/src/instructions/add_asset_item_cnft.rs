@@ -0,0 +1,192 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{process_trade_or_offer, TradeOrOffer},
+        state::{AssetItem, AssetItemCNFT, AssetItemType, Authority, Trade},
+        utils::{
+            assert_mint_allowed, assert_not_paused, cnft_asset_id, transfer_cnft,
+            CnftTransferAccounts, BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            SPL_NOOP_PROGRAM_ID,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(from_type: u8, _index: u32, asset_type_index: u32)]
+pub struct AddAssetItemCnftContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the cnft, new owner of the leaf
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemCNFT::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemCNFT::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_cnft: Box<Account<'info, AssetItemCNFT>>,
+
+    // ? tree_authority: bubblegum config of the merkle tree
+    /// CHECK: checked by the bubblegum program
+    pub tree_authority: UncheckedAccount<'info>,
+
+    // ? merkle_tree: merkle tree of the leaf
+    #[account(mut)]
+    /// CHECK: checked by the account compression program
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    // ? mint_registry_entry: registry entry of the asset id, required unless the registry is open
+    /// CHECK: the address is checked against the asset id in runtime, the entry may not exist
+    pub mint_registry_entry: Option<UncheckedAccount<'info>>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? user: owner of the leaf
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: address checked
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: proof of the leaf, from the leaf to the canopy
+}
+
+// ? compressed nfts have no token account, the leaf is transferred to the trade or offer with a
+// ? proof against the current root of the tree, and claimed by the receiver once released
+#[allow(clippy::too_many_arguments)]
+pub fn add_asset_item_cnft<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddAssetItemCnftContext<'info>>,
+    from_type: u8,
+    asset_index: u32,
+    asset_type_index: u32,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    leaf_index: u32,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
+    // * fill the asset item first, the transfer reads the leaf from it
+    let asset_item_cnft = &mut ctx.accounts.asset_item_cnft;
+    asset_item_cnft.index = asset_index;
+    asset_item_cnft.type_index = asset_type_index;
+    asset_item_cnft.from = ctx.accounts.trade_or_offer.key();
+    asset_item_cnft.user = ctx.accounts.user.key();
+    asset_item_cnft.asset_type = AssetItemType::CNFT.to_code();
+    asset_item_cnft.tree = ctx.accounts.merkle_tree.key();
+    asset_item_cnft.leaf_index = leaf_index;
+    asset_item_cnft.nonce = nonce;
+    asset_item_cnft.data_hash = data_hash;
+    asset_item_cnft.creator_hash = creator_hash;
+    asset_item_cnft.escrowed = true;
+    asset_item_cnft.receiver = Pubkey::default();
+
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer,
+        ctx.accounts.trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item_cnft(trade_or_offer, &ctx, strict_trade, asset_type_index, root)
+        },
+    )?;
+
+    Ok(())
+}
+
+fn process_asset_item_cnft<'info>(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    ctx: &Context<'_, '_, '_, 'info, AddAssetItemCnftContext<'info>>,
+    strict_trade: Option<&Trade>,
+    asset_type_index: u32,
+    root: [u8; 32],
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    // |-asset item index must be the next cnft index
+    require!(
+        trade_or_offer.get_cnft_count() == asset_type_index,
+        SolTradeError::AddAssetItemInvalidIndex
+    );
+    // |-asset id must pass the mint registry
+    assert_mint_allowed(
+        &accounts.authority,
+        &cnft_asset_id(&accounts.merkle_tree.key(), accounts.asset_item_cnft.nonce),
+        accounts
+            .mint_registry_entry
+            .as_ref()
+            .map(|entry| entry.as_ref()),
+    )?;
+    // |-offers of a strict trade only hold wanted assets
+    if let Some(trade) = strict_trade {
+        require!(
+            trade.wants(AssetItemType::CNFT, &Pubkey::default(), None),
+            SolTradeError::AddAssetItemNotWanted
+        );
+    }
+
+    // * move the leaf to the trade or offer, bubblegum fails on an invalid proof
+    transfer_cnft(
+        CnftTransferAccounts {
+            tree_authority: &accounts.tree_authority.to_account_info(),
+            leaf_owner: &accounts.user.to_account_info(),
+            new_leaf_owner: &accounts.trade_or_offer,
+            merkle_tree: &accounts.merkle_tree.to_account_info(),
+            log_wrapper: &accounts.log_wrapper.to_account_info(),
+            compression_program: &accounts.compression_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+            bubblegum_program: &accounts.bubblegum_program.to_account_info(),
+        },
+        &accounts.asset_item_cnft,
+        root,
+        ctx.remaining_accounts,
+        &[],
+    )?;
+
+    trade_or_offer.on_asset_added(
+        &accounts.asset_item_cnft.key(),
+        &accounts.asset_item_cnft.content(),
+    );
+    trade_or_offer.set_cnft_count(trade_or_offer.get_cnft_count().checked_add(1).unwrap());
+    trade_or_offer.set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{process_trade_or_offer, TradeOrOffer},
+        state::{AssetItem, AssetItemCore, AssetItemType, Authority, RoyaltyCreator, Trade},
+        utils::{
+            assert_mint_allowed, assert_not_paused, read_core_asset, read_core_collection,
+            transfer_core, CoreTransferAccounts, MPL_CORE_PROGRAM_ID,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(from_type: u8, _index: u32, asset_type_index: u32)]
+pub struct AddAssetItemCoreContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the core asset, new owner of the asset
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemCore::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemCore::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_core: Box<Account<'info, AssetItemCore>>,
+
+    // ? core_asset: metaplex core asset owned by the user
+    #[account(mut)]
+    /// CHECK: read as a core asset in runtime, moved by the core program
+    pub core_asset: UncheckedAccount<'info>,
+
+    // ? core_collection: core collection of the asset, required when the asset has one
+    /// CHECK: checked against the collection of the asset in runtime
+    pub core_collection: Option<UncheckedAccount<'info>>,
+
+    // ? mint_registry_entry: registry entry of the asset id, required unless the registry is open
+    /// CHECK: the address is checked against the asset id in runtime, the entry may not exist
+    pub mint_registry_entry: Option<UncheckedAccount<'info>>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? user: owner of the core asset
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: address checked
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+// ? core assets are single accounts, they are escrowed for real by moving them to the trade or
+// ? offer with the core program, and claimed by the receiver once released
+pub fn add_asset_item_core(
+    ctx: Context<AddAssetItemCoreContext>,
+    from_type: u8,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
+    // * the trade of an offer is only read, it is taken out of the accounts written by process
+    let trade = ctx.accounts.trade.take();
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer.to_account_info(),
+        trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item_core(
+                trade_or_offer,
+                ctx.accounts,
+                strict_trade,
+                asset_index,
+                asset_type_index,
+            )
+        },
+    )?;
+    ctx.accounts.trade = trade;
+
+    Ok(())
+}
+
+fn process_asset_item_core(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    accounts: &mut AddAssetItemCoreContext,
+    strict_trade: Option<&Trade>,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    let core_asset = read_core_asset(&accounts.core_asset)?;
+    let collection_plugins = match core_asset.collection {
+        Some(collection) => {
+            let core_collection = accounts
+                .core_collection
+                .as_ref()
+                .ok_or(SolTradeError::CoreInvalidCollection)?;
+            require_keys_eq!(
+                core_collection.key(),
+                collection,
+                SolTradeError::CoreInvalidCollection
+            );
+            Some(read_core_collection(core_collection)?)
+        }
+        None => None,
+    };
+
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    // |-asset item index must be the next nft index, core assets share it with the nfts
+    require!(
+        trade_or_offer.get_nft_count() == asset_type_index,
+        SolTradeError::AddAssetItemInvalidIndex
+    );
+    // |-a core asset of the user
+    require_keys_eq!(
+        core_asset.owner,
+        accounts.user.key(),
+        SolTradeError::CoreInvalidOwner
+    );
+    // |-asset id must pass the mint registry
+    assert_mint_allowed(
+        &accounts.authority,
+        &accounts.core_asset.key(),
+        accounts
+            .mint_registry_entry
+            .as_ref()
+            .map(|entry| entry.as_ref()),
+    )?;
+    // |-not frozen, and no permanent delegate able to take it out of escrow
+    for plugins in [Some(&core_asset.plugins), collection_plugins.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        require!(!plugins.frozen, SolTradeError::CoreFrozen);
+        require!(
+            !plugins.permanent_delegate,
+            SolTradeError::CorePermanentDelegateUnsupported
+        );
+    }
+    // |-royalties of the asset override the ones of its collection
+    let (seller_fee_basis_points, creators) = core_asset
+        .plugins
+        .royalties
+        .clone()
+        .or_else(|| collection_plugins.and_then(|plugins| plugins.royalties))
+        .unwrap_or_default();
+    require!(
+        creators.len() <= RoyaltyCreator::MAX_LEN,
+        SolTradeError::CoreTooManyCreators
+    );
+    // |-offers of a strict trade only hold wanted assets
+    if let Some(trade) = strict_trade {
+        require!(
+            trade.wants(
+                AssetItemType::CORE,
+                &accounts.core_asset.key(),
+                core_asset.collection.as_ref(),
+            ),
+            SolTradeError::AddAssetItemNotWanted
+        );
+    }
+
+    // * move the asset to the trade or offer, the core program runs the plugins of the asset
+    transfer_core(
+        CoreTransferAccounts {
+            asset: &accounts.core_asset.to_account_info(),
+            collection: accounts
+                .core_collection
+                .as_ref()
+                .map(|collection| collection.as_ref()),
+            payer: &accounts.user.to_account_info(),
+            authority: &accounts.user.to_account_info(),
+            new_owner: &accounts.trade_or_offer,
+            system_program: &accounts.system_program.to_account_info(),
+            mpl_core_program: &accounts.mpl_core_program.to_account_info(),
+        },
+        &[],
+    )?;
+
+    let asset_item_core = &mut accounts.asset_item_core;
+    asset_item_core.index = asset_index;
+    asset_item_core.type_index = asset_type_index;
+    asset_item_core.from = accounts.trade_or_offer.key();
+    asset_item_core.user = accounts.user.key();
+    asset_item_core.asset_type = AssetItemType::CORE.to_code();
+    asset_item_core.asset = accounts.core_asset.key();
+    asset_item_core.collection = core_asset.collection.unwrap_or_default();
+    asset_item_core.seller_fee_basis_points = seller_fee_basis_points;
+    asset_item_core.creators = creators;
+    asset_item_core.royalty_paid = 0;
+    asset_item_core.escrowed = true;
+    asset_item_core.receiver = Pubkey::default();
+
+    trade_or_offer.on_asset_added(&asset_item_core.key(), &asset_item_core.content());
+
+    trade_or_offer.set_nft_count(trade_or_offer.get_nft_count().checked_add(1).unwrap());
+    trade_or_offer.set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{process_trade_or_offer, TradeOrOffer},
+        state::{AssetItem, AssetItemNFT, AssetItemType, Authority, Trade},
+        utils::{
+            assert_mint_allowed, assert_nft, assert_not_paused, is_programmable, royalty_creators,
+            transfer_pnft, verified_collection, PnftTransferAccounts,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::{get_associated_token_address, AssociatedToken},
+        metadata::{Metadata, MetadataAccount},
+        token::{Mint, Token, TokenAccount},
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(from_type: u8, _index: u32, asset_type_index: u32)]
+pub struct AddAssetItemPnftContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the pnft, owner of the escrow
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemNFT::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemNFT::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_nft: Box<Account<'info, AssetItemNFT>>,
+
+    // ? nft_mint: mint of the pnft
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    // ? nft_metadata: metaplex metadata of the pnft
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+
+    // ? nft_edition: master edition of the pnft
+    /// CHECK: checked by the token metadata program
+    pub nft_edition: UncheckedAccount<'info>,
+
+    // ? user_token: token account of the user holding the pnft
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = user,
+    )]
+    pub user_token: Box<Account<'info, TokenAccount>>,
+
+    // ? user_token_record: token record of the user token account
+    #[account(mut)]
+    /// CHECK: checked by the token metadata program
+    pub user_token_record: UncheckedAccount<'info>,
+
+    // ? escrow_token: associated token account of the trade or offer, created by the transfer
+    #[account(
+        mut,
+        address = get_associated_token_address(&trade_or_offer.key(), &nft_mint.key()),
+    )]
+    /// CHECK: address checked above, created by the token metadata program
+    pub escrow_token: UncheckedAccount<'info>,
+
+    // ? escrow_token_record: token record of the escrow token account
+    #[account(mut)]
+    /// CHECK: checked by the token metadata program
+    pub escrow_token_record: UncheckedAccount<'info>,
+
+    // ? authorization_rules_program: rule set program, required when the pnft has a rule set
+    /// CHECK: checked by the token metadata program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    // ? authorization_rules: rule set of the pnft, required when the pnft has a rule set
+    /// CHECK: checked by the token metadata program
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    // ? mint_registry_entry: registry entry of the mint, required unless the registry is open
+    /// CHECK: the address is checked against the mint in runtime, the entry may not exist
+    pub mint_registry_entry: Option<UncheckedAccount<'info>>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? user: who is adding the pnft
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? sysvar_instructions: instructions sysvar, read by the token metadata program
+    /// CHECK: address checked
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+// ? programmable nfts are frozen in the wallet, so they are escrowed for real in the token
+// ? account of the trade or offer, and claimed by the receiver once released
+pub fn add_asset_item_pnft(
+    ctx: Context<AddAssetItemPnftContext>,
+    from_type: u8,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
+    // * the trade of an offer is only read, it is taken out of the accounts written by process
+    let trade = ctx.accounts.trade.take();
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer.to_account_info(),
+        trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item_pnft(
+                trade_or_offer,
+                ctx.accounts,
+                strict_trade,
+                asset_index,
+                asset_type_index,
+            )
+        },
+    )?;
+    ctx.accounts.trade = trade;
+
+    Ok(())
+}
+
+fn process_asset_item_pnft(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    accounts: &mut AddAssetItemPnftContext,
+    strict_trade: Option<&Trade>,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    // |-asset item index must be the next nft index
+    require!(
+        trade_or_offer.get_nft_count() == asset_type_index,
+        SolTradeError::AddAssetItemInvalidIndex
+    );
+    // |-a programmable metaplex nft
+    assert_nft(&accounts.nft_mint, &accounts.nft_metadata)?;
+    require!(
+        is_programmable(&accounts.nft_metadata),
+        SolTradeError::PnftInvalidTokenStandard
+    );
+    // |-mint must pass the mint registry
+    assert_mint_allowed(
+        &accounts.authority,
+        &accounts.nft_mint.key(),
+        accounts
+            .mint_registry_entry
+            .as_ref()
+            .map(|entry| entry.as_ref()),
+    )?;
+    // |-offers of a strict trade only hold wanted assets
+    if let Some(trade) = strict_trade {
+        require!(
+            trade.wants(
+                AssetItemType::NFT,
+                &accounts.nft_mint.key(),
+                verified_collection(&accounts.nft_metadata).as_ref(),
+            ),
+            SolTradeError::AddAssetItemNotWanted
+        );
+    }
+
+    // * move the pnft into the escrow of the trade or offer
+    transfer_pnft(
+        PnftTransferAccounts {
+            token: &accounts.user_token.to_account_info(),
+            token_owner: &accounts.user.to_account_info(),
+            destination: &accounts.escrow_token.to_account_info(),
+            destination_owner: &accounts.trade_or_offer,
+            mint: &accounts.nft_mint.to_account_info(),
+            metadata: &accounts.nft_metadata.to_account_info(),
+            edition: &accounts.nft_edition.to_account_info(),
+            owner_token_record: &accounts.user_token_record.to_account_info(),
+            destination_token_record: &accounts.escrow_token_record.to_account_info(),
+            authority: &accounts.user.to_account_info(),
+            payer: &accounts.user.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+            sysvar_instructions: &accounts.sysvar_instructions.to_account_info(),
+            token_program: &accounts.token_program.to_account_info(),
+            associated_token_program: &accounts.associated_token_program.to_account_info(),
+            token_metadata_program: &accounts.token_metadata_program.to_account_info(),
+            authorization_rules_program: accounts
+                .authorization_rules_program
+                .as_ref()
+                .map(|program| program.as_ref()),
+            authorization_rules: accounts
+                .authorization_rules
+                .as_ref()
+                .map(|rules| rules.as_ref()),
+        },
+        &[],
+    )?;
+
+    let nft_metadata = &accounts.nft_metadata;
+    let asset_item_nft = &mut accounts.asset_item_nft;
+    asset_item_nft.index = asset_index;
+    asset_item_nft.type_index = asset_type_index;
+    asset_item_nft.from = accounts.trade_or_offer.key();
+    asset_item_nft.user = accounts.user.key();
+    asset_item_nft.asset_type = AssetItemType::NFT.to_code();
+    asset_item_nft.token = accounts.escrow_token.key();
+    asset_item_nft.mint = accounts.nft_mint.key();
+    asset_item_nft.collection = verified_collection(nft_metadata).unwrap_or_default();
+    asset_item_nft.seller_fee_basis_points = nft_metadata.data.seller_fee_basis_points;
+    asset_item_nft.creators = royalty_creators(nft_metadata);
+    asset_item_nft.royalty_paid = 0;
+    asset_item_nft.programmable = true;
+    asset_item_nft.escrowed = true;
+    asset_item_nft.receiver = Pubkey::default();
+
+    trade_or_offer.on_asset_added(&asset_item_nft.key(), &asset_item_nft.content());
+
+    trade_or_offer.set_nft_count(trade_or_offer.get_nft_count().checked_add(1).unwrap());
+    trade_or_offer.set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+    Ok(())
+}
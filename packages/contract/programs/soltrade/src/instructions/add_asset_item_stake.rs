@@ -0,0 +1,173 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{process_trade_or_offer, TradeOrOffer},
+        state::{AssetItem, AssetItemStake, AssetItemType, Authority, Trade},
+        utils::{assert_not_paused, authorize_stake, read_stake_state, stake_activation},
+    },
+    anchor_lang::{prelude::*, solana_program::stake},
+};
+
+#[derive(Accounts)]
+#[instruction(from_type: u8, _index: u32, asset_type_index: u32)]
+pub struct AddAssetItemStakeContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the stake, new staker and withdrawer
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = AssetItemStake::SIZE,
+        seeds = [
+            trade_or_offer.key().as_ref(),
+            AssetItemStake::PREFIX.as_bytes(),
+            asset_type_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub asset_item_stake: Box<Account<'info, AssetItemStake>>,
+
+    // ? stake_account: stake account of the user, staker and withdrawer
+    #[account(mut)]
+    /// CHECK: read as a stake account in runtime, updated by the stake program
+    pub stake_account: UncheckedAccount<'info>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? user: staker and withdrawer of the stake account
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: address checked
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+// ? stake accounts stay delegated in escrow, the trade or offer takes over their authorities
+// ? and hands them to the receiver once released
+pub fn add_asset_item_stake(
+    ctx: Context<AddAssetItemStakeContext>,
+    from_type: u8,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
+    // * the trade of an offer is only read, it is taken out of the accounts written by process
+    let trade = ctx.accounts.trade.take();
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer.to_account_info(),
+        trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_item_stake(
+                trade_or_offer,
+                ctx.accounts,
+                strict_trade,
+                asset_index,
+                asset_type_index,
+            )
+        },
+    )?;
+    ctx.accounts.trade = trade;
+
+    Ok(())
+}
+
+fn process_asset_item_stake(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    accounts: &mut AddAssetItemStakeContext,
+    strict_trade: Option<&Trade>,
+    asset_index: u32,
+    asset_type_index: u32,
+) -> Result<()> {
+    let stake_state = read_stake_state(&accounts.stake_account)?;
+    let meta = stake_state
+        .meta()
+        .ok_or(SolTradeError::StakeInvalidAccount)?;
+    let delegation = stake_state.delegation();
+
+    // ! @check
+    // |-assets are frozen once an offer is accepted
+    require!(
+        !trade_or_offer.is_frozen(),
+        SolTradeError::AddAssetItemFrozen
+    );
+    // |-asset item index must be the next stake index
+    require!(
+        trade_or_offer.get_stake_count() == asset_type_index,
+        SolTradeError::AddAssetItemInvalidIndex
+    );
+    // |-the user holds both authorities
+    require!(
+        meta.authorized.staker == accounts.user.key()
+            && meta.authorized.withdrawer == accounts.user.key(),
+        SolTradeError::StakeInvalidAuthority
+    );
+    // |-a locked up withdrawer can only be changed by its custodian
+    require!(
+        !meta.lockup.is_in_force(&accounts.clock, None),
+        SolTradeError::StakeLockupInForce
+    );
+    // |-offers of a strict trade only hold wanted assets
+    if let Some(trade) = strict_trade {
+        require!(
+            trade.wants(AssetItemType::STAKE, &accounts.stake_account.key(), None),
+            SolTradeError::AddAssetItemNotWanted
+        );
+    }
+
+    // * the trade or offer becomes the staker and the withdrawer
+    authorize_stake(
+        &accounts.stake_account.to_account_info(),
+        &accounts.user.to_account_info(),
+        &accounts.trade_or_offer,
+        &accounts.clock.to_account_info(),
+        &accounts.stake_program.to_account_info(),
+        &[],
+    )?;
+
+    let lamports = accounts.stake_account.to_account_info().lamports();
+    let epoch = accounts.clock.epoch;
+    let asset_item_stake = &mut accounts.asset_item_stake;
+    asset_item_stake.index = asset_index;
+    asset_item_stake.type_index = asset_type_index;
+    asset_item_stake.from = accounts.trade_or_offer.key();
+    asset_item_stake.user = accounts.user.key();
+    asset_item_stake.asset_type = AssetItemType::STAKE.to_code();
+    asset_item_stake.stake = accounts.stake_account.key();
+    asset_item_stake.voter = delegation.map_or(Pubkey::default(), |d| d.voter_pubkey);
+    asset_item_stake.delegated_amount = delegation.map_or(0, |d| d.stake);
+    asset_item_stake.activation_epoch = delegation.map_or(0, |d| d.activation_epoch);
+    asset_item_stake.deactivation_epoch = delegation.map_or(0, |d| d.deactivation_epoch);
+    asset_item_stake.activation = stake_activation(delegation.as_ref(), epoch).to_code();
+    asset_item_stake.lamports = lamports;
+    asset_item_stake.escrowed = true;
+    asset_item_stake.receiver = Pubkey::default();
+
+    trade_or_offer.on_asset_added(&asset_item_stake.key(), &asset_item_stake.content());
+
+    trade_or_offer.set_stake_count(trade_or_offer.get_stake_count().checked_add(1).unwrap());
+    trade_or_offer.set_asset_count(trade_or_offer.get_asset_count().checked_add(1).unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use {
+    crate::{
+        error::SolTradeError,
+        instructions::{
+            process_asset_item, process_trade_or_offer, AssetItemDeposit, NftEscrow, SplEscrow,
+            TradeOrOffer,
+        },
+        state::{AssetItemNFT, AssetItemSOL, AssetItemSPL, AssetItemType, Authority, Trade},
+        utils::assert_not_paused,
+    },
+    anchor_lang::{prelude::*, Discriminator},
+    anchor_spl::{
+        associated_token::AssociatedToken,
+        metadata::MetadataAccount,
+        token::{Mint, Token},
+        token_interface,
+    },
+};
+
+// ? one asset item of the batch, options are the ones of add_asset_item for the type
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AssetItemSpec {
+    pub asset_type: u8, // AssetItemType code, SOL, SPL or NFT
+    pub options: Vec<u8>,
+}
+
+// ? 10 nfts take 50 remaining accounts, the fixed accounts go in an address lookup table along
+// ? with them and the transaction stays under the 64 account locks
+pub const ADD_ASSET_ITEMS_MAX: usize = 10;
+
+#[derive(Accounts)]
+pub struct AddAssetItemsContext<'info> {
+    // ? authority: program authority, checked for the paused flag and the mint registry
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade_or_offer: trade or offer account to add the asset items
+    #[account(mut)]
+    /// CHECK: this can be trade or offer and check in runtime
+    pub trade_or_offer: AccountInfo<'info>,
+
+    // ? trade: trade of the offer, required when adding to an offer, checked for the wanted assets
+    pub trade: Option<Box<Account<'info, Trade>>>,
+
+    // ? associated_token_program: creates the vaults, required for spl and nfts
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    // ? token_program: moves the nfts to their vaults, required for nfts
+    pub token_program: Option<Program<'info, Token>>,
+
+    // ? user: owner of the asset items
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: for each asset item, in the order of the specs
+    // ? - sol: asset_item_sol
+    // ? - spl: asset_item_spl, spl_mint, spl_token, spl_vault, token_program, mint_registry_entry
+    // ? - nft: asset_item_nft, nft_mint, nft_metadata, nft_token, nft_vault, mint_registry_entry
+    // ? the asset item accounts are created here, at the next index of their type
+}
+
+// ? same as add_asset_item for every spec, all or nothing, the trade or offer is saved once.
+// ? mints with a transfer hook go through add_asset_item, their extra accounts vary
+pub fn add_asset_items<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddAssetItemsContext<'info>>,
+    from_type: u8,
+    assets: Vec<AssetItemSpec>,
+) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-at least one asset item, no more than fits in a transaction
+    require!(
+        !assets.is_empty() && assets.len() <= ADD_ASSET_ITEMS_MAX,
+        SolTradeError::AddAssetItemsInvalidCount
+    );
+
+    process_trade_or_offer(
+        from_type,
+        &ctx.accounts.trade_or_offer,
+        ctx.accounts.trade.as_deref(),
+        &ctx.accounts.user.key(),
+        |trade_or_offer, strict_trade| {
+            process_asset_items(
+                trade_or_offer,
+                ctx.accounts,
+                strict_trade,
+                ctx.remaining_accounts,
+                &assets,
+            )
+        },
+    )?;
+
+    Ok(())
+}
+
+fn process_asset_items<'info>(
+    trade_or_offer: &mut dyn TradeOrOffer,
+    accounts: &AddAssetItemsContext<'info>,
+    strict_trade: Option<&Trade>,
+    remaining_accounts: &[AccountInfo<'info>],
+    assets: &[AssetItemSpec],
+) -> Result<()> {
+    let from_key = accounts.trade_or_offer.key();
+    let user = &accounts.user;
+    let payer = user.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    let mut remaining_accounts = remaining_accounts.iter();
+    let mut next_account = || {
+        remaining_accounts
+            .next()
+            .ok_or(SolTradeError::AddAssetItemsInvalidAccounts)
+    };
+
+    for spec in assets {
+        let asset_item_type = AssetItemType::from_code(spec.asset_type)?;
+        let asset_index = trade_or_offer.get_asset_count();
+
+        match asset_item_type {
+            AssetItemType::SOL => {
+                let type_index = trade_or_offer.get_sol_count();
+                let mut asset_item_sol: Option<Account<AssetItemSOL>> = Some(init_asset_item(
+                    next_account()?,
+                    &from_key,
+                    AssetItemSOL::PREFIX,
+                    type_index,
+                    AssetItemSOL::SIZE,
+                    &payer,
+                    &system_program,
+                )?);
+                process_asset_item(
+                    trade_or_offer,
+                    AssetItemDeposit {
+                        from_key: &from_key,
+                        from_account_info: accounts.trade_or_offer.to_account_info(),
+                        asset_item_type,
+                        asset_index,
+                        asset_type_index: type_index,
+                        asset_type: spec.asset_type,
+                        options: &spec.options,
+                        user,
+                        authority: &accounts.authority,
+                        mint_registry_entry: None,
+                        strict_trade,
+                        asset_item_sol: &mut asset_item_sol,
+                        asset_item_spl: &mut None,
+                        asset_item_nft: &mut None,
+                        nft_mint: None,
+                        nft_metadata: None,
+                        nft_escrow: None,
+                        spl_escrow: None,
+                        wsol_deposit: None,
+                    },
+                )?;
+                asset_item_sol.unwrap().exit(&crate::id())?;
+            }
+            AssetItemType::SPL => {
+                let type_index = trade_or_offer.get_spl_count();
+                let mut asset_item_spl: Option<Account<AssetItemSPL>> = Some(init_asset_item(
+                    next_account()?,
+                    &from_key,
+                    AssetItemSPL::PREFIX,
+                    type_index,
+                    AssetItemSPL::SIZE,
+                    &payer,
+                    &system_program,
+                )?);
+                // ? both are owned by the token program or token 2022, checked by try_from,
+                // ? and the mint must be owned by the token program passed
+                let mint: InterfaceAccount<token_interface::Mint> =
+                    InterfaceAccount::try_from(next_account()?)?;
+                let token: InterfaceAccount<token_interface::TokenAccount> =
+                    InterfaceAccount::try_from(next_account()?)?;
+                let vault = next_account()?;
+                let token_program = next_account()?;
+                let mint_registry_entry = next_account()?;
+                let associated_token_program = accounts
+                    .associated_token_program
+                    .as_ref()
+                    .ok_or(SolTradeError::AddAssetItemInvalidSpl)?;
+                process_asset_item(
+                    trade_or_offer,
+                    AssetItemDeposit {
+                        from_key: &from_key,
+                        from_account_info: accounts.trade_or_offer.to_account_info(),
+                        asset_item_type,
+                        asset_index,
+                        asset_type_index: type_index,
+                        asset_type: spec.asset_type,
+                        options: &spec.options,
+                        user,
+                        authority: &accounts.authority,
+                        mint_registry_entry: Some(mint_registry_entry),
+                        strict_trade,
+                        asset_item_sol: &mut None,
+                        asset_item_spl: &mut asset_item_spl,
+                        asset_item_nft: &mut None,
+                        nft_mint: None,
+                        nft_metadata: None,
+                        nft_escrow: None,
+                        spl_escrow: Some(SplEscrow {
+                            mint: &mint,
+                            token: &token,
+                            vault,
+                            token_program,
+                            associated_token_program: associated_token_program.as_ref(),
+                            system_program: &system_program,
+                            extra_accounts: &[],
+                        }),
+                        wsol_deposit: None,
+                    },
+                )?;
+                asset_item_spl.unwrap().exit(&crate::id())?;
+            }
+            AssetItemType::NFT => {
+                let type_index = trade_or_offer.get_nft_count();
+                let mut asset_item_nft: Option<Box<Account<AssetItemNFT>>> =
+                    Some(Box::new(init_asset_item(
+                        next_account()?,
+                        &from_key,
+                        AssetItemNFT::PREFIX,
+                        type_index,
+                        AssetItemNFT::SIZE,
+                        &payer,
+                        &system_program,
+                    )?));
+                let nft_mint: Account<Mint> = Account::try_from(next_account()?)?;
+                let nft_metadata: Account<MetadataAccount> = Account::try_from(next_account()?)?;
+                let nft_token = next_account()?;
+                let nft_vault = next_account()?;
+                let mint_registry_entry = next_account()?;
+                let (token_program, associated_token_program) = accounts
+                    .token_program
+                    .as_ref()
+                    .zip(accounts.associated_token_program.as_ref())
+                    .ok_or(SolTradeError::AddAssetItemInvalidNft)?;
+                process_asset_item(
+                    trade_or_offer,
+                    AssetItemDeposit {
+                        from_key: &from_key,
+                        from_account_info: accounts.trade_or_offer.to_account_info(),
+                        asset_item_type,
+                        asset_index,
+                        asset_type_index: type_index,
+                        asset_type: spec.asset_type,
+                        options: &spec.options,
+                        user,
+                        authority: &accounts.authority,
+                        mint_registry_entry: Some(mint_registry_entry),
+                        strict_trade,
+                        asset_item_sol: &mut None,
+                        asset_item_spl: &mut None,
+                        asset_item_nft: &mut asset_item_nft,
+                        nft_mint: Some(&nft_mint),
+                        nft_metadata: Some(&nft_metadata),
+                        nft_escrow: Some(NftEscrow {
+                            token: nft_token,
+                            vault: nft_vault,
+                            token_program: token_program.as_ref(),
+                            associated_token_program: associated_token_program.as_ref(),
+                            system_program: &system_program,
+                        }),
+                        spl_escrow: None,
+                        wsol_deposit: None,
+                    },
+                )?;
+                asset_item_nft.unwrap().exit(&crate::id())?;
+            }
+            _ => return Err(SolTradeError::InvalidAssetItemType.into()),
+        }
+    }
+
+    Ok(())
+}
+
+// ? what #[account(init)] does, for asset item accounts only known at runtime
+fn init_asset_item<'info, T>(
+    asset_item: &AccountInfo<'info>,
+    from_key: &Pubkey,
+    prefix: &str,
+    type_index: u32,
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<Account<'info, T>>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone + Discriminator,
+{
+    let type_index = type_index.to_le_bytes();
+    let seeds: &[&[u8]] = &[from_key.as_ref(), prefix.as_bytes(), &type_index];
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::id());
+    require_keys_eq!(
+        asset_item.key(),
+        address,
+        SolTradeError::AddAssetItemsInvalidAccounts
+    );
+    let bump = [bump];
+    let mut signer = seeds.to_vec();
+    signer.push(&bump);
+
+    // * the address can be funded before, so lamports already there only have to be topped up to
+    // * the rent, then the account is allocated and assigned, the same way #[account(init)] does
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = asset_item.lamports();
+    if current_lamports == 0 {
+        anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: asset_item.clone(),
+                },
+                &[&signer],
+            ),
+            rent,
+            space as u64,
+            &crate::id(),
+        )?;
+    } else {
+        let required_lamports = rent.max(1).saturating_sub(current_lamports);
+        if required_lamports > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.clone(),
+                        to: asset_item.clone(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+        anchor_lang::system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::Allocate {
+                    account_to_allocate: asset_item.clone(),
+                },
+                &[&signer],
+            ),
+            space as u64,
+        )?;
+        anchor_lang::system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::Assign {
+                    account_to_assign: asset_item.clone(),
+                },
+                &[&signer],
+            ),
+            &crate::id(),
+        )?;
+    }
+    asset_item.try_borrow_mut_data()?[..8].copy_from_slice(&T::DISCRIMINATOR);
+    Account::try_from(asset_item)
+}
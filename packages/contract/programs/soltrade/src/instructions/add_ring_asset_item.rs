@@ -0,0 +1,236 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemType, Authority, Ring, RingAssetItem},
+        utils::{
+            assert_mint_allowed, assert_mint_supported, assert_not_paused, token_amount,
+            transfer_spl,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken},
+        token::spl_token::native_mint,
+        token_interface::{Mint, TokenAccount, TokenInterface},
+    },
+};
+
+#[derive(Accounts)]
+pub struct AddRingAssetItemContext<'info> {
+    // ? authority: program authority, checked for the paused flag and the mint registry
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? ring: ring to add the asset item to, holds the lamports of SOL
+    #[account(mut)]
+    pub ring: Box<Account<'info, Ring>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = RingAssetItem::SIZE,
+        seeds = [
+            ring.key().as_ref(),
+            RingAssetItem::PREFIX.as_bytes(),
+            ring.asset_count.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub ring_asset_item: Box<Account<'info, RingAssetItem>>,
+
+    // ======================[ SPL ONLY ]======================
+    // ? spl_mint: mint of the tokens, of the token program or token 2022
+    pub spl_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    // ? spl_token: token account of the user the tokens come from
+    #[account(mut)]
+    pub spl_token: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // ? spl_vault: associated token account of the ring, created if needed
+    #[account(mut)]
+    /// CHECK: the address is checked against the mint in runtime
+    pub spl_vault: Option<UncheckedAccount<'info>>,
+
+    // ? token_program: token program of the mint
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // ? associated_token_program: creates the vault
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    // ? mint_registry_entry: registry entry of the mint, required unless the registry is open
+    /// CHECK: the address is checked against the mint in runtime, the entry may not exist
+    pub mint_registry_entry: Option<UncheckedAccount<'info>>,
+
+    // ======================[ ALL ]======================
+    // ? user: participant escrowing the asset item
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: for spl, the accounts of the transfer hook of the mint
+}
+
+// ? every new asset item changes what the ring is worth, so every confirmation is cleared
+pub fn add_ring_asset_item<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddRingAssetItemContext<'info>>,
+    asset_type: u8,
+    recipient: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let ring = &accounts.ring;
+    let user = &accounts.user;
+    let asset_item_type = AssetItemType::from_code(asset_type)?;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&accounts.authority)?;
+    // |-ring still open
+    require!(ring.is_open(), SolTradeError::RingClosed);
+    // |-only participants escrow, for another participant
+    let user_bit = ring
+        .participant_bit(&user.key())
+        .ok_or(SolTradeError::RingNotParticipant)?;
+    require!(
+        recipient != user.key() && ring.participant_bit(&recipient).is_some(),
+        SolTradeError::RingInvalidRecipient
+    );
+    require!(amount > 0, SolTradeError::RingInvalidAmount);
+
+    let ring_key = ring.key();
+    let (mint, received, decimals, vault, token_program) = match asset_item_type {
+        AssetItemType::SOL => {
+            // * lamports are held by the ring
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &user.key(),
+                &ring_key,
+                amount,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[user.to_account_info(), accounts.ring.to_account_info()],
+            )?;
+            (
+                Pubkey::default(),
+                amount,
+                native_mint::DECIMALS,
+                Pubkey::default(),
+                Pubkey::default(),
+            )
+        }
+        AssetItemType::SPL => {
+            let (spl_mint, spl_token, spl_vault, token_program, associated_token_program) = match (
+                accounts.spl_mint.as_deref(),
+                accounts.spl_token.as_deref(),
+                accounts.spl_vault.as_ref(),
+                accounts.token_program.as_ref(),
+                accounts.associated_token_program.as_ref(),
+            ) {
+                (Some(mint), Some(token), Some(vault), Some(token_program), Some(associated)) => {
+                    (mint, token, vault, token_program, associated)
+                }
+                _ => return Err(SolTradeError::RingInvalidSpl.into()),
+            };
+
+            // ! @check
+            // |-mint must pass the mint registry
+            assert_mint_allowed(
+                &accounts.authority,
+                &spl_mint.key(),
+                accounts
+                    .mint_registry_entry
+                    .as_ref()
+                    .map(|entry| entry.as_ref()),
+            )?;
+            // |-the tokens come from the token account of the user, into the vault of the ring
+            require!(
+                *spl_mint.to_account_info().owner == token_program.key(),
+                SolTradeError::RingInvalidSpl
+            );
+            require_keys_eq!(
+                spl_token.mint,
+                spl_mint.key(),
+                SolTradeError::AddAssetItemSplMintMismatch
+            );
+            require_keys_eq!(
+                spl_token.owner,
+                user.key(),
+                SolTradeError::AddAssetItemSplInvalidOwner
+            );
+            require_keys_eq!(
+                spl_vault.key(),
+                get_associated_token_address_with_program_id(
+                    &ring_key,
+                    &spl_mint.key(),
+                    &token_program.key(),
+                ),
+                SolTradeError::RingInvalidSpl
+            );
+            // |-token 2022 extensions the escrow can't hold
+            assert_mint_supported(&spl_mint.to_account_info())?;
+
+            // * move the tokens to the vault, the amount is what the vault received after the
+            // * transfer fees of the mint
+            associated_token::create_idempotent(CpiContext::new(
+                associated_token_program.to_account_info(),
+                associated_token::Create {
+                    payer: user.to_account_info(),
+                    associated_token: spl_vault.to_account_info(),
+                    authority: accounts.ring.to_account_info(),
+                    mint: spl_mint.to_account_info(),
+                    system_program: accounts.system_program.to_account_info(),
+                    token_program: token_program.to_account_info(),
+                },
+            ))?;
+            let vault_before = token_amount(spl_vault)?;
+            transfer_spl(
+                &token_program.to_account_info(),
+                &spl_token.to_account_info(),
+                &spl_mint.to_account_info(),
+                spl_vault,
+                &user.to_account_info(),
+                amount,
+                spl_mint.decimals,
+                ctx.remaining_accounts,
+                &[],
+            )?;
+            let received = token_amount(spl_vault)?.checked_sub(vault_before).unwrap();
+            (
+                spl_mint.key(),
+                received,
+                spl_mint.decimals,
+                spl_vault.key(),
+                token_program.key(),
+            )
+        }
+        _ => return Err(SolTradeError::RingInvalidAssetType.into()),
+    };
+
+    let ring_asset_item = &mut accounts.ring_asset_item;
+    ring_asset_item.index = accounts.ring.asset_count;
+    ring_asset_item.ring = ring_key;
+    ring_asset_item.user = user.key();
+    ring_asset_item.recipient = recipient;
+    ring_asset_item.asset_type = asset_item_type.to_code();
+    ring_asset_item.mint = mint;
+    ring_asset_item.amount = received;
+    ring_asset_item.decimals = decimals;
+    ring_asset_item.vault = vault;
+    ring_asset_item.token_program = token_program;
+    ring_asset_item.escrowed = true;
+
+    let ring = &mut accounts.ring;
+    ring.asset_count = ring.asset_count.checked_add(1).unwrap();
+    ring.deposited |= user_bit;
+    ring.confirmed = 0;
+
+    Ok(())
+}
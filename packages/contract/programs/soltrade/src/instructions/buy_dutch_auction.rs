@@ -0,0 +1,206 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Trade, TradeMode, UserProfile},
+        utils::{
+            assert_allowed_user, assert_not_paused, pay_price, settle_lot, sol_payout_account,
+            sync_sol_payouts, SettleLotAccounts,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct BuyDutchAuctionContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: dutch auction to buy
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? maker: owner of the trade, gets the price
+    #[account(mut)]
+    /// CHECK: checked against the trade user in runtime
+    pub maker: AccountInfo<'info>,
+
+    // ? maker_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            trade.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub maker_profile: Account<'info, UserProfile>,
+
+    // ? user_profile: profile of the buyer, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user: buyer, pays the price and gets the lot
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+
+    // ======================[ SPL PRICE ONLY ]======================
+    // ? user_token_account: token account paying the price
+    #[account(mut)]
+    pub user_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? maker_token_account: token account of the maker, gets the price
+    #[account(mut)]
+    pub maker_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? token_program: token program, also required to pay a wrapped SOL account
+    pub token_program: Option<Program<'info, Token>>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? user_wsol_account: wrapped SOL account of the buyer, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the buyer in runtime
+    pub user_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? maker_wsol_account: wrapped SOL account of the maker, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the maker in runtime
+    pub maker_wsol_account: Option<UncheckedAccount<'info>>,
+    //
+    // ? remaining_accounts: every asset item of the trade, ordered by index, then the creators
+    // ? of its nfts when the price is in SOL
+}
+
+// ? settles right away at the current price, max_price protects the buyer from a stale price
+pub fn buy_dutch_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyDutchAuctionContext<'info>>,
+    max_price: u64,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let maker = &ctx.accounts.maker;
+    let user = &ctx.accounts.user;
+    let now = Clock::get()?.unix_timestamp;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only a running dutch auction
+    require!(
+        trade.mode == TradeMode::DutchAuction.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(trade.buyer == Pubkey::default(), SolTradeError::TradeSold);
+    require!(
+        now >= trade.start_time,
+        SolTradeError::DutchAuctionNotStarted
+    );
+    require_keys_eq!(trade.user, maker.key(), SolTradeError::AuctionUnauthorized);
+    // |-private trades only sell to their allowed users
+    assert_allowed_user(trade, &user.key())?;
+    // |-the whole lot must be passed
+    require!(
+        ctx.remaining_accounts.len() >= trade.asset_count as usize,
+        SolTradeError::LotInvalidAssetItems
+    );
+    let (lot, creators) = ctx.remaining_accounts.split_at(trade.asset_count as usize);
+    // |-price from the clock, not above what the buyer has seen
+    let price = trade.dutch_auction_price(now)?;
+    require!(price <= max_price, SolTradeError::DutchAuctionPriceAboveMax);
+    let user_payout = sol_payout_account(
+        &user.to_account_info(),
+        Some(&ctx.accounts.user_profile),
+        ctx.accounts.user_wsol_account.as_deref(),
+    )?;
+    let maker_payout = sol_payout_account(
+        maker,
+        Some(&ctx.accounts.maker_profile),
+        ctx.accounts.maker_wsol_account.as_deref(),
+    )?;
+
+    // * a price in tokens is paid before the lot moves
+    if trade.auction_mint != Pubkey::default() {
+        pay_price(
+            &trade.auction_mint,
+            price,
+            user,
+            maker,
+            ctx.accounts.user_token_account.as_deref(),
+            ctx.accounts.maker_token_account.as_deref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.system_program,
+        )?;
+    }
+
+    // * move the lot to the buyer and pay the maker, the creators of the nfts get their royalties
+    // * out of a price in SOL
+    let sol_price = if trade.auction_mint == Pubkey::default() {
+        price
+    } else {
+        0
+    };
+    let user_account_info = user.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let released_items = settle_lot(
+        SettleLotAccounts {
+            lot,
+            from: &trade.to_account_info(),
+            receiver: &user_account_info,
+            sol_receiver: &user_payout,
+            payer: &user_account_info,
+            seller: &maker_payout,
+            system_program: Some(&system_program),
+        },
+        sol_price,
+        false,
+        &mut creators.iter(),
+    )?;
+    let sol_amount = released_items.iter().fold(sol_price, |total, released| {
+        total.checked_add(released.lamports()).unwrap()
+    });
+    let token_program = ctx
+        .accounts
+        .token_program
+        .as_ref()
+        .map(|token_program| token_program.to_account_info());
+    sync_sol_payouts(
+        &[&user_payout, &maker_payout],
+        &[&trade.to_account_info()],
+        token_program.as_ref(),
+    )?;
+    trade.exchanged_count = trade.asset_count;
+    trade.buyer = user.key();
+
+    // * update the profiles of both sides.
+    // * when maker and buyer are the same wallet both accounts are the same profile,
+    // * so both copies get the same update and the last one written wins
+    ctx.accounts.user_profile.user = user.key();
+    for profile in [
+        &mut ctx.accounts.maker_profile,
+        &mut ctx.accounts.user_profile,
+    ] {
+        profile.sol_volume = profile.sol_volume.checked_add(sol_amount).unwrap();
+        profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use {
+    crate::{error::SolTradeError, state::CollectionOffer},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CancelCollectionOfferContext<'info> {
+    // ? collection_offer: offer to cancel, the lamports left go back to the user
+    #[account(mut, close = user)]
+    pub collection_offer: Account<'info, CollectionOffer>,
+
+    // ? user: owner of the collection offer
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+// ? not checked against the paused flag, so the buyer can always take the lamports back
+pub fn cancel_collection_offer(ctx: Context<CancelCollectionOfferContext>) -> Result<()> {
+    // ! @check
+    // |-only the owner can cancel
+    require_keys_eq!(
+        ctx.accounts.collection_offer.user,
+        ctx.accounts.user.key(),
+        SolTradeError::CollectionOfferUnauthorized
+    );
+
+    Ok(())
+}
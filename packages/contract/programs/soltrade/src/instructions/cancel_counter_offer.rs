@@ -0,0 +1,65 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, CounterOffer, Offer, Trade},
+        utils::{assert_not_paused, assert_offer_of_trade},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CancelCounterOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the offer
+    pub trade: Account<'info, Trade>,
+
+    // ? offer: countered offer
+    #[account(mut)]
+    pub offer: Account<'info, Offer>,
+
+    // ? counter_offer: pending counter offer, rent goes back to the maker
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            offer.key().as_ref(),
+            CounterOffer::PREFIX.as_bytes(),
+        ],
+        bump,
+    )]
+    pub counter_offer: Account<'info, CounterOffer>,
+
+    // ? user: owner of the trade
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+pub fn cancel_counter_offer(ctx: Context<CancelCounterOfferContext>) -> Result<()> {
+    let trade = &ctx.accounts.trade;
+    let offer = &mut ctx.accounts.offer;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner of the trade can withdraw its counter offer
+    require!(
+        trade.user == user.key(),
+        SolTradeError::CounterOfferUnauthorized
+    );
+    assert_offer_of_trade(offer, &trade.key())?;
+
+    // * the round stays, the offer can be accepted as it is in this round
+    offer.counter_pending = false;
+
+    Ok(())
+}
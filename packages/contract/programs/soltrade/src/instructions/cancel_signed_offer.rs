@@ -0,0 +1,37 @@
+use {crate::state::SignedOfferNonce, anchor_lang::prelude::*};
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelSignedOfferContext<'info> {
+    // ? signed_offer_nonce: nonce of the offer, once created no offer with it can be filled
+    #[account(
+        init,
+        payer = user,
+        space = SignedOfferNonce::SIZE,
+        seeds = [
+            SignedOfferNonce::PREFIX.as_bytes(),
+            user.key().as_ref(),
+            nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub signed_offer_nonce: Account<'info, SignedOfferNonce>,
+
+    // ? user: taker who signed the offer
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+// ? revokes an offer signed off-chain before it expires. not checked against the paused flag,
+// ? it moves nothing
+pub fn cancel_signed_offer(ctx: Context<CancelSignedOfferContext>, nonce: u64) -> Result<()> {
+    let signed_offer_nonce = &mut ctx.accounts.signed_offer_nonce;
+    signed_offer_nonce.user = ctx.accounts.user.key();
+    signed_offer_nonce.nonce = nonce;
+    signed_offer_nonce.trade = Pubkey::default();
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Trade, UserProfile},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CancelTradeContext<'info> {
+    // ? trade: trade to cancel
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? user_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user: owner of the trade
+    pub user: Signer<'info>,
+}
+
+// ? not checked against the paused flag, cancelling is the first step of a refund
+pub fn cancel_trade(ctx: Context<CancelTradeContext>) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let user = &ctx.accounts.user;
+    let user_profile = &mut ctx.accounts.user_profile;
+
+    // ! @check
+    // |-only the owner can cancel
+    require!(
+        trade.user == user.key(),
+        SolTradeError::CancelTradeUnauthorized
+    );
+    // |-can't cancel twice
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-can't cancel once an offer is accepted, the exchange is on its way
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::CancelTradeOfferAccepted
+    );
+    // |-can't cancel once sold
+    require!(trade.buyer == Pubkey::default(), SolTradeError::TradeSold);
+    // |-an auction with bids runs until finalize_auction
+    require!(
+        trade.best_offer == Pubkey::default(),
+        SolTradeError::AuctionHasBids
+    );
+
+    // * cancel, the assets are given back with refund_asset_item
+    trade.cancelled = true;
+    user_profile.trades_cancelled = user_profile.trades_cancelled.checked_add(1).unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemAuthority, Authority, AuthorityKind, Offer, Trade},
+        utils::{
+            assert_not_paused, assert_offer_of_trade, set_held_authority, SetHeldAuthorityAccounts,
+        },
+    },
+    anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable},
+    anchor_spl::token_interface::TokenInterface,
+};
+
+#[derive(Accounts)]
+pub struct ClaimAuthorityContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_authority: Box<Account<'info, AssetItemAuthority>>,
+
+    // ? target: spl mint or upgradeable program the authority is over
+    #[account(mut, address = asset_item_authority.target)]
+    /// CHECK: address checked above
+    pub target: UncheckedAccount<'info>,
+
+    // ? program_data: program data account of the target, required for the upgrade authority
+    #[account(mut)]
+    /// CHECK: checked against the target by the upgradeable loader
+    pub program_data: Option<UncheckedAccount<'info>>,
+
+    // ? receiver: who the asset item was released to, new holder of the authority
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a claim, the authority only goes to the receiver
+    pub user: Signer<'info>,
+
+    // ? token_program: token program of the mint, required for the mint and freeze authorities
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // ? bpf_loader_upgradeable_program: required for the upgrade authority
+    /// CHECK: address checked
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable_program: Option<UncheckedAccount<'info>>,
+}
+
+pub fn claim_authority(ctx: Context<ClaimAuthorityContext>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_authority = &accounts.asset_item_authority;
+    let trade_key = accounts.trade.key();
+    let kind = AuthorityKind::from_code(asset_item_authority.kind)?;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(
+        asset_item_authority.escrowed,
+        SolTradeError::AuthorityNotEscrowed
+    );
+    // |-asset item must be released, by a settlement or a refund
+    require!(
+        asset_item_authority.exchanged,
+        SolTradeError::AuthorityNotReleased
+    );
+    // |-authority only goes to the one it was released to
+    require_keys_eq!(
+        asset_item_authority.receiver,
+        accounts.receiver.key(),
+        SolTradeError::AuthorityInvalidReceiver
+    );
+
+    // * the trade or offer holding the authority signs the set authority
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (escrow_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_authority.from == trade_key
+    {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_authority.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    let target = accounts.target.to_account_info();
+    let program = match kind {
+        AuthorityKind::ProgramUpgrade => accounts
+            .bpf_loader_upgradeable_program
+            .as_ref()
+            .map(|program| program.to_account_info()),
+        _ => accounts
+            .token_program
+            .as_ref()
+            .filter(|token_program| token_program.key() == *target.owner)
+            .map(|token_program| token_program.to_account_info()),
+    }
+    .ok_or(SolTradeError::AuthorityInvalidTarget)?;
+    set_held_authority(
+        kind,
+        SetHeldAuthorityAccounts {
+            target: &target,
+            program_data: accounts
+                .program_data
+                .as_ref()
+                .map(|program_data| program_data.as_ref()),
+            authority: &escrow_owner,
+            new_authority: &accounts.receiver,
+            program: &program,
+        },
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_authority.escrowed = false;
+
+    Ok(())
+}
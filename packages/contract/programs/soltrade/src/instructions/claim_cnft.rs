@@ -0,0 +1,145 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemCNFT, Authority, Offer, Trade},
+        utils::{
+            assert_not_paused, assert_offer_of_trade, transfer_cnft, CnftTransferAccounts,
+            BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ClaimCnftContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_cnft: Box<Account<'info, AssetItemCNFT>>,
+
+    // ? tree_authority: bubblegum config of the merkle tree
+    /// CHECK: checked by the bubblegum program
+    pub tree_authority: UncheckedAccount<'info>,
+
+    // ? merkle_tree: merkle tree of the leaf
+    #[account(mut)]
+    /// CHECK: checked against the asset item in runtime
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    // ? receiver: who the asset item was released to
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a claim, the leaf only goes to the receiver
+    pub user: Signer<'info>,
+
+    /// CHECK: address checked
+    #[account(address = SPL_NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = SPL_ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: address checked
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: proof of the leaf, from the leaf to the canopy
+}
+
+pub fn claim_cnft<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimCnftContext<'info>>,
+    root: [u8; 32],
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_cnft = &accounts.asset_item_cnft;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(asset_item_cnft.escrowed, SolTradeError::CnftNotEscrowed);
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_cnft.exchanged, SolTradeError::CnftNotReleased);
+    // |-leaf only goes to the one it was released to
+    require_keys_eq!(
+        asset_item_cnft.receiver,
+        accounts.receiver.key(),
+        SolTradeError::CnftInvalidReceiver
+    );
+    // |-tree of the leaf
+    require_keys_eq!(
+        asset_item_cnft.tree,
+        accounts.merkle_tree.key(),
+        SolTradeError::CnftInvalidTree
+    );
+
+    // * the trade or offer owning the leaf signs the transfer
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (leaf_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_cnft.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_cnft.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    transfer_cnft(
+        CnftTransferAccounts {
+            tree_authority: &accounts.tree_authority.to_account_info(),
+            leaf_owner: &leaf_owner,
+            new_leaf_owner: &accounts.receiver,
+            merkle_tree: &accounts.merkle_tree.to_account_info(),
+            log_wrapper: &accounts.log_wrapper.to_account_info(),
+            compression_program: &accounts.compression_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+            bubblegum_program: &accounts.bubblegum_program.to_account_info(),
+        },
+        asset_item_cnft,
+        root,
+        ctx.remaining_accounts,
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_cnft.escrowed = false;
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemCore, Authority, Offer, Trade},
+        utils::{
+            assert_not_paused, assert_offer_of_trade, transfer_core, CoreTransferAccounts,
+            MPL_CORE_PROGRAM_ID,
+        },
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ClaimCoreContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_core: Box<Account<'info, AssetItemCore>>,
+
+    // ? core_asset: core asset held by the trade or offer
+    #[account(mut, address = asset_item_core.asset)]
+    /// CHECK: address checked above, moved by the core program
+    pub core_asset: UncheckedAccount<'info>,
+
+    // ? core_collection: core collection of the asset, required when the asset has one
+    /// CHECK: checked against the asset item collection in runtime
+    pub core_collection: Option<UncheckedAccount<'info>>,
+
+    // ? receiver: who the asset item was released to
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a claim, the asset only goes to the receiver
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: address checked
+    #[account(address = MPL_CORE_PROGRAM_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+pub fn claim_core(ctx: Context<ClaimCoreContext>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_core = &accounts.asset_item_core;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(asset_item_core.escrowed, SolTradeError::CoreNotEscrowed);
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_core.exchanged, SolTradeError::CoreNotReleased);
+    // |-asset only goes to the one it was released to
+    require_keys_eq!(
+        asset_item_core.receiver,
+        accounts.receiver.key(),
+        SolTradeError::CoreInvalidReceiver
+    );
+    // |-collection of the asset, the core program needs it for its plugins
+    require!(
+        accounts
+            .core_collection
+            .as_ref()
+            .map(|collection| collection.key())
+            == asset_item_core.core_collection(),
+        SolTradeError::CoreInvalidCollection
+    );
+
+    // * the trade or offer holding the asset signs the transfer
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (escrow_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_core.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_core.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    transfer_core(
+        CoreTransferAccounts {
+            asset: &accounts.core_asset.to_account_info(),
+            collection: accounts
+                .core_collection
+                .as_ref()
+                .map(|collection| collection.as_ref()),
+            payer: &accounts.user.to_account_info(),
+            authority: &escrow_owner,
+            new_owner: &accounts.receiver,
+            system_program: &accounts.system_program.to_account_info(),
+            mpl_core_program: &accounts.mpl_core_program.to_account_info(),
+        },
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_core.escrowed = false;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemNFT, Authority, Offer, Trade},
+        utils::{assert_not_paused, assert_offer_of_trade, transfer_spl},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::AssociatedToken,
+        token::{Mint, Token, TokenAccount},
+    },
+};
+
+#[derive(Accounts)]
+pub struct ClaimNftContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_nft: Box<Account<'info, AssetItemNFT>>,
+
+    // ? nft_mint: mint of the nft
+    #[account(address = asset_item_nft.mint)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    // ? escrow_token: token account of the trade or offer holding the nft
+    #[account(mut, address = asset_item_nft.token)]
+    pub escrow_token: Box<Account<'info, TokenAccount>>,
+
+    // ? receiver: who the asset item was released to
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? receiver_token: associated token account of the receiver, created if needed
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = nft_mint,
+        associated_token::authority = receiver,
+    )]
+    pub receiver_token: Box<Account<'info, TokenAccount>>,
+
+    // ? user: anyone can push a claim, the nft only goes to the receiver
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn claim_nft(ctx: Context<ClaimNftContext>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_nft = &accounts.asset_item_nft;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must be an nft still in escrow, programmable ones go through claim_pnft
+    require!(
+        !asset_item_nft.programmable && asset_item_nft.escrowed,
+        SolTradeError::NftNotEscrowed
+    );
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_nft.exchanged, SolTradeError::NftNotReleased);
+    // |-nft only goes to the one it was released to
+    require_keys_eq!(
+        asset_item_nft.receiver,
+        accounts.receiver.key(),
+        SolTradeError::NftInvalidReceiver
+    );
+
+    // * the trade or offer owning the escrow signs the transfer
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (escrow_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_nft.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_nft.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    transfer_spl(
+        &accounts.token_program.to_account_info(),
+        &accounts.escrow_token.to_account_info(),
+        &accounts.nft_mint.to_account_info(),
+        &accounts.receiver_token.to_account_info(),
+        &escrow_owner,
+        1,
+        accounts.nft_mint.decimals,
+        &[],
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_nft.escrowed = false;
+
+    Ok(())
+}
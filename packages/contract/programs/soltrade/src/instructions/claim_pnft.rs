@@ -0,0 +1,184 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemNFT, Authority, Offer, Trade},
+        utils::{assert_not_paused, assert_offer_of_trade, transfer_pnft, PnftTransferAccounts},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::{get_associated_token_address, AssociatedToken},
+        metadata::Metadata,
+        token::{Mint, Token},
+    },
+};
+
+#[derive(Accounts)]
+pub struct ClaimPnftContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_nft: Box<Account<'info, AssetItemNFT>>,
+
+    // ? nft_mint: mint of the pnft
+    #[account(address = asset_item_nft.mint)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    // ? nft_metadata: metaplex metadata of the pnft
+    #[account(mut)]
+    /// CHECK: checked by the token metadata program
+    pub nft_metadata: UncheckedAccount<'info>,
+
+    // ? nft_edition: master edition of the pnft
+    /// CHECK: checked by the token metadata program
+    pub nft_edition: UncheckedAccount<'info>,
+
+    // ? escrow_token: token account of the trade or offer holding the pnft
+    #[account(mut, address = asset_item_nft.token)]
+    /// CHECK: address checked above
+    pub escrow_token: UncheckedAccount<'info>,
+
+    // ? escrow_token_record: token record of the escrow token account
+    #[account(mut)]
+    /// CHECK: checked by the token metadata program
+    pub escrow_token_record: UncheckedAccount<'info>,
+
+    // ? receiver: who the asset item was released to
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? receiver_token: associated token account of the receiver, created by the transfer
+    #[account(
+        mut,
+        address = get_associated_token_address(&receiver.key(), &nft_mint.key()),
+    )]
+    /// CHECK: address checked above, created by the token metadata program
+    pub receiver_token: UncheckedAccount<'info>,
+
+    // ? receiver_token_record: token record of the receiver token account
+    #[account(mut)]
+    /// CHECK: checked by the token metadata program
+    pub receiver_token_record: UncheckedAccount<'info>,
+
+    // ? authorization_rules_program: rule set program, required when the pnft has a rule set
+    /// CHECK: checked by the token metadata program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    // ? authorization_rules: rule set of the pnft, required when the pnft has a rule set
+    /// CHECK: checked by the token metadata program
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    // ? user: anyone can push a claim, the pnft only goes to the receiver
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? sysvar_instructions: instructions sysvar, read by the token metadata program
+    /// CHECK: address checked
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+pub fn claim_pnft(ctx: Context<ClaimPnftContext>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_nft = &accounts.asset_item_nft;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must be a pnft still in escrow
+    require!(
+        asset_item_nft.programmable && asset_item_nft.escrowed,
+        SolTradeError::PnftNotEscrowed
+    );
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_nft.exchanged, SolTradeError::PnftNotReleased);
+    // |-pnft only goes to the one it was released to
+    require_keys_eq!(
+        asset_item_nft.receiver,
+        accounts.receiver.key(),
+        SolTradeError::PnftInvalidReceiver
+    );
+
+    // * the trade or offer holding the escrow signs the transfer
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (escrow_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_nft.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_nft.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    transfer_pnft(
+        PnftTransferAccounts {
+            token: &accounts.escrow_token.to_account_info(),
+            token_owner: &escrow_owner,
+            destination: &accounts.receiver_token.to_account_info(),
+            destination_owner: &accounts.receiver,
+            mint: &accounts.nft_mint.to_account_info(),
+            metadata: &accounts.nft_metadata.to_account_info(),
+            edition: &accounts.nft_edition.to_account_info(),
+            owner_token_record: &accounts.escrow_token_record.to_account_info(),
+            destination_token_record: &accounts.receiver_token_record.to_account_info(),
+            authority: &escrow_owner,
+            payer: &accounts.user.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+            sysvar_instructions: &accounts.sysvar_instructions.to_account_info(),
+            token_program: &accounts.token_program.to_account_info(),
+            associated_token_program: &accounts.associated_token_program.to_account_info(),
+            token_metadata_program: &accounts.token_metadata_program.to_account_info(),
+            authorization_rules_program: accounts
+                .authorization_rules_program
+                .as_ref()
+                .map(|program| program.as_ref()),
+            authorization_rules: accounts
+                .authorization_rules
+                .as_ref()
+                .map(|rules| rules.as_ref()),
+        },
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_nft.escrowed = false;
+
+    Ok(())
+}
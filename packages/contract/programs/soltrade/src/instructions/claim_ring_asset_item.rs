@@ -0,0 +1,132 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemType, Authority, Ring, RingAssetItem},
+        utils::{assert_not_paused, transfer_spl},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct ClaimRingAssetItemContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? ring: ring of the asset item, holds the lamports of SOL
+    #[account(mut)]
+    pub ring: Account<'info, Ring>,
+
+    #[account(mut, has_one = ring)]
+    pub ring_asset_item: Box<Account<'info, RingAssetItem>>,
+
+    // ? receiver: recipient of the asset item once the ring is settled, its owner once cancelled
+    #[account(mut)]
+    /// CHECK: checked against the asset item in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a claim, the asset item only goes to the receiver
+    pub user: Signer<'info>,
+
+    // ======================[ SPL ONLY ]======================
+    // ? spl_mint: mint of the tokens
+    #[account(address = ring_asset_item.mint)]
+    pub spl_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    // ? spl_vault: token account of the ring holding the tokens
+    #[account(mut, address = ring_asset_item.vault)]
+    pub spl_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // ? receiver_token: token account of the receiver, for the mint
+    #[account(mut)]
+    pub receiver_token: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(address = ring_asset_item.token_program)]
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    //
+    // ? remaining_accounts: for spl, the accounts of the transfer hook of the mint
+}
+
+pub fn claim_ring_asset_item<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimRingAssetItemContext<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let ring = &accounts.ring;
+    let ring_asset_item = &accounts.ring_asset_item;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(ring_asset_item.escrowed, SolTradeError::RingNotEscrowed);
+    // |-ring must be settled or cancelled, the asset item only goes to its receiver
+    let receiver = ring_asset_item
+        .receiver(ring)
+        .ok_or(SolTradeError::RingNotReleased)?;
+    require_keys_eq!(
+        receiver,
+        accounts.receiver.key(),
+        SolTradeError::RingInvalidReceiver
+    );
+
+    match AssetItemType::from_code(ring_asset_item.asset_type)? {
+        AssetItemType::SOL => {
+            // * lamports held by the ring
+            **accounts.ring.to_account_info().try_borrow_mut_lamports()? -= ring_asset_item.amount;
+            **accounts.receiver.try_borrow_mut_lamports()? += ring_asset_item.amount;
+        }
+        AssetItemType::SPL => {
+            let (spl_mint, spl_vault, receiver_token, token_program) = match (
+                accounts.spl_mint.as_deref(),
+                accounts.spl_vault.as_deref(),
+                accounts.receiver_token.as_deref(),
+                accounts.token_program.as_ref(),
+            ) {
+                (Some(mint), Some(vault), Some(receiver_token), Some(token_program)) => {
+                    (mint, vault, receiver_token, token_program)
+                }
+                _ => return Err(SolTradeError::RingInvalidSpl.into()),
+            };
+            require!(
+                receiver_token.owner == receiver && receiver_token.mint == ring_asset_item.mint,
+                SolTradeError::RingInvalidReceiver
+            );
+
+            // * the ring owning the vault signs the transfer
+            let ring_index = ring.index.to_le_bytes();
+            let (_, bump) = Pubkey::find_program_address(
+                &[Ring::PREFIX.as_bytes(), ring.user.as_ref(), &ring_index],
+                &crate::id(),
+            );
+            let signer: &[&[u8]] = &[
+                Ring::PREFIX.as_bytes(),
+                ring.user.as_ref(),
+                &ring_index,
+                &[bump],
+            ];
+            transfer_spl(
+                &token_program.to_account_info(),
+                &spl_vault.to_account_info(),
+                &spl_mint.to_account_info(),
+                &receiver_token.to_account_info(),
+                &ring.to_account_info(),
+                ring_asset_item.amount,
+                spl_mint.decimals,
+                ctx.remaining_accounts,
+                &[signer],
+            )?;
+        }
+        _ => return Err(SolTradeError::RingInvalidAssetType.into()),
+    }
+
+    ctx.accounts.ring_asset_item.escrowed = false;
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemSPL, Authority, Offer, Trade},
+        utils::{assert_not_paused, assert_offer_of_trade, transfer_spl},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct ClaimSplContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_spl: Box<Account<'info, AssetItemSPL>>,
+
+    // ? spl_mint: mint of the tokens
+    #[account(address = asset_item_spl.mint)]
+    pub spl_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // ? spl_vault: token account of the trade or offer holding the tokens
+    #[account(mut, address = asset_item_spl.vault)]
+    pub spl_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // ? receiver_token: token account of the receiver, for the mint
+    #[account(mut)]
+    pub receiver_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // ? user: anyone can push a claim, the tokens only go to the receiver
+    pub user: Signer<'info>,
+
+    #[account(address = asset_item_spl.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
+    //
+    // ? remaining_accounts: the accounts of the transfer hook of the mint
+}
+
+pub fn claim_spl<'info>(ctx: Context<'_, '_, '_, 'info, ClaimSplContext<'info>>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_spl = &accounts.asset_item_spl;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(asset_item_spl.escrowed, SolTradeError::SplNotEscrowed);
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_spl.exchanged, SolTradeError::SplNotReleased);
+    // |-tokens only go to the one they were released to
+    require!(
+        accounts.receiver_token.owner == asset_item_spl.receiver
+            && accounts.receiver_token.mint == asset_item_spl.mint,
+        SolTradeError::SplInvalidReceiver
+    );
+
+    // * the trade or offer owning the vault signs the transfer
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (vault_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_spl.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_spl.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    // * the amount is the one the vault received, a transfer fee of the claim is withheld
+    // * from the receiver
+    transfer_spl(
+        &accounts.token_program.to_account_info(),
+        &accounts.spl_vault.to_account_info(),
+        &accounts.spl_mint.to_account_info(),
+        &accounts.receiver_token.to_account_info(),
+        &vault_owner,
+        asset_item_spl.amount,
+        accounts.spl_mint.decimals,
+        ctx.remaining_accounts,
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_spl.escrowed = false;
+
+    Ok(())
+}
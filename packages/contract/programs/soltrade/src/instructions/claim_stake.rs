@@ -0,0 +1,111 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemStake, Authority, Offer, Trade},
+        utils::{assert_not_paused, assert_offer_of_trade, authorize_stake},
+    },
+    anchor_lang::{prelude::*, solana_program::stake},
+};
+
+#[derive(Accounts)]
+pub struct ClaimStakeContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the asset item, or trade of the offer
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: offer of the asset item, only when claiming from an offer
+    pub offer: Option<Box<Account<'info, Offer>>>,
+
+    #[account(mut)]
+    pub asset_item_stake: Box<Account<'info, AssetItemStake>>,
+
+    // ? stake_account: stake account held by the trade or offer
+    #[account(mut, address = asset_item_stake.stake)]
+    /// CHECK: address checked above, updated by the stake program
+    pub stake_account: UncheckedAccount<'info>,
+
+    // ? receiver: who the asset item was released to, new staker and withdrawer
+    /// CHECK: checked against the asset item receiver in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a claim, the authorities only go to the receiver
+    pub user: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: address checked
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+pub fn claim_stake(ctx: Context<ClaimStakeContext>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let asset_item_stake = &accounts.asset_item_stake;
+    let trade_key = accounts.trade.key();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-asset item must still be in escrow
+    require!(asset_item_stake.escrowed, SolTradeError::StakeNotEscrowed);
+    // |-asset item must be released, by a settlement or a refund
+    require!(asset_item_stake.exchanged, SolTradeError::StakeNotReleased);
+    // |-authorities only go to the one it was released to
+    require_keys_eq!(
+        asset_item_stake.receiver,
+        accounts.receiver.key(),
+        SolTradeError::StakeInvalidReceiver
+    );
+
+    // * the trade or offer holding the authorities signs the authorize
+    let trade_index = accounts.trade.index.to_le_bytes();
+    let offer_index;
+    let (escrow_owner, seeds): (AccountInfo, Vec<&[u8]>) = if asset_item_stake.from == trade_key {
+        (
+            accounts.trade.to_account_info(),
+            vec![Trade::PREFIX.as_bytes(), &trade_index],
+        )
+    } else {
+        let offer = accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade_key)?;
+        require_keys_eq!(
+            asset_item_stake.from,
+            offer.key(),
+            SolTradeError::RefundAssetItemInvalidFrom
+        );
+        offer_index = offer.index.to_le_bytes();
+        (
+            offer.to_account_info(),
+            vec![trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        )
+    };
+    let (_, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+    let bump = [bump];
+    let mut signer = seeds.clone();
+    signer.push(&bump);
+
+    authorize_stake(
+        &accounts.stake_account.to_account_info(),
+        &escrow_owner,
+        &accounts.receiver,
+        &accounts.clock.to_account_info(),
+        &accounts.stake_program.to_account_info(),
+        &[&signer],
+    )?;
+
+    ctx.accounts.asset_item_stake.escrowed = false;
+
+    Ok(())
+}
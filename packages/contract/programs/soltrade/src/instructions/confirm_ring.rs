@@ -0,0 +1,56 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Ring},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ConfirmRingContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? ring: ring to confirm
+    #[account(mut)]
+    pub ring: Account<'info, Ring>,
+
+    // ? user: participant confirming
+    pub user: Signer<'info>,
+}
+
+// ? the last confirmation settles the ring, every asset item then goes to its recipient with
+// ? claim_ring_asset_item
+pub fn confirm_ring(ctx: Context<ConfirmRingContext>) -> Result<()> {
+    let ring = &mut ctx.accounts.ring;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-ring still open
+    require!(ring.is_open(), SolTradeError::RingClosed);
+    // |-only participants confirm, once they escrowed something
+    let user_bit = ring
+        .participant_bit(&ctx.accounts.user.key())
+        .ok_or(SolTradeError::RingNotParticipant)?;
+    require!(
+        ring.deposited & user_bit != 0,
+        SolTradeError::RingNothingDeposited
+    );
+
+    // * confirm, settle once everyone did
+    ring.confirmed |= user_bit;
+    if ring.confirmed == ring.all_participants() {
+        ring.settled = true;
+    }
+
+    Ok(())
+}
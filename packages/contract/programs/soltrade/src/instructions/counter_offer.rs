@@ -0,0 +1,119 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, CounterOffer, Offer, Trade, TradeMode, WantedAsset},
+        utils::{assert_not_paused, assert_offer_of_trade},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CounterOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade of the offer
+    pub trade: Account<'info, Trade>,
+
+    // ? offer: offer to counter
+    #[account(mut)]
+    pub offer: Account<'info, Offer>,
+
+    // ? counter_offer: requested modification, replaced by a newer counter offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            offer.key().as_ref(),
+            CounterOffer::PREFIX.as_bytes(),
+        ],
+        space = CounterOffer::SIZE,
+        bump,
+    )]
+    pub counter_offer: Account<'info, CounterOffer>,
+
+    // ? user: owner of the trade
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn counter_offer(
+    ctx: Context<CounterOfferContext>,
+    sol_amount: u64,
+    drop_asset_items: Vec<Pubkey>,
+    requested_assets: Vec<WantedAsset>,
+) -> Result<()> {
+    let trade = &ctx.accounts.trade;
+    let offer = &mut ctx.accounts.offer;
+    let counter_offer = &mut ctx.accounts.counter_offer;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner of the trade can counter
+    require!(
+        trade.user == user.key(),
+        SolTradeError::CounterOfferUnauthorized
+    );
+    // |-offer must belong to an open trade
+    assert_offer_of_trade(offer, &trade.key())?;
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    // |-something must change
+    require!(
+        sol_amount > 0 || !drop_asset_items.is_empty() || !requested_assets.is_empty(),
+        SolTradeError::CounterOfferEmpty
+    );
+    require!(
+        drop_asset_items.len() <= CounterOffer::MAX_DROP_ASSET_ITEMS,
+        SolTradeError::CounterOfferTooManyDropAssetItems
+    );
+    // |-each asset item is dropped once
+    for (i, drop_asset_item) in drop_asset_items.iter().enumerate() {
+        require!(
+            !drop_asset_items[..i].contains(drop_asset_item),
+            SolTradeError::CounterOfferDuplicateDropAssetItem
+        );
+    }
+    // |-requested assets max 5, each one complete
+    require!(
+        requested_assets.len() <= WantedAsset::MAX_LEN,
+        SolTradeError::CounterOfferTooManyRequestedAssets
+    );
+    for requested_asset in requested_assets.iter() {
+        require!(
+            requested_asset.is_complete()?,
+            SolTradeError::CounterOfferInvalidRequestedAsset
+        );
+    }
+
+    // * every counter offer opens a new round, so the taker accepts exactly this one
+    offer.round = offer.round.checked_add(1).unwrap();
+    offer.counter_pending = true;
+
+    counter_offer.offer = offer.key();
+    counter_offer.round = offer.round;
+    counter_offer.sol_amount = sol_amount;
+    counter_offer.drop_asset_items = drop_asset_items;
+    counter_offer.requested_assets = requested_assets;
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, CollectionOffer, UserProfile},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CreateCollectionOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user_profile: profile of the user, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? collection_offer: account to create, escrows the lamports of every wanted nft
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            CollectionOffer::PREFIX.as_bytes(),
+            user.key().as_ref(),
+            user_profile.collection_offer_count.to_le_bytes().as_ref(),
+        ],
+        space = CollectionOffer::SIZE,
+        bump,
+    )]
+    pub collection_offer: Account<'info, CollectionOffer>,
+
+    // ? user: buyer
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_collection_offer(
+    ctx: Context<CreateCollectionOfferContext>,
+    collection: Pubkey,
+    price: u64,
+    quantity: u32,
+) -> Result<()> {
+    let collection_offer = &mut ctx.accounts.collection_offer;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-something to bid on
+    require!(
+        collection != Pubkey::default() && price > 0 && quantity > 0,
+        SolTradeError::CollectionOfferInvalidParams
+    );
+
+    // * escrow the lamports of every nft
+    let total = price
+        .checked_mul(quantity as u64)
+        .ok_or(SolTradeError::CollectionOfferInvalidParams)?;
+    let ix = anchor_lang::solana_program::system_instruction::transfer(
+        &user.key(),
+        &collection_offer.key(),
+        total,
+    );
+    anchor_lang::solana_program::program::invoke(
+        &ix,
+        &[user.to_account_info(), collection_offer.to_account_info()],
+    )?;
+
+    collection_offer.index = user_profile.collection_offer_count;
+    collection_offer.user = user.key();
+    collection_offer.collection = collection;
+    collection_offer.price = price;
+    collection_offer.quantity = quantity;
+    collection_offer.filled = 0;
+    collection_offer.royalty_paid = 0;
+
+    user_profile.user = user.key();
+    user_profile.collection_offer_count =
+        user_profile.collection_offer_count.checked_add(1).unwrap();
+
+    Ok(())
+}
@@ -1,14 +1,26 @@
 use {
     crate::{
         error::SolTradeError,
-        state::{Offer, Trade},
+        state::{Authority, Offer, Trade, TradeMode, UserOffer, UserProfile},
+        utils::{assert_not_paused, release_bid},
     },
     anchor_lang::prelude::*,
+    anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer},
 };
 
 #[derive(Accounts)]
 #[instruction(index: u32)]
 pub struct CreateOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
     // ? offer: account to create
     #[account(
         init,
@@ -28,23 +40,88 @@ pub struct CreateOfferContext<'info> {
     #[account(mut)]
     pub trade: Account<'info, Trade>,
 
+    // ? user_profile: profile of the user, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user_offer: link from the user nonce to the offer, to enumerate offers of the user
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            UserOffer::PREFIX.as_bytes(),
+            user.key().as_ref(),
+            user_profile.offer_count.to_le_bytes().as_ref(),
+        ],
+        space = UserOffer::SIZE,
+        bump,
+    )]
+    pub user_offer: Account<'info, UserOffer>,
+
     // ? user: who is creating the offer
     #[account(mut)]
     pub user: Signer<'info>,
 
     // ? system_program: system program
     pub system_program: Program<'info, System>,
+
+    // ======================[ AUCTION ONLY ]======================
+    // ? previous_offer: current best offer, outbid and refunded by this offer when bids are in SOL
+    #[account(mut)]
+    pub previous_offer: Option<Account<'info, Offer>>,
+
+    // ? previous_user: owner of the previous offer
+    #[account(mut)]
+    /// CHECK: checked against the previous offer user in runtime
+    pub previous_user: Option<UncheckedAccount<'info>>,
+
+    // ? bid_mint: mint of the bids, when the auction is not in SOL
+    pub bid_mint: Option<Box<Account<'info, Mint>>>,
+
+    // ? user_token_account: token account paying the bid
+    #[account(mut)]
+    pub user_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? bid_vault: escrow of the bid, owned by the offer
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            offer.key().as_ref(),
+            Offer::BID_VAULT_PREFIX.as_bytes(),
+        ],
+        bump,
+        token::mint = bid_mint,
+        token::authority = offer,
+    )]
+    pub bid_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? token_program: token program
+    pub token_program: Option<Program<'info, Token>>,
 }
 
-pub fn create_offer(ctx: Context<CreateOfferContext>, index: u32) -> Result<()> {
+pub fn create_offer(ctx: Context<CreateOfferContext>, index: u32, bid_amount: u64) -> Result<()> {
     let offer = &mut ctx.accounts.offer;
     let user = &mut ctx.accounts.user;
     let trade = &mut ctx.accounts.trade;
 
     // ! @check
-    // |-index should be same as current asset count in trade
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-trade must not be cancelled
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-index should be same as current offer count in trade
     require!(
-        offer.asset_count == index,
+        trade.offer_count == index,
         SolTradeError::CreateOfferInvalidIndex
     );
 
@@ -55,11 +132,150 @@ pub fn create_offer(ctx: Context<CreateOfferContext>, index: u32) -> Result<()>
     offer.enabled = false;
     offer.asset_sol_count = 0;
     offer.asset_spl_count = 0;
+    offer.asset_nft_count = 0;
+    offer.asset_cnft_count = 0;
+    offer.asset_stake_count = 0;
+    offer.asset_authority_count = 0;
     offer.exchanged_count = 0;
     offer.accepted = false;
+    offer.round = 0;
+    offer.counter_pending = false;
+    offer.content_hash = [0; 32];
+    offer.bid_amount = 0;
+    offer.bid_refunded = false;
 
     // * increase offer count
     trade.offer_count = trade.offer_count.checked_add(1).unwrap();
 
+    // * auction bid
+    match TradeMode::from_code(trade.mode)? {
+        TradeMode::Offer => require!(bid_amount == 0, SolTradeError::AuctionUnexpectedBid),
+        TradeMode::Auction => process_bid(ctx.accounts, bid_amount)?,
+        // ? dutch auctions and fixed price listings are bought right away, without offers
+        TradeMode::DutchAuction | TradeMode::FixedPrice => {
+            return Err(SolTradeError::TradeInvalidMode.into())
+        }
+    }
+
+    // * link the offer to the user profile
+    let accounts = ctx.accounts;
+    accounts.user_profile.user = accounts.user.key();
+    accounts.user_offer.trade = accounts.trade.key();
+    accounts.user_offer.offer = accounts.offer.key();
+    accounts.user_profile.offer_count = accounts.user_profile.offer_count.checked_add(1).unwrap();
+
+    Ok(())
+}
+
+fn process_bid(accounts: &mut CreateOfferContext, bid_amount: u64) -> Result<()> {
+    let trade = &mut accounts.trade;
+    let offer = &mut accounts.offer;
+    let user = &accounts.user;
+
+    // ! @check
+    // |-auction still running
+    require!(
+        Clock::get()?.unix_timestamp < trade.end_time,
+        SolTradeError::AuctionEnded
+    );
+    // |-bid above the reserve price, and above the best bid plus the minimum increment
+    require!(
+        bid_amount > 0 && bid_amount >= trade.reserve_price,
+        SolTradeError::AuctionBidTooLow
+    );
+    if trade.best_offer != Pubkey::default() {
+        require!(
+            bid_amount > trade.best_bid
+                && bid_amount >= trade.best_bid.checked_add(trade.min_increment).unwrap(),
+            SolTradeError::AuctionBidTooLow
+        );
+    }
+
+    // * escrow the bid, lamports in the offer account or tokens in the bid vault
+    if trade.auction_mint == Pubkey::default() {
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &user.key(),
+            &offer.key(),
+            bid_amount,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[user.to_account_info(), offer.to_account_info()],
+        )?;
+    } else {
+        let bid_mint = accounts
+            .bid_mint
+            .as_ref()
+            .ok_or(SolTradeError::AuctionInvalidBidAccounts)?;
+        let user_token_account = accounts
+            .user_token_account
+            .as_ref()
+            .ok_or(SolTradeError::AuctionInvalidBidAccounts)?;
+        let bid_vault = accounts
+            .bid_vault
+            .as_ref()
+            .ok_or(SolTradeError::AuctionInvalidBidAccounts)?;
+        let token_program = accounts
+            .token_program
+            .as_ref()
+            .ok_or(SolTradeError::AuctionInvalidBidAccounts)?;
+        require_keys_eq!(
+            bid_mint.key(),
+            trade.auction_mint,
+            SolTradeError::AuctionInvalidBidAccounts
+        );
+
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: user_token_account.to_account_info(),
+                    to: bid_vault.to_account_info(),
+                    authority: user.to_account_info(),
+                },
+            ),
+            bid_amount,
+        )?;
+    }
+    offer.bid_amount = bid_amount;
+
+    // * refund the outbid offer. tokens are taken back with withdraw_bid, a token account of the
+    // * outbid user could be closed or frozen and block every later bid
+    if trade.best_offer != Pubkey::default() && trade.auction_mint == Pubkey::default() {
+        let previous_offer = accounts
+            .previous_offer
+            .as_mut()
+            .ok_or(SolTradeError::AuctionInvalidPreviousBidAccounts)?;
+        let previous_user = accounts
+            .previous_user
+            .as_ref()
+            .ok_or(SolTradeError::AuctionInvalidPreviousBidAccounts)?;
+        require_keys_eq!(
+            previous_offer.key(),
+            trade.best_offer,
+            SolTradeError::AuctionInvalidPreviousBidAccounts
+        );
+        require_keys_eq!(
+            previous_offer.user,
+            previous_user.key(),
+            SolTradeError::AuctionInvalidPreviousBidAccounts
+        );
+
+        release_bid(
+            trade,
+            previous_offer,
+            &previous_user.to_account_info(),
+            None,
+            None,
+            None,
+            SolTradeError::AuctionInvalidPreviousBidAccounts,
+        )?;
+        previous_offer.bid_refunded = true;
+    }
+
+    // * this offer leads now
+    trade.best_offer = offer.key();
+    trade.best_bid = bid_amount;
+
     Ok(())
 }
@@ -0,0 +1,94 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Ring, UserProfile},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CreateRingContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user_profile: profile of the user, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? ring: account to create, holds the lamports of the SOL asset items
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            Ring::PREFIX.as_bytes(),
+            user.key().as_ref(),
+            user_profile.ring_count.to_le_bytes().as_ref(),
+        ],
+        space = Ring::SIZE,
+        bump,
+    )]
+    pub ring: Account<'info, Ring>,
+
+    // ? user: creator, one of the participants
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+// ? the participants are fixed once the ring is created, each one then escrows its asset items
+// ? with add_ring_asset_item and confirms with confirm_ring
+pub fn create_ring(ctx: Context<CreateRingContext>, participants: Vec<Pubkey>) -> Result<()> {
+    let ring = &mut ctx.accounts.ring;
+    let user_profile = &mut ctx.accounts.user_profile;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-3 to 8 participants, the creator one of them
+    require!(
+        (Ring::MIN_PARTICIPANTS..=Ring::MAX_PARTICIPANTS).contains(&participants.len())
+            && participants.contains(&user.key()),
+        SolTradeError::RingInvalidParticipants
+    );
+    // |-each participant once
+    for (position, participant) in participants.iter().enumerate() {
+        require!(
+            *participant != Pubkey::default() && !participants[..position].contains(participant),
+            SolTradeError::RingInvalidParticipants
+        );
+    }
+
+    ring.index = user_profile.ring_count;
+    ring.user = user.key();
+    ring.participants = participants;
+    ring.deposited = 0;
+    ring.confirmed = 0;
+    ring.asset_count = 0;
+    ring.settled = false;
+    ring.cancelled = false;
+
+    user_profile.user = user.key();
+    user_profile.ring_count = user_profile.ring_count.checked_add(1).unwrap();
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 use {
     crate::{
         error::SolTradeError,
-        state::{Authority, Trade},
+        state::{
+            Authority, AutoAcceptRule, DutchAuctionDecay, Trade, TradeMode, UserProfile, UserTrade,
+            WantedAsset,
+        },
+        utils::assert_not_paused,
     },
     anchor_lang::prelude::*,
 };
@@ -33,6 +37,33 @@ pub struct CreateTradeContext<'info> {
     )]
     pub trade: Account<'info, Trade>,
 
+    // ? user_profile: profile of the user, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user_trade: link from the user nonce to the trade, to enumerate trades of the user
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            UserTrade::PREFIX.as_bytes(),
+            user.key().as_ref(),
+            user_profile.trade_count.to_le_bytes().as_ref(),
+        ],
+        space = UserTrade::SIZE,
+        bump,
+    )]
+    pub user_trade: Account<'info, UserTrade>,
+
     // ? user: who is creating the trade
     #[account(mut)]
     pub user: Signer<'info>,
@@ -45,12 +76,16 @@ pub fn create_trade(
     ctx: Context<CreateTradeContext>,
     trade_index: u32,
     allowed_users: Vec<Pubkey>,
+    wanted_assets: Vec<WantedAsset>,
+    strict: bool,
 ) -> Result<()> {
     let authority = &mut ctx.accounts.authority;
     let trade = &mut ctx.accounts.trade;
     let user = &mut ctx.accounts.user;
 
     // ! @check
+    // |-program must not be paused
+    assert_not_paused(authority)?;
     // |-allowed users max 5
     require!(
         allowed_users.len() <= 5,
@@ -62,6 +97,23 @@ pub fn create_trade(
         SolTradeError::CreateTradeNotAllowedUser
     );
 
+    // |-wanted assets max 5, each one complete
+    require!(
+        wanted_assets.len() <= WantedAsset::MAX_LEN,
+        SolTradeError::CreateTradeTooManyWantedAssets
+    );
+    for wanted_asset in wanted_assets.iter() {
+        require!(
+            wanted_asset.is_complete()?,
+            SolTradeError::CreateTradeInvalidWantedAsset
+        );
+    }
+    // |-strict mode needs something to check against
+    require!(
+        !strict || !wanted_assets.is_empty(),
+        SolTradeError::CreateTradeStrictWithoutWantedAssets
+    );
+
     // * create trade account
     trade.index = trade_index;
     trade.user = user.key();
@@ -71,9 +123,39 @@ pub fn create_trade(
     trade.allowed_users = allowed_users;
     trade.asset_sol_count = 0;
     trade.asset_spl_count = 0;
+    trade.asset_nft_count = 0;
+    trade.asset_cnft_count = 0;
+    trade.asset_stake_count = 0;
+    trade.asset_authority_count = 0;
     trade.exchanged_count = 0;
+    trade.cancelled = false;
+    trade.mode = TradeMode::Offer.to_code();
+    trade.auction_mint = Pubkey::default();
+    trade.reserve_price = 0;
+    trade.min_increment = 0;
+    trade.end_time = 0;
+    trade.best_offer = Pubkey::default();
+    trade.best_bid = 0;
+    trade.start_price = 0;
+    trade.end_price = 0;
+    trade.start_time = 0;
+    trade.decay = DutchAuctionDecay::Linear.to_code();
+    trade.step_interval = 0;
+    trade.price = 0;
+    trade.buyer = Pubkey::default();
+    trade.wanted_assets = wanted_assets;
+    trade.strict = strict;
+    trade.auto_accept_rule = AutoAcceptRule::Disabled.to_code();
+    trade.auto_accept_mint = Pubkey::default();
+    trade.auto_accept_amount = 0;
 
     authority.trade_count = authority.trade_count.checked_add(1).unwrap();
 
+    // * link the trade to the user profile
+    let user_profile = &mut ctx.accounts.user_profile;
+    user_profile.user = user.key();
+    ctx.accounts.user_trade.trade = trade.key();
+    user_profile.trade_count = user_profile.trade_count.checked_add(1).unwrap();
+
     Ok(())
 }
@@ -0,0 +1,264 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemType, Authority, RoyaltyCreator},
+        utils::{
+            assert_mint_allowed, assert_mint_supported, assert_nft, assert_not_paused,
+            is_programmable, pay_with_royalties, royalty_creators, transfer_spl,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{metadata::MetadataAccount, token::Mint, token_interface},
+};
+
+// ? one asset of the swap, given by the maker or the taker to the other one
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DirectSwapAsset {
+    pub asset_type: u8, // AssetItemType code, SOL, SPL or NFT
+    pub from_maker: bool,
+    pub amount: u64, // lamports or tokens, ignored for nfts
+}
+
+// ? same bound as add_asset_items, the accounts of both sides share one transaction
+pub const DIRECT_SWAP_MAX: usize = 16;
+
+#[derive(Accounts)]
+pub struct DirectSwapContext<'info> {
+    // ? authority: program authority, checked for the paused flag and the mint registry
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? maker: one side of the swap, signs first and passes the transaction to the taker
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // ? taker: other side of the swap, signs last and sends the transaction
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    // ? system_program: moves the lamports
+    pub system_program: Program<'info, System>,
+    //
+    // ? remaining_accounts: for each asset, in the order of the assets
+    // ? - sol: none
+    // ? - spl: spl_mint, from_token, to_token, token_program, mint_registry_entry
+    // ? - nft: nft_mint, nft_metadata, from_token, to_token, token_program, mint_registry_entry
+    // ? then the creators of the nfts of the taker, paid by the lamports of the maker,
+    // ? then the creators of the nfts of the maker, paid by the lamports of the taker,
+    // ? for each nft in order, then each of its creators with a share. creators are only passed
+    // ? for a side giving lamports
+}
+
+// ? swaps the assets of both sides in one transaction, nothing is escrowed and no trade or offer
+// ? is created. the lamports of a side pay the royalties of the nfts it receives, as in exchange.
+// ? mints with a transfer hook and programmable nfts go through a trade, their accounts vary
+pub fn direct_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, DirectSwapContext<'info>>,
+    assets: Vec<DirectSwapAsset>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let maker = accounts.maker.to_account_info();
+    let taker = accounts.taker.to_account_info();
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&accounts.authority)?;
+    // |-two different users
+    require_keys_neq!(maker.key(), taker.key(), SolTradeError::DirectSwapSameUser);
+    // |-at least one asset, no more than fits in a transaction
+    require!(
+        !assets.is_empty() && assets.len() <= DIRECT_SWAP_MAX,
+        SolTradeError::DirectSwapInvalidCount
+    );
+    // |-both sides give something, otherwise it is a gift
+    require!(
+        assets.iter().any(|asset| asset.from_maker) && assets.iter().any(|asset| !asset.from_maker),
+        SolTradeError::DirectSwapOneSided
+    );
+
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    let mut next_account = || {
+        remaining_accounts
+            .next()
+            .ok_or(SolTradeError::DirectSwapInvalidAccounts)
+    };
+
+    // lamports given by the maker and the taker, and the royalties of the nfts they give
+    let mut maker_lamports: u64 = 0;
+    let mut taker_lamports: u64 = 0;
+    let mut maker_royalties: Vec<(u16, Vec<RoyaltyCreator>)> = vec![];
+    let mut taker_royalties: Vec<(u16, Vec<RoyaltyCreator>)> = vec![];
+
+    for asset in assets.iter() {
+        let (from, to) = if asset.from_maker {
+            (&maker, &taker)
+        } else {
+            (&taker, &maker)
+        };
+
+        match AssetItemType::from_code(asset.asset_type)? {
+            AssetItemType::SOL => {
+                require!(asset.amount > 0, SolTradeError::DirectSwapInvalidAmount);
+                let lamports = if asset.from_maker {
+                    &mut maker_lamports
+                } else {
+                    &mut taker_lamports
+                };
+                *lamports = lamports.checked_add(asset.amount).unwrap();
+            }
+            AssetItemType::SPL => {
+                // ? both are owned by the token program or token 2022, checked by try_from
+                let mint: InterfaceAccount<token_interface::Mint> =
+                    InterfaceAccount::try_from(next_account()?)?;
+                let from_token = next_account()?;
+                let to_token = next_account()?;
+                let token_program = next_account()?;
+                let mint_registry_entry = next_account()?;
+
+                // ! @check
+                // |-amount must be positive
+                require!(asset.amount > 0, SolTradeError::DirectSwapInvalidAmount);
+                // |-mint must pass the mint registry
+                assert_mint_allowed(&accounts.authority, &mint.key(), Some(mint_registry_entry))?;
+                // |-token 2022 extensions the program can't move
+                assert_mint_supported(&mint.to_account_info())?;
+                // |-the tokens go from a token account of the giver to one of the other side
+                assert_swap_token_accounts(
+                    &mint.to_account_info(),
+                    from_token,
+                    to_token,
+                    token_program,
+                    from,
+                    to,
+                )?;
+
+                // * the giver signed the transaction, it is the authority of its token account
+                transfer_spl(
+                    token_program,
+                    from_token,
+                    &mint.to_account_info(),
+                    to_token,
+                    from,
+                    asset.amount,
+                    mint.decimals,
+                    &[],
+                    &[],
+                )?;
+            }
+            AssetItemType::NFT => {
+                let nft_mint: Account<Mint> = Account::try_from(next_account()?)?;
+                let nft_metadata: Account<MetadataAccount> = Account::try_from(next_account()?)?;
+                let from_token = next_account()?;
+                let to_token = next_account()?;
+                let token_program = next_account()?;
+                let mint_registry_entry = next_account()?;
+
+                // ! @check
+                // |-a metaplex nft, with its own metadata
+                assert_nft(&nft_mint, &nft_metadata)?;
+                // |-mint must pass the mint registry
+                assert_mint_allowed(
+                    &accounts.authority,
+                    &nft_mint.key(),
+                    Some(mint_registry_entry),
+                )?;
+                // |-programmable nfts can't be moved by a plain transfer
+                require!(
+                    !is_programmable(&nft_metadata),
+                    SolTradeError::DirectSwapInvalidAssetType
+                );
+                // |-the nft goes from a token account of the giver to one of the other side
+                assert_swap_token_accounts(
+                    &nft_mint.to_account_info(),
+                    from_token,
+                    to_token,
+                    token_program,
+                    from,
+                    to,
+                )?;
+
+                // * move the nft
+                transfer_spl(
+                    token_program,
+                    from_token,
+                    &nft_mint.to_account_info(),
+                    to_token,
+                    from,
+                    1,
+                    0,
+                    &[],
+                    &[],
+                )?;
+
+                let royalties = (
+                    nft_metadata.data.seller_fee_basis_points,
+                    royalty_creators(&nft_metadata),
+                );
+                if asset.from_maker {
+                    maker_royalties.push(royalties);
+                } else {
+                    taker_royalties.push(royalties);
+                }
+            }
+            _ => return Err(SolTradeError::DirectSwapInvalidAssetType.into()),
+        }
+    }
+
+    // * the lamports of each side go to the other one, the creators of the nfts it receives get
+    // * their royalties first
+    let system_program = accounts.system_program.to_account_info();
+    let mut creator_accounts = remaining_accounts;
+    pay_with_royalties(
+        maker_lamports,
+        &taker_royalties,
+        &maker,
+        &taker,
+        &mut creator_accounts,
+        Some(&system_program),
+    )?;
+    pay_with_royalties(
+        taker_lamports,
+        &maker_royalties,
+        &taker,
+        &maker,
+        &mut creator_accounts,
+        Some(&system_program),
+    )?;
+
+    Ok(())
+}
+
+// ? token accounts of a swapped mint: from the giver, to the other side, both of the mint and
+// ? its token program
+fn assert_swap_token_accounts<'info>(
+    mint: &AccountInfo<'info>,
+    from_token: &AccountInfo<'info>,
+    to_token: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(
+        *mint.owner,
+        token_program.key(),
+        SolTradeError::DirectSwapInvalidAccounts
+    );
+    let from_token: InterfaceAccount<token_interface::TokenAccount> =
+        InterfaceAccount::try_from(from_token)?;
+    let to_token: InterfaceAccount<token_interface::TokenAccount> =
+        InterfaceAccount::try_from(to_token)?;
+    require!(
+        from_token.mint == mint.key()
+            && to_token.mint == mint.key()
+            && from_token.owner == from.key()
+            && to_token.owner == to.key(),
+        SolTradeError::DirectSwapInvalidAccounts
+    );
+    Ok(())
+}
@@ -1,20 +1,34 @@
 use {
     crate::{
         error::SolTradeError,
-        state::{AssetItemSOL, AssetItemSPL, AssetItemType, Offer, Trade},
+        state::{
+            AssetItem, AssetItemAuthority, AssetItemCNFT, AssetItemCore, AssetItemNFT,
+            AssetItemSOL, AssetItemSPL, AssetItemStake, AssetItemType, Authority, Offer, Trade,
+            UserProfile,
+        },
+        utils::{
+            as_asset_item, assert_not_paused, pay_nft_royalties, release_asset_item,
+            sol_payout_account, sync_sol_payouts, AssetItemRelease, RoyaltyAssetItem,
+        },
     },
     anchor_lang::prelude::*,
-    solana_program::{
-        instruction::AccountMeta,
-        program::{invoke, invoke_signed},
-        pubkey::Pubkey,
-        system_instruction,
-    },
+    anchor_spl::token::Token,
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
 };
 
 #[derive(Accounts)]
 #[instruction(trade_index: u32, offer_index: u32)]
 pub struct ExchangeContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
     // ? trade: account to create
     #[account(mut, seeds = [ Trade::PREFIX.as_bytes(), trade_index.to_le_bytes().as_ref() ], bump)]
     pub trade: Account<'info, Trade>,
@@ -29,6 +43,43 @@ pub struct ExchangeContext<'info> {
     #[account(mut)]
     pub asset_item_spl: Option<Account<'info, AssetItemSPL>>,
 
+    #[account(mut)]
+    pub asset_item_nft: Option<Box<Account<'info, AssetItemNFT>>>,
+
+    #[account(mut)]
+    pub asset_item_cnft: Option<Box<Account<'info, AssetItemCNFT>>>,
+
+    #[account(mut)]
+    pub asset_item_core: Option<Box<Account<'info, AssetItemCore>>>,
+
+    #[account(mut)]
+    pub asset_item_stake: Option<Box<Account<'info, AssetItemStake>>>,
+
+    #[account(mut)]
+    pub asset_item_authority: Option<Box<Account<'info, AssetItemAuthority>>>,
+
+    // ? maker_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            trade.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub maker_profile: Account<'info, UserProfile>,
+
+    // ? taker_profile: profile of the offer owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            offer.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub taker_profile: Account<'info, UserProfile>,
+
     // ? user: who the target user
     #[account(mut)]
     /// CHECK: this user is from the trade or offer
@@ -40,185 +91,203 @@ pub struct ExchangeContext<'info> {
 
     // ? system_program: system program
     pub system_program: Program<'info, System>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? user_wsol_account: wrapped SOL account of the user, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the user in runtime
+    pub user_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? token_program: token program, required to pay a wrapped SOL account
+    pub token_program: Option<Program<'info, Token>>,
+    //
+    // ? remaining_accounts: for SOL asset items, every nft and core asset item of the other side
+    // ? ordered by type index, then the creators of the nfts, the lamports pay for them
 }
 
-pub fn exchange(
-    ctx: Context<ExchangeContext>,
-    trade_index: u32,
-    offer_index: u32,
+pub fn exchange<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExchangeContext<'info>>,
+    _trade_index: u32,
+    _offer_index: u32,
     from_type: u8,
     asset_type: u8,
-    asset_index: u32,
-    asset_type_index: u32,
+    _asset_index: u32,
+    _asset_type_index: u32,
 ) -> Result<()> {
-    let user = &mut ctx.accounts.user;
+    let user = &ctx.accounts.user;
     let trade = &mut ctx.accounts.trade;
     let offer = &mut ctx.accounts.offer;
 
-    let asset_item_type = AssetItemType::from_code(asset_type)?;
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-offer must be the accepted one
+    require_keys_eq!(
+        trade.accepted_offer,
+        offer.key(),
+        SolTradeError::ExchangeOfferNotAccepted
+    );
+    // |-the signer gets the asset item, it must own the other side: the offer when exchanging
+    // |-from the trade, the trade when exchanging from the offer
+    let receiver = match from_type {
+        1 => offer.user,
+        2 => trade.user,
+        _ => return Err(SolTradeError::InvalidAssetItemType.into()),
+    };
+    require_keys_eq!(user.key(), receiver, SolTradeError::ExchangeInvalidUser);
+
+    // * lamports go to the user, or its wrapped SOL account
+    let user_profile = if from_type == 1 {
+        &ctx.accounts.taker_profile
+    } else {
+        &ctx.accounts.maker_profile
+    };
+    let user_payout = sol_payout_account(
+        &user.to_account_info(),
+        Some(user_profile),
+        ctx.accounts.user_wsol_account.as_deref(),
+    )?;
 
     msg!("trade: {:?}", trade.key());
     msg!("offer: {:?}", offer.key());
 
-    if from_type == 1 {
-        // if from trade, so exchange from trade to offer
+    let (from_account_info, to_key, to_nft_count) = if from_type == 1 {
         msg!(
             "exchange from trade to offer | from user {:?} to user {:?}",
-            ctx.accounts.user.key(),
-            ctx.accounts.user_from.key()
-        );
-
-        // prepare
-        let program_id = ctx.program_id;
-        let (pda_trade, bump_trade) = Pubkey::find_program_address(
-            &[trade.key().as_ref(), &trade_index.to_le_bytes()],
-            program_id,
+            ctx.accounts.user_from.key(),
+            user.key()
         );
-        let signer_seeds: &[&[&[u8]]] = &[&[b"trade", &[bump_trade]]];
-
-        // process
-        process_asset_item(
-            from_type,
-            &trade.key(),
-            trade.to_account_info(),
-            signer_seeds,
-            &ctx.accounts.user_from.to_account_info(),
-            &ctx.accounts.user.to_account_info(),
-            asset_item_type,
-            &mut ctx.accounts.asset_item_sol,
-            &mut ctx.accounts.asset_item_spl,
-            ctx.accounts.system_program.to_account_info(),
-        )?;
-
-        // update the trade
-        trade.exchanged_count = trade.exchanged_count.checked_add(1).unwrap();
-    } else if from_type == 2 {
-        // if from offer, so exchange from offer to trade
+        (trade.to_account_info(), offer.key(), offer.asset_nft_count)
+    } else {
         msg!(
             "exchange from offer to trade | from user {:?} to user {:?}",
             ctx.accounts.user_from.key(),
-            ctx.accounts.user.key()
+            user.key()
         );
+        (offer.to_account_info(), trade.key(), trade.asset_nft_count)
+    };
 
-        // prepare
-        let program_id = ctx.program_id;
-        let (pda_offer, bump_offer) = Pubkey::find_program_address(
-            &[
-                trade.key().as_ref(),
-                Offer::PREFIX.as_bytes(),
-                &offer_index.to_le_bytes(),
-            ],
-            program_id,
-        );
-        let trade_address = trade.key();
-        let offer_index_bytes = offer_index.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            trade_address.as_ref(),
-            b"offer",
-            &offer_index_bytes,
-            &[bump_offer],
-        ]];
-
-        // process
-        process_asset_item(
-            from_type,
-            &offer.key(),
-            offer.to_account_info(),
-            signer_seeds,
-            &ctx.accounts.user_from.to_account_info(),
-            &ctx.accounts.user.to_account_info(),
-            asset_item_type,
-            &mut ctx.accounts.asset_item_sol,
-            &mut ctx.accounts.asset_item_spl,
-            ctx.accounts.system_program.to_account_info(),
+    let asset_item: &mut dyn AssetItem = match AssetItemType::from_code(asset_type)? {
+        AssetItemType::SOL => ctx.accounts.asset_item_sol.as_mut().map(as_asset_item),
+        AssetItemType::SPL => ctx.accounts.asset_item_spl.as_mut().map(as_asset_item),
+        AssetItemType::NFT => ctx
+            .accounts
+            .asset_item_nft
+            .as_deref_mut()
+            .map(as_asset_item),
+        AssetItemType::CNFT => ctx
+            .accounts
+            .asset_item_cnft
+            .as_deref_mut()
+            .map(as_asset_item),
+        AssetItemType::CORE => ctx
+            .accounts
+            .asset_item_core
+            .as_deref_mut()
+            .map(as_asset_item),
+        AssetItemType::STAKE => ctx
+            .accounts
+            .asset_item_stake
+            .as_deref_mut()
+            .map(as_asset_item),
+        AssetItemType::AUTHORITY => ctx
+            .accounts
+            .asset_item_authority
+            .as_deref_mut()
+            .map(as_asset_item),
+    }
+    .ok_or(SolTradeError::ExchangeMissingAssetItem)?;
+
+    // * the assets in escrow stay with the trade or offer, they are claimed by the user
+    release_asset_item(
+        asset_item,
+        AssetItemRelease::Exchange,
+        from_account_info.key,
+        None,
+        &user.key(),
+    )?;
+
+    // ! transfer lamports, the creators of the nfts of the other side get their royalties
+    let sol_amount = asset_item.released().lamports();
+    if sol_amount > 0 {
+        pay_sol_asset_item(
+            sol_amount,
+            &from_account_info,
+            &user_payout,
+            &to_key,
+            to_nft_count,
+            ctx.remaining_accounts,
         )?;
+    }
 
-        // update the offer
-        offer.exchanged_count = offer.exchanged_count.checked_add(1).unwrap();
+    if from_type == 1 {
+        trade.exchanged_count = trade.exchanged_count.checked_add(1).unwrap();
     } else {
-        return Err(SolTradeError::InvalidAssetItemType.into());
+        offer.exchanged_count = offer.exchanged_count.checked_add(1).unwrap();
+    }
+    sync_sol_payouts(
+        &[&user_payout],
+        &[&trade.to_account_info(), &offer.to_account_info()],
+        ctx.accounts
+            .token_program
+            .as_ref()
+            .map(|token_program| token_program.as_ref()),
+    )?;
+
+    // * update the profiles of both sides, the trade is completed after its last asset.
+    // * when maker and taker are the same wallet both accounts are the same profile,
+    // * so both copies get the same update and the last one written wins
+    let completed =
+        trade.exchanged_count == trade.asset_count && offer.exchanged_count == offer.asset_count;
+    for profile in [
+        &mut ctx.accounts.maker_profile,
+        &mut ctx.accounts.taker_profile,
+    ] {
+        profile.sol_volume = profile.sol_volume.checked_add(sol_amount).unwrap();
+        if completed {
+            profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+        }
     }
 
     Ok(())
 }
 
-fn process_asset_item<'info>(
-    from_type: u8,
-    from_key: &Pubkey,
-    from_account_info: AccountInfo<'info>,
-    from_signer: &[&[&[u8]]],
-
-    from_user: &AccountInfo<'info>,
-    to_user: &AccountInfo<'info>,
-
-    asset_item_type: AssetItemType,
-    asset_item_sol: &mut Option<Account<'info, AssetItemSOL>>,
-    asset_item_spl: &mut Option<Account<'info, AssetItemSPL>>,
-
-    system_program: AccountInfo<'info>,
+// ? pays the lamports of a sol asset item, every nft of the other side must be passed in order,
+// ? then their creators
+fn pay_sol_asset_item<'info>(
+    lamports: u64,
+    from_account_info: &AccountInfo<'info>,
+    // the user, or its wrapped SOL account, gets the lamports
+    to_payout: &AccountInfo<'info>,
+    // the other side, its nfts are paid by the lamports
+    to_key: &Pubkey,
+    to_nft_count: u32,
+    remaining_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
-    match asset_item_type {
-        AssetItemType::SOL => {
-            if let Some(asset_item_sol) = asset_item_sol {
-                // ! @check
-                // |- check asset_item_sol.exchanged must not be true
-                assert!(!asset_item_sol.exchanged, "Asset item already exchanged");
-                // |- check asset_item_sol.from must be from_key
-                assert_eq!(asset_item_sol.from, *from_key, "Invalid from key");
-
-                // tranfer sol first
-                msg!("transfer lamports {:?}", asset_item_sol.amount);
-                msg!(
-                    "from_account_info lamports before : {:?}",
-                    from_account_info.to_account_info().lamports()
-                );
-                msg!(
-                    "user lamports before : {:?}",
-                    to_user.to_account_info().lamports()
-                );
-
-                // let ix = anchor_lang::solana_program::system_instruction::transfer(
-                //     &from_account_info.key(),
-                //     // &asset_item_sol.key(),
-                //     &to_user.key(),
-                //     asset_item_sol.amount,
-                // );
-                // anchor_lang::solana_program::program::invoke_signed(
-                //     &ix,
-                //     &[
-                //         // from
-                //         from_account_info.to_account_info(),
-                //         // asset_item_sol.to_account_info(),
-                //         // to
-                //         to_user.clone(),
-                //     ],
-                //     from_signer,
-                // )?;
-                // ! transfer lamports
-                **from_account_info
-                    .to_account_info()
-                    .try_borrow_mut_lamports()? -= asset_item_sol.amount;
-                **to_user.to_account_info().try_borrow_mut_lamports()? += asset_item_sol.amount;
-
-                // debug
-                msg!(
-                    "from_account_info lamports after : {:?}",
-                    from_account_info.to_account_info().lamports()
-                );
-                msg!(
-                    "user lamports after : {:?}",
-                    to_user.to_account_info().lamports()
-                );
-
-                // update the asset item
-                asset_item_sol.exchanged = true;
-            }
-
-            Ok(())
-        }
-        AssetItemType::SPL => Ok(()),
-        _ => {
-            return Err(SolTradeError::InvalidAssetItemType.into());
-        }
+    // ! @check
+    // |- every nft of the other side must be passed, in order
+    require!(
+        remaining_accounts.len() >= to_nft_count as usize,
+        SolTradeError::RoyaltyInvalidAssetItems
+    );
+    let (nft_items, creators) = remaining_accounts.split_at(to_nft_count as usize);
+    for (type_index, nft_item) in nft_items.iter().enumerate() {
+        let royalty_item = RoyaltyAssetItem::try_from(nft_item)?;
+        require!(
+            royalty_item.from() == *to_key && royalty_item.type_index() == type_index as u32,
+            SolTradeError::RoyaltyInvalidAssetItems
+        );
     }
+
+    msg!("transfer lamports {:?}", lamports);
+    pay_nft_royalties(
+        lamports,
+        nft_items,
+        from_account_info,
+        to_payout,
+        &mut creators.iter(),
+        None,
+    )?;
+
+    Ok(())
 }
@@ -0,0 +1,192 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, CollectionOffer, UserProfile},
+        utils::{
+            assert_nft, assert_not_paused, pay_with_royalties, royalty_creators,
+            sol_payout_account, sync_sol_payouts, verified_collection,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::AssociatedToken,
+        metadata::MetadataAccount,
+        token::{self, Mint, Token, TokenAccount, Transfer},
+    },
+};
+
+#[derive(Accounts)]
+pub struct FillCollectionOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? collection_offer: offer to fill
+    #[account(mut)]
+    pub collection_offer: Account<'info, CollectionOffer>,
+
+    // ? mint: nft to sell
+    pub mint: Box<Account<'info, Mint>>,
+
+    // ? metadata: metaplex metadata of the nft
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+
+    // ? user_token_account: token account of the seller holding the nft
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ? buyer: owner of the collection offer
+    /// CHECK: checked against the collection offer user in runtime
+    pub buyer: AccountInfo<'info>,
+
+    // ? buyer_token_account: associated token account of the buyer, gets the nft
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ? buyer_profile: profile of the buyer
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            collection_offer.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub buyer_profile: Account<'info, UserProfile>,
+
+    // ? user_profile: profile of the seller, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user: seller, gets the price
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? token_program: token program
+    pub token_program: Program<'info, Token>,
+
+    // ? associated_token_program: associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? user_wsol_account: wrapped SOL account of the seller, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the seller in runtime
+    pub user_wsol_account: Option<UncheckedAccount<'info>>,
+    //
+    // ? remaining_accounts: creators of the nft with a share, in the order of the metadata
+}
+
+// ? fills one nft of the offer, the nft and the price move in the same instruction
+pub fn fill_collection_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, FillCollectionOfferContext<'info>>,
+) -> Result<()> {
+    let collection_offer = &mut ctx.accounts.collection_offer;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-still wants nfts
+    require!(
+        collection_offer.quantity > 0,
+        SolTradeError::CollectionOfferFilled
+    );
+    require_keys_eq!(
+        collection_offer.user,
+        ctx.accounts.buyer.key(),
+        SolTradeError::CollectionOfferInvalidBuyer
+    );
+    // |-a verified nft of the collection
+    assert_nft(&ctx.accounts.mint, &ctx.accounts.metadata)?;
+    require!(
+        verified_collection(&ctx.accounts.metadata) == Some(collection_offer.collection),
+        SolTradeError::NftCollectionMismatch
+    );
+
+    // * nft to the buyer
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.buyer_token_account.to_account_info(),
+                authority: user.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    // * price to the seller, less the royalties of the creators
+    let user_payout = sol_payout_account(
+        &user.to_account_info(),
+        Some(&ctx.accounts.user_profile),
+        ctx.accounts.user_wsol_account.as_deref(),
+    )?;
+    let price = collection_offer.price;
+    let metadata = &ctx.accounts.metadata;
+    let royalties = pay_with_royalties(
+        price,
+        &[(
+            metadata.data.seller_fee_basis_points,
+            royalty_creators(metadata),
+        )],
+        &collection_offer.to_account_info(),
+        &user_payout,
+        &mut ctx.remaining_accounts.iter(),
+        None,
+    )?;
+    sync_sol_payouts(
+        &[&user_payout],
+        &[&collection_offer.to_account_info()],
+        Some(&ctx.accounts.token_program.to_account_info()),
+    )?;
+    collection_offer.royalty_paid = collection_offer
+        .royalty_paid
+        .checked_add(royalties.iter().sum())
+        .unwrap();
+
+    collection_offer.quantity = collection_offer.quantity.checked_sub(1).unwrap();
+    collection_offer.filled = collection_offer.filled.checked_add(1).unwrap();
+
+    // * update the profiles of both sides.
+    // * when buyer and seller are the same wallet both accounts are the same profile,
+    // * so both copies get the same update and the last one written wins
+    ctx.accounts.user_profile.user = user.key();
+    for profile in [
+        &mut ctx.accounts.buyer_profile,
+        &mut ctx.accounts.user_profile,
+    ] {
+        profile.sol_volume = profile.sol_volume.checked_add(price).unwrap();
+        profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+    }
+
+    Ok(())
+}
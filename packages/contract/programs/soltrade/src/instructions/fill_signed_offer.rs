@@ -0,0 +1,279 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{
+            AssetItemType, Authority, SignedOffer, SignedOfferNonce, Trade, TradeMode, UserProfile,
+        },
+        utils::{
+            assert_allowed_user, assert_ed25519_signature, assert_mint_allowed,
+            assert_mint_supported, assert_not_paused, settle_lot, sol_payout_account,
+            sync_sol_payouts, transfer_spl, SettleLotAccounts,
+        },
+    },
+    anchor_lang::{prelude::*, solana_program::sysvar},
+    anchor_spl::{token::Token, token_interface},
+};
+
+#[derive(Accounts)]
+#[instruction(offer: SignedOffer)]
+pub struct FillSignedOfferContext<'info> {
+    // ? authority: program authority, checked for the paused flag and the mint registry
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade the offer was signed for, settled here
+    #[account(mut)]
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? maker_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            trade.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub maker_profile: Box<Account<'info, UserProfile>>,
+
+    // ? taker_profile: profile of the taker, created on its first trade or offer
+    #[account(
+        init_if_needed,
+        payer = maker,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            taker.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub taker_profile: Box<Account<'info, UserProfile>>,
+
+    // ? signed_offer_nonce: nonce of the offer, can't be created twice
+    #[account(
+        init,
+        payer = maker,
+        space = SignedOfferNonce::SIZE,
+        seeds = [
+            SignedOfferNonce::PREFIX.as_bytes(),
+            taker.key().as_ref(),
+            offer.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub signed_offer_nonce: Account<'info, SignedOfferNonce>,
+
+    // ? delegate: approved by the taker on the token accounts it gives
+    #[account(
+        seeds = [SignedOffer::DELEGATE_PREFIX.as_bytes()],
+        bump,
+    )]
+    /// CHECK: pda of the program, signs the transfers only
+    pub delegate: UncheckedAccount<'info>,
+
+    // ? maker: owner of the trade, submits the offer and gets the assets of the taker
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    // ? taker: signer of the offer, gets the lot of the trade
+    #[account(mut)]
+    /// CHECK: checked against the signed offer and the ed25519 instruction in runtime
+    pub taker: UncheckedAccount<'info>,
+
+    // ? sysvar_instructions: instructions sysvar, holds the ed25519 instruction
+    #[account(address = sysvar::instructions::ID)]
+    /// CHECK: the address is checked
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? taker_wsol_account: wrapped SOL account of the taker, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the taker in runtime
+    pub taker_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? token_program: token program, pays the wrapped SOL account
+    pub token_program: Option<Program<'info, Token>>,
+    //
+    // ? remaining_accounts: every asset item of the trade, ordered by index, then for each
+    // ? asset of the offer: mint, taker_token, maker_token, token_program, mint_registry_entry
+}
+
+// ? settles the trade against an offer the taker only signed, no offer account is created. the
+// ? lot goes to the taker as in buy, the tokens of the taker are pulled by the delegate
+pub fn fill_signed_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, FillSignedOfferContext<'info>>,
+    offer: SignedOffer,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let trade = &mut accounts.trade;
+    let maker = &accounts.maker;
+    let taker = &accounts.taker;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&accounts.authority)?;
+    // |-trade still takes offers, only its owner fills one
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(
+        trade.user == maker.key(),
+        SolTradeError::AcceptOfferUnauthorized
+    );
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    require!(trade.buyer == Pubkey::default(), SolTradeError::TradeSold);
+    // |-the offer was signed by the taker for this trade, and not expired
+    require!(
+        offer.program == crate::id() && offer.trade == trade.key() && offer.taker == taker.key(),
+        SolTradeError::SignedOfferMismatch
+    );
+    require_keys_neq!(taker.key(), maker.key(), SolTradeError::SignedOfferMismatch);
+    require!(
+        Clock::get()?.unix_timestamp < offer.expiry,
+        SolTradeError::SignedOfferExpired
+    );
+    assert_ed25519_signature(
+        &accounts.sysvar_instructions,
+        &taker.key(),
+        &offer.message()?,
+    )?;
+    // |-private trades only take offers of their allowed users
+    assert_allowed_user(trade, &taker.key())?;
+    require!(
+        !offer.assets.is_empty()
+            && offer.assets.len() <= SignedOffer::MAX_ASSETS
+            && offer.assets.iter().all(|asset| asset.amount > 0),
+        SolTradeError::SignedOfferInvalidAssets
+    );
+    // |-the whole lot must be passed
+    require!(
+        ctx.remaining_accounts.len() >= trade.asset_count as usize,
+        SolTradeError::LotInvalidAssetItems
+    );
+    let (lot, remaining_accounts) = ctx.remaining_accounts.split_at(trade.asset_count as usize);
+    require!(
+        remaining_accounts.len() == offer.assets.len() * 5,
+        SolTradeError::SignedOfferInvalidAccounts
+    );
+
+    // * pull the tokens of the taker to the maker
+    let (_, delegate_bump) =
+        Pubkey::find_program_address(&[SignedOffer::DELEGATE_PREFIX.as_bytes()], &crate::id());
+    let delegate_bump = [delegate_bump];
+    let delegate_seeds: &[&[u8]] = &[SignedOffer::DELEGATE_PREFIX.as_bytes(), &delegate_bump];
+    for (asset, asset_accounts) in offer.assets.iter().zip(remaining_accounts.chunks(5)) {
+        let mint: InterfaceAccount<token_interface::Mint> =
+            InterfaceAccount::try_from(&asset_accounts[0])?;
+        let taker_token = &asset_accounts[1];
+        let maker_token = &asset_accounts[2];
+        let token_program = &asset_accounts[3];
+
+        // ! @check
+        // |-mint of the signed asset, of the token program passed
+        require!(
+            mint.key() == asset.mint && *mint.to_account_info().owner == token_program.key(),
+            SolTradeError::SignedOfferInvalidAccounts
+        );
+        // |-mint must pass the mint registry
+        assert_mint_allowed(&accounts.authority, &asset.mint, Some(&asset_accounts[4]))?;
+        // |-token 2022 extensions the program can't move
+        assert_mint_supported(&mint.to_account_info())?;
+        // |-offers of a strict trade only hold wanted assets
+        if trade.strict {
+            require!(
+                trade.wants(AssetItemType::SPL, &asset.mint, None),
+                SolTradeError::AddAssetItemNotWanted
+            );
+        }
+        // |-from the token account of the taker to one of the maker
+        let taker_token_account: InterfaceAccount<token_interface::TokenAccount> =
+            InterfaceAccount::try_from(taker_token)?;
+        let maker_token_account: InterfaceAccount<token_interface::TokenAccount> =
+            InterfaceAccount::try_from(maker_token)?;
+        require!(
+            taker_token_account.mint == asset.mint
+                && maker_token_account.mint == asset.mint
+                && taker_token_account.owner == taker.key()
+                && maker_token_account.owner == maker.key(),
+            SolTradeError::SignedOfferInvalidAccounts
+        );
+
+        // * the token program checks the delegate and its allowance
+        transfer_spl(
+            token_program,
+            taker_token,
+            &mint.to_account_info(),
+            maker_token,
+            &accounts.delegate,
+            asset.amount,
+            mint.decimals,
+            &[],
+            &[delegate_seeds],
+        )?;
+    }
+
+    // * move the lot to the taker
+    let taker_payout = sol_payout_account(
+        taker,
+        Some(&accounts.taker_profile),
+        accounts.taker_wsol_account.as_deref(),
+    )?;
+    // * the price was paid in tokens above, there are no royalties to pay out of it
+    let sol_amount = settle_lot(
+        SettleLotAccounts {
+            lot,
+            from: &trade.to_account_info(),
+            receiver: taker,
+            sol_receiver: &taker_payout,
+            payer: taker,
+            seller: maker,
+            system_program: None,
+        },
+        0,
+        false,
+        &mut [].iter(),
+    )?
+    .iter()
+    .fold(0u64, |total, released| {
+        total.checked_add(released.lamports()).unwrap()
+    });
+    let token_program = accounts
+        .token_program
+        .as_ref()
+        .map(|token_program| token_program.to_account_info());
+    sync_sol_payouts(
+        &[&taker_payout],
+        &[&trade.to_account_info()],
+        token_program.as_ref(),
+    )?;
+    trade.exchanged_count = trade.asset_count;
+    trade.buyer = taker.key();
+
+    let signed_offer_nonce = &mut accounts.signed_offer_nonce;
+    signed_offer_nonce.user = taker.key();
+    signed_offer_nonce.nonce = offer.nonce;
+    signed_offer_nonce.trade = trade.key();
+
+    // * update the profiles of both sides
+    accounts.taker_profile.user = taker.key();
+    for profile in [&mut accounts.maker_profile, &mut accounts.taker_profile] {
+        profile.sol_volume = profile.sol_volume.checked_add(sol_amount).unwrap();
+        profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+    }
+
+    Ok(())
+}
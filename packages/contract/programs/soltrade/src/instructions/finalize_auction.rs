@@ -0,0 +1,236 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Offer, Trade, TradeMode, UserProfile},
+        utils::{
+            assert_not_paused, release_bid, settle_lot, sol_payout_account, sync_sol_payouts,
+            SettleLotAccounts,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct FinalizeAuctionContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: auction to finalize
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? maker: owner of the trade, gets the winning bid
+    #[account(mut)]
+    /// CHECK: checked against the trade user in runtime
+    pub maker: AccountInfo<'info>,
+
+    // ? maker_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            trade.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub maker_profile: Account<'info, UserProfile>,
+
+    // ======================[ WINNER ONLY ]======================
+    // ? best_offer: winning offer
+    #[account(mut)]
+    pub best_offer: Option<Account<'info, Offer>>,
+
+    // ? winner: owner of the winning offer, gets the lot
+    #[account(mut)]
+    /// CHECK: checked against the best offer user in runtime
+    pub winner: Option<UncheckedAccount<'info>>,
+
+    // ? winner_profile: profile of the owner of the winning offer
+    #[account(mut)]
+    pub winner_profile: Option<Account<'info, UserProfile>>,
+
+    // ? bid_vault: escrow of the winning bid, when the auction is not in SOL
+    #[account(mut)]
+    pub bid_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? maker_token_account: token account of the maker, gets the winning bid
+    #[account(mut)]
+    pub maker_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    // ? token_program: token program, also required to pay a wrapped SOL account
+    pub token_program: Option<Program<'info, Token>>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? winner_wsol_account: wrapped SOL account of the winner, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the winner in runtime
+    pub winner_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? maker_wsol_account: wrapped SOL account of the maker, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the maker in runtime
+    pub maker_wsol_account: Option<UncheckedAccount<'info>>,
+    //
+    // ? remaining_accounts: every asset item of the trade, ordered by index, then the creators
+    // ? of its nfts when the auction is in SOL
+}
+
+// ? permissionless, anyone can settle the auction once it has ended
+pub fn finalize_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, FinalizeAuctionContext<'info>>,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let maker = &ctx.accounts.maker;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only a running auction past its end time
+    require!(
+        trade.mode == TradeMode::Auction.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    require!(
+        Clock::get()?.unix_timestamp >= trade.end_time,
+        SolTradeError::AuctionNotEnded
+    );
+    require_keys_eq!(trade.user, maker.key(), SolTradeError::AuctionUnauthorized);
+
+    // * no bids, the trade is cancelled and the maker takes the lot back with refund_asset_item
+    if trade.best_offer == Pubkey::default() {
+        trade.cancelled = true;
+        let maker_profile = &mut ctx.accounts.maker_profile;
+        maker_profile.trades_cancelled = maker_profile.trades_cancelled.checked_add(1).unwrap();
+        return Ok(());
+    }
+
+    let best_offer = ctx
+        .accounts
+        .best_offer
+        .as_mut()
+        .ok_or(SolTradeError::AuctionInvalidWinnerAccounts)?;
+    let winner = ctx
+        .accounts
+        .winner
+        .as_ref()
+        .ok_or(SolTradeError::AuctionInvalidWinnerAccounts)?;
+    let winner_profile = ctx
+        .accounts
+        .winner_profile
+        .as_mut()
+        .ok_or(SolTradeError::AuctionInvalidWinnerAccounts)?;
+
+    // ! @check
+    // |-winner accounts must be the ones of the best offer
+    require_keys_eq!(
+        best_offer.key(),
+        trade.best_offer,
+        SolTradeError::AuctionInvalidWinnerAccounts
+    );
+    require_keys_eq!(
+        best_offer.user,
+        winner.key(),
+        SolTradeError::AuctionInvalidWinnerAccounts
+    );
+    let (winner_profile_address, _) = Pubkey::find_program_address(
+        &[UserProfile::PREFIX.as_bytes(), winner.key().as_ref()],
+        &crate::id(),
+    );
+    require_keys_eq!(
+        winner_profile.key(),
+        winner_profile_address,
+        SolTradeError::AuctionInvalidWinnerAccounts
+    );
+    // |-the whole lot must be passed
+    require!(
+        ctx.remaining_accounts.len() >= trade.asset_count as usize,
+        SolTradeError::LotInvalidAssetItems
+    );
+    let (lot, creators) = ctx.remaining_accounts.split_at(trade.asset_count as usize);
+    let winner_payout = sol_payout_account(
+        winner,
+        Some(winner_profile),
+        ctx.accounts.winner_wsol_account.as_deref(),
+    )?;
+    let maker_payout = sol_payout_account(
+        maker,
+        Some(&ctx.accounts.maker_profile),
+        ctx.accounts.maker_wsol_account.as_deref(),
+    )?;
+
+    // * the best offer wins
+    trade.accepted_offer = best_offer.key();
+    best_offer.accepted = true;
+
+    // * a bid in tokens is paid before the lot moves
+    if trade.auction_mint != Pubkey::default() {
+        release_bid(
+            trade,
+            best_offer,
+            maker,
+            ctx.accounts.maker_token_account.as_deref(),
+            ctx.accounts.bid_vault.as_deref(),
+            ctx.accounts.token_program.as_ref(),
+            SolTradeError::AuctionInvalidBidAccounts,
+        )?;
+    }
+
+    // * move the lot to the winner and pay the winning bid to the maker, the creators of the nfts
+    // * get their royalties out of a bid in SOL
+    let sol_price = if trade.auction_mint == Pubkey::default() {
+        best_offer.bid_amount
+    } else {
+        0
+    };
+    let released_items = settle_lot(
+        SettleLotAccounts {
+            lot,
+            from: &trade.to_account_info(),
+            receiver: &winner.to_account_info(),
+            sol_receiver: &winner_payout,
+            payer: &best_offer.to_account_info(),
+            seller: &maker_payout,
+            system_program: None,
+        },
+        sol_price,
+        false,
+        &mut creators.iter(),
+    )?;
+    let sol_amount = released_items.iter().fold(sol_price, |total, released| {
+        total.checked_add(released.lamports()).unwrap()
+    });
+    let token_program = ctx
+        .accounts
+        .token_program
+        .as_ref()
+        .map(|token_program| token_program.to_account_info());
+    sync_sol_payouts(
+        &[&winner_payout, &maker_payout],
+        &[&trade.to_account_info(), &best_offer.to_account_info()],
+        token_program.as_ref(),
+    )?;
+    trade.exchanged_count = trade.asset_count;
+
+    // * update the profiles of both sides.
+    // * when maker and winner are the same wallet both accounts are the same profile,
+    // * so both copies get the same update and the last one written wins
+    for profile in [&mut ctx.accounts.maker_profile, winner_profile] {
+        profile.sol_volume = profile.sol_volume.checked_add(sol_amount).unwrap();
+        profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+    }
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
 use {
-    crate::{error::SolTradeError, state::Authority},
+    crate::{
+        program::Soltrade,
+        state::{Authority, MintRegistryMode},
+        utils::assert_upgrade_authority,
+    },
     anchor_lang::prelude::*,
 };
 
@@ -18,7 +22,13 @@ pub struct InitializeContext<'info> {
     )]
     pub authority: Account<'info, Authority>,
 
-    // ? user: who is creating the trade
+    // ? program: this program, used to find its program data account
+    pub program: Program<'info, Soltrade>,
+
+    // ? program_data: program data account of this program, holds the upgrade authority
+    pub program_data: Account<'info, ProgramData>,
+
+    // ? user: upgrade authority of the program, becomes the admin
     #[account(mut)]
     pub user: Signer<'info>,
 
@@ -27,10 +37,25 @@ pub struct InitializeContext<'info> {
 }
 
 pub fn initialize(ctx: Context<InitializeContext>) -> Result<()> {
+    // ! @check
+    // |-only the upgrade authority can initialize, so the authority can't be front-run after deploy
+    assert_upgrade_authority(
+        &ctx.accounts.program,
+        &ctx.accounts.program_data,
+        ctx.accounts.user.key,
+    )?;
+
     let authority = &mut ctx.accounts.authority;
 
     authority.user = *ctx.accounts.user.key;
     authority.trade_count = 0;
 
+    // * the admin starts with every role, they can be handed out with set_roles
+    authority.pending_user = Pubkey::default();
+    authority.pauser = *ctx.accounts.user.key;
+    authority.fee_manager = *ctx.accounts.user.key;
+    authority.paused = false;
+    authority.mint_registry_mode = MintRegistryMode::Open.to_code();
+
     Ok(())
 }
@@ -0,0 +1,58 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Trade, TradeMode},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ListFixedPriceContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade to list, its assets are sold together
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? user: owner of the trade
+    pub user: Signer<'info>,
+}
+
+pub fn list_fixed_price(
+    ctx: Context<ListFixedPriceContext>,
+    price_mint: Pubkey,
+    price: u64,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner can list the trade
+    require!(trade.user == user.key(), SolTradeError::AuctionUnauthorized);
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-only a plain trade without offers can be listed
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(trade.offer_count == 0, SolTradeError::AuctionHasOffers);
+    require!(price > 0, SolTradeError::FixedPriceInvalidPrice);
+
+    // * the assets are frozen from now on
+    trade.mode = TradeMode::FixedPrice.to_code();
+    trade.auction_mint = price_mint;
+    trade.price = price;
+
+    Ok(())
+}
@@ -1,17 +1,111 @@
 // pub mod create_offer;
 pub mod add_asset_item;
+pub mod add_asset_item_authority;
+pub mod add_asset_item_cnft;
+pub mod add_asset_item_core;
+pub mod add_asset_item_pnft;
+pub mod add_asset_item_stake;
+pub mod add_asset_items;
+pub mod add_ring_asset_item;
 // pub mod add_asset_item_offer;
+pub mod accept_admin;
+pub mod accept_counter_offer;
 pub mod accept_offer;
+pub mod buy;
+pub mod buy_dutch_auction;
+pub mod cancel_collection_offer;
+pub mod cancel_counter_offer;
+pub mod cancel_signed_offer;
+pub mod cancel_trade;
+pub mod claim_authority;
+pub mod claim_cnft;
+pub mod claim_core;
+pub mod claim_nft;
+pub mod claim_pnft;
+pub mod claim_ring_asset_item;
+pub mod claim_spl;
+pub mod claim_stake;
+pub mod confirm_ring;
+pub mod counter_offer;
+pub mod create_collection_offer;
 pub mod create_offer;
+pub mod create_ring;
 pub mod create_trade;
+pub mod direct_swap;
 pub mod exchange;
+pub mod fill_collection_offer;
+pub mod fill_signed_offer;
+pub mod finalize_auction;
 pub mod initialize;
+pub mod list_fixed_price;
+pub mod propose_admin;
+pub mod refund_asset_item;
+pub mod remove_mint_registry_entry;
+pub mod set_auto_accept;
+pub mod set_mint_registry_entry;
+pub mod set_mint_registry_mode;
+pub mod set_paused;
+pub mod set_roles;
+pub mod set_sol_payout;
+pub mod start_auction;
+pub mod start_dutch_auction;
+pub mod try_auto_accept;
+pub mod update_authority_config;
+pub mod withdraw_bid;
+pub mod withdraw_ring;
 
 // use create_offer::*;
 pub use add_asset_item::*;
+pub use add_asset_item_authority::*;
+pub use add_asset_item_cnft::*;
+pub use add_asset_item_core::*;
+pub use add_asset_item_pnft::*;
+pub use add_asset_item_stake::*;
+pub use add_asset_items::*;
+pub use add_ring_asset_item::*;
 // pub use add_asset_item_offer::*;
+pub use accept_admin::*;
+pub use accept_counter_offer::*;
 pub use accept_offer::*;
+pub use buy::*;
+pub use buy_dutch_auction::*;
+pub use cancel_collection_offer::*;
+pub use cancel_counter_offer::*;
+pub use cancel_signed_offer::*;
+pub use cancel_trade::*;
+pub use claim_authority::*;
+pub use claim_cnft::*;
+pub use claim_core::*;
+pub use claim_nft::*;
+pub use claim_pnft::*;
+pub use claim_ring_asset_item::*;
+pub use claim_spl::*;
+pub use claim_stake::*;
+pub use confirm_ring::*;
+pub use counter_offer::*;
+pub use create_collection_offer::*;
 pub use create_offer::*;
+pub use create_ring::*;
 pub use create_trade::*;
+pub use direct_swap::*;
 pub use exchange::*;
+pub use fill_collection_offer::*;
+pub use fill_signed_offer::*;
+pub use finalize_auction::*;
 pub use initialize::*;
+pub use list_fixed_price::*;
+pub use propose_admin::*;
+pub use refund_asset_item::*;
+pub use remove_mint_registry_entry::*;
+pub use set_auto_accept::*;
+pub use set_mint_registry_entry::*;
+pub use set_mint_registry_mode::*;
+pub use set_paused::*;
+pub use set_roles::*;
+pub use set_sol_payout::*;
+pub use start_auction::*;
+pub use start_dutch_auction::*;
+pub use try_auto_accept::*;
+pub use update_authority_config::*;
+pub use withdraw_bid::*;
+pub use withdraw_ring::*;
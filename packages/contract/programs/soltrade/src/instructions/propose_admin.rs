@@ -0,0 +1,39 @@
+use {
+    crate::{error::SolTradeError, state::Authority},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ProposeAdminContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user: current admin
+    pub user: Signer<'info>,
+}
+
+pub fn propose_admin(ctx: Context<ProposeAdminContext>, new_user: Pubkey) -> Result<()> {
+    let authority = &mut ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-only the admin can propose a new admin
+    require!(
+        authority.user == user.key(),
+        SolTradeError::AdminUnauthorized
+    );
+
+    // * the transfer is finished by the new admin with accept_admin,
+    // * proposing the default pubkey cancels a pending transfer
+    authority.pending_user = new_user;
+
+    Ok(())
+}
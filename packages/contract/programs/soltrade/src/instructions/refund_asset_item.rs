@@ -0,0 +1,117 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{
+            AssetItem, AssetItemAuthority, AssetItemCNFT, AssetItemCore, AssetItemNFT,
+            AssetItemSOL, AssetItemSPL, AssetItemStake, AssetItemType, Offer, Trade,
+        },
+        utils::{as_asset_item, assert_offer_of_trade, release_asset_item, AssetItemRelease},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RefundAssetItemContext<'info> {
+    // ? trade: trade of the asset item, or trade of the offer
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? offer: offer of the asset item, only when refunding from an offer
+    #[account(mut)]
+    pub offer: Option<Account<'info, Offer>>,
+
+    #[account(mut)]
+    pub asset_item_sol: Option<Account<'info, AssetItemSOL>>,
+
+    #[account(mut)]
+    pub asset_item_spl: Option<Account<'info, AssetItemSPL>>,
+
+    #[account(mut)]
+    pub asset_item_nft: Option<Box<Account<'info, AssetItemNFT>>>,
+
+    #[account(mut)]
+    pub asset_item_cnft: Option<Box<Account<'info, AssetItemCNFT>>>,
+
+    #[account(mut)]
+    pub asset_item_core: Option<Box<Account<'info, AssetItemCore>>>,
+
+    #[account(mut)]
+    pub asset_item_stake: Option<Box<Account<'info, AssetItemStake>>>,
+
+    #[account(mut)]
+    pub asset_item_authority: Option<Box<Account<'info, AssetItemAuthority>>>,
+
+    // ? receiver: who deposited the asset item
+    #[account(mut)]
+    /// CHECK: checked against the asset item user in runtime
+    pub receiver: AccountInfo<'info>,
+
+    // ? user: anyone can push a refund, assets only go back to their owner
+    pub user: Signer<'info>,
+}
+
+// ? not checked against the paused flag, refunds must always be possible
+pub fn refund_asset_item(
+    ctx: Context<RefundAssetItemContext>,
+    from_type: u8,
+    asset_type: u8,
+) -> Result<()> {
+    let trade = &ctx.accounts.trade;
+    let asset_item_type = AssetItemType::from_code(asset_type)?;
+
+    // ! @check
+    // |-asset of a trade is refundable once the trade is cancelled,
+    // |-asset of an offer once the trade is cancelled or another offer got accepted
+    let from_account_info = if from_type == 1 {
+        require!(trade.cancelled, SolTradeError::RefundAssetItemNotRefundable);
+        trade.to_account_info()
+    } else if from_type == 2 {
+        let offer = ctx
+            .accounts
+            .offer
+            .as_ref()
+            .ok_or(SolTradeError::RefundAssetItemInvalidFrom)?;
+        assert_offer_of_trade(offer, &trade.key())?;
+        require!(
+            trade.cancelled
+                || (trade.accepted_offer != Pubkey::default()
+                    && trade.accepted_offer != offer.key()),
+            SolTradeError::RefundAssetItemNotRefundable
+        );
+        offer.to_account_info()
+    } else {
+        return Err(SolTradeError::InvalidAssetItemType.into());
+    };
+
+    let receiver = ctx.accounts.receiver.to_account_info();
+    let accounts = &mut *ctx.accounts;
+    let asset_item: &mut dyn AssetItem = match asset_item_type {
+        AssetItemType::SOL => accounts.asset_item_sol.as_mut().map(as_asset_item),
+        AssetItemType::SPL => accounts.asset_item_spl.as_mut().map(as_asset_item),
+        AssetItemType::NFT => accounts.asset_item_nft.as_deref_mut().map(as_asset_item),
+        AssetItemType::CNFT => accounts.asset_item_cnft.as_deref_mut().map(as_asset_item),
+        AssetItemType::CORE => accounts.asset_item_core.as_deref_mut().map(as_asset_item),
+        AssetItemType::STAKE => accounts.asset_item_stake.as_deref_mut().map(as_asset_item),
+        AssetItemType::AUTHORITY => accounts
+            .asset_item_authority
+            .as_deref_mut()
+            .map(as_asset_item),
+    }
+    .ok_or(SolTradeError::InvalidAssetItemType)?;
+
+    // * released from escrow, same flag as an exchange. the receiver must be the depositor, it
+    // * claims the assets in escrow and gets the lamports back right away
+    release_asset_item(
+        asset_item,
+        AssetItemRelease::Refund,
+        from_account_info.key,
+        Some(receiver.key),
+        receiver.key,
+    )?;
+
+    let lamports = asset_item.released().lamports();
+    **from_account_info.try_borrow_mut_lamports()? -= lamports;
+    **receiver.try_borrow_mut_lamports()? += lamports;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, MintRegistryEntry},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RemoveMintRegistryEntryContext<'info> {
+    // ? authority: program authority
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? mint_registry_entry: entry to remove, rent goes back to the admin
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            MintRegistryEntry::PREFIX.as_bytes(),
+            mint_registry_entry.mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub mint_registry_entry: Account<'info, MintRegistryEntry>,
+
+    // ? user: admin
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+pub fn remove_mint_registry_entry(ctx: Context<RemoveMintRegistryEntryContext>) -> Result<()> {
+    let authority = &ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-only the admin can manage the registry
+    require!(
+        authority.user == user.key(),
+        SolTradeError::AdminUnauthorized
+    );
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, AutoAcceptRule, Trade, TradeMode},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetAutoAcceptContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade to set the rule on
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? user: owner of the trade
+    pub user: Signer<'info>,
+}
+
+// ? rule = AutoAcceptRule::Disabled turns auto accept off
+pub fn set_auto_accept(
+    ctx: Context<SetAutoAcceptContext>,
+    rule: u8,
+    mint: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let user = &ctx.accounts.user;
+    let rule = AutoAcceptRule::from_code(rule)?;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner can set the rule
+    require!(
+        trade.user == user.key(),
+        SolTradeError::AutoAcceptUnauthorized
+    );
+    // |-only on an open trade
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    // |-rule must be complete
+    let valid = match rule {
+        AutoAcceptRule::Disabled => true,
+        AutoAcceptRule::MinLamports => amount > 0,
+        AutoAcceptRule::MinTokens => amount > 0 && mint != Pubkey::default(),
+        AutoAcceptRule::WantedAssets => !trade.wanted_assets.is_empty(),
+    };
+    require!(valid, SolTradeError::AutoAcceptInvalidRule);
+
+    trade.auto_accept_rule = rule.to_code();
+    trade.auto_accept_mint = match rule {
+        AutoAcceptRule::MinTokens => mint,
+        _ => Pubkey::default(),
+    };
+    trade.auto_accept_amount = match rule {
+        AutoAcceptRule::MinLamports | AutoAcceptRule::MinTokens => amount,
+        _ => 0,
+    };
+
+    Ok(())
+}
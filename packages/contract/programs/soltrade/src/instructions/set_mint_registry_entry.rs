@@ -0,0 +1,63 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, MintRegistryEntry, MintRegistryStatus},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct SetMintRegistryEntryContext<'info> {
+    // ? authority: program authority
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? mint_registry_entry: entry of the mint, created on first use
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            MintRegistryEntry::PREFIX.as_bytes(),
+            mint.as_ref(),
+        ],
+        space = MintRegistryEntry::SIZE,
+        bump,
+    )]
+    pub mint_registry_entry: Account<'info, MintRegistryEntry>,
+
+    // ? user: admin
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_mint_registry_entry(
+    ctx: Context<SetMintRegistryEntryContext>,
+    mint: Pubkey,
+    status: u8,
+) -> Result<()> {
+    let authority = &ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+    let mint_registry_entry = &mut ctx.accounts.mint_registry_entry;
+
+    // ! @check
+    // |-only the admin can manage the registry
+    require!(
+        authority.user == user.key(),
+        SolTradeError::AdminUnauthorized
+    );
+
+    mint_registry_entry.mint = mint;
+    mint_registry_entry.status = MintRegistryStatus::from_code(status)?.to_code();
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, MintRegistryMode},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetMintRegistryModeContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user: admin
+    pub user: Signer<'info>,
+}
+
+pub fn set_mint_registry_mode(ctx: Context<SetMintRegistryModeContext>, mode: u8) -> Result<()> {
+    let authority = &mut ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-only the admin can change the mode
+    require!(
+        authority.user == user.key(),
+        SolTradeError::AdminUnauthorized
+    );
+
+    authority.mint_registry_mode = MintRegistryMode::from_code(mode)?.to_code();
+
+    Ok(())
+}
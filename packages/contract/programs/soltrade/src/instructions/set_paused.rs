@@ -0,0 +1,37 @@
+use {
+    crate::{error::SolTradeError, state::Authority},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetPausedContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user: pauser
+    pub user: Signer<'info>,
+}
+
+pub fn set_paused(ctx: Context<SetPausedContext>, paused: bool) -> Result<()> {
+    let authority = &mut ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-only the pauser can pause / unpause
+    require!(
+        authority.pauser == user.key(),
+        SolTradeError::PauserUnauthorized
+    );
+
+    authority.paused = paused;
+
+    Ok(())
+}
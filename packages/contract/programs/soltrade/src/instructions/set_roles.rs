@@ -0,0 +1,38 @@
+use {
+    crate::{error::SolTradeError, state::Authority},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetRolesContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user: admin
+    pub user: Signer<'info>,
+}
+
+pub fn set_roles(ctx: Context<SetRolesContext>, pauser: Pubkey, fee_manager: Pubkey) -> Result<()> {
+    let authority = &mut ctx.accounts.authority;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-only the admin can assign roles
+    require!(
+        authority.user == user.key(),
+        SolTradeError::AdminUnauthorized
+    );
+
+    authority.pauser = pauser;
+    authority.fee_manager = fee_manager;
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use {
+    crate::{
+        state::{Authority, UserProfile},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetSolPayoutContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? user_profile: profile of the user, created on the first trade or offer
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            user.key().as_ref(),
+        ],
+        space = UserProfile::SIZE,
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    // ? user: who gets the lamports at settlement
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // ? system_program: system program
+    pub system_program: Program<'info, System>,
+}
+
+// ? wrapped = true pays the lamports of settlements to the wrapped SOL associated token account of
+// ? the user, which must exist
+pub fn set_sol_payout(ctx: Context<SetSolPayoutContext>, wrapped: bool) -> Result<()> {
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+
+    let user_profile = &mut ctx.accounts.user_profile;
+    user_profile.user = ctx.accounts.user.key();
+    user_profile.wsol_payout = wrapped;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, Trade, TradeMode},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct StartAuctionContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade to auction, its assets are the lot
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? user: owner of the trade
+    pub user: Signer<'info>,
+}
+
+pub fn start_auction(
+    ctx: Context<StartAuctionContext>,
+    auction_mint: Pubkey,
+    reserve_price: u64,
+    min_increment: u64,
+    end_time: i64,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let user = &ctx.accounts.user;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner can auction the trade
+    require!(trade.user == user.key(), SolTradeError::AuctionUnauthorized);
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-only a plain trade without offers can become an auction
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(trade.offer_count == 0, SolTradeError::AuctionHasOffers);
+    // |-end time in the future
+    require!(
+        end_time > Clock::get()?.unix_timestamp,
+        SolTradeError::AuctionInvalidEndTime
+    );
+
+    // * the lot is frozen from now on
+    trade.mode = TradeMode::Auction.to_code();
+    trade.auction_mint = auction_mint;
+    trade.reserve_price = reserve_price;
+    trade.min_increment = min_increment;
+    trade.end_time = end_time;
+    trade.best_offer = Pubkey::default();
+    trade.best_bid = 0;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Authority, DutchAuctionDecay, Trade, TradeMode},
+        utils::assert_not_paused,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct StartDutchAuctionContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade to auction, its assets are the lot
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? user: owner of the trade
+    pub user: Signer<'info>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn start_dutch_auction(
+    ctx: Context<StartDutchAuctionContext>,
+    price_mint: Pubkey,
+    start_price: u64,
+    end_price: u64,
+    start_time: i64,
+    end_time: i64,
+    decay: u8,
+    step_interval: i64,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let user = &ctx.accounts.user;
+    let decay = DutchAuctionDecay::from_code(decay)?;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-only the owner can auction the trade
+    require!(trade.user == user.key(), SolTradeError::AuctionUnauthorized);
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    // |-only a plain trade without offers can become an auction
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(trade.offer_count == 0, SolTradeError::AuctionHasOffers);
+    // |-price goes down
+    require!(
+        start_price > 0 && start_price >= end_price,
+        SolTradeError::DutchAuctionInvalidPrices
+    );
+    // |-ends in the future, after it starts
+    require!(
+        end_time > start_time && end_time > Clock::get()?.unix_timestamp,
+        SolTradeError::DutchAuctionInvalidTimes
+    );
+    // |-stepwise decay drops at least once
+    if decay == DutchAuctionDecay::Stepwise {
+        require!(
+            step_interval > 0 && step_interval <= end_time - start_time,
+            SolTradeError::DutchAuctionInvalidTimes
+        );
+    }
+
+    // * the lot is frozen from now on
+    trade.mode = TradeMode::DutchAuction.to_code();
+    trade.auction_mint = price_mint;
+    trade.start_price = start_price;
+    trade.end_price = end_price;
+    trade.start_time = start_time;
+    trade.end_time = end_time;
+    trade.decay = decay.to_code();
+    trade.step_interval = match decay {
+        DutchAuctionDecay::Linear => 0,
+        DutchAuctionDecay::Stepwise => step_interval,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{AssetItemType, Authority, AutoAcceptRule, Offer, Trade, TradeMode, UserProfile},
+        utils::{
+            assert_not_paused, assert_offer_of_trade, lot_asset_item, settle_lot,
+            sol_payout_account, sync_sol_payouts, SettleLotAccounts,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::Token,
+};
+
+#[derive(Accounts)]
+pub struct TryAutoAcceptContext<'info> {
+    // ? authority: program authority, checked for the paused flag
+    #[account(
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? trade: trade with an auto accept rule
+    #[account(mut)]
+    pub trade: Account<'info, Trade>,
+
+    // ? offer: offer to check against the rule
+    #[account(mut)]
+    pub offer: Account<'info, Offer>,
+
+    // ? maker: owner of the trade, gets the assets of the offer
+    #[account(mut)]
+    /// CHECK: checked against the trade user in runtime
+    pub maker: AccountInfo<'info>,
+
+    // ? taker: owner of the offer, gets the assets of the trade
+    #[account(mut)]
+    /// CHECK: checked against the offer user in runtime
+    pub taker: AccountInfo<'info>,
+
+    // ? maker_profile: profile of the trade owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            trade.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub maker_profile: Account<'info, UserProfile>,
+
+    // ? taker_profile: profile of the offer owner
+    #[account(
+        mut,
+        seeds = [
+            UserProfile::PREFIX.as_bytes(),
+            offer.user.as_ref(),
+        ],
+        bump,
+    )]
+    pub taker_profile: Account<'info, UserProfile>,
+
+    // ? user: anyone, auto accept is permissionless
+    pub user: Signer<'info>,
+
+    // ======================[ WRAPPED SOL PAYOUT ONLY ]======================
+    // ? maker_wsol_account: wrapped SOL account of the maker, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the maker in runtime
+    pub maker_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? taker_wsol_account: wrapped SOL account of the taker, when its profile asks for it
+    #[account(mut)]
+    /// CHECK: checked against the profile of the taker in runtime
+    pub taker_wsol_account: Option<UncheckedAccount<'info>>,
+
+    // ? token_program: token program, required to pay a wrapped SOL account
+    pub token_program: Option<Program<'info, Token>>,
+    //
+    // ? remaining_accounts: every asset item of the offer then every asset item of the trade,
+    // ? each ordered by index, then the creators of the nfts of the trade and of the offer
+}
+
+// ? same as accept_offer followed by the exchange of every asset item
+pub fn try_auto_accept<'info>(
+    ctx: Context<'_, '_, '_, 'info, TryAutoAcceptContext<'info>>,
+) -> Result<()> {
+    let trade = &mut ctx.accounts.trade;
+    let offer = &mut ctx.accounts.offer;
+    let maker = &ctx.accounts.maker;
+    let taker = &ctx.accounts.taker;
+    let rule = AutoAcceptRule::from_code(trade.auto_accept_rule)?;
+
+    // ! @check
+    // |-program must not be paused
+    assert_not_paused(&ctx.accounts.authority)?;
+    // |-trade open to offers, with auto accept on
+    require!(!trade.cancelled, SolTradeError::TradeCancelled);
+    require!(
+        trade.mode == TradeMode::Offer.to_code(),
+        SolTradeError::TradeInvalidMode
+    );
+    require!(
+        rule != AutoAcceptRule::Disabled,
+        SolTradeError::AutoAcceptDisabled
+    );
+    // |-offer must belong to the trade, and only one offer can be accepted
+    assert_offer_of_trade(offer, &trade.key())?;
+    require!(
+        trade.accepted_offer == Pubkey::default(),
+        SolTradeError::OfferAlreadyAccepted
+    );
+    require!(
+        !offer.counter_pending,
+        SolTradeError::AcceptOfferCounterPending
+    );
+    // |-assets go to their owners
+    require!(
+        trade.user == maker.key() && offer.user == taker.key(),
+        SolTradeError::AutoAcceptInvalidUsers
+    );
+    // |-every asset item of both sides must be passed
+    let offer_asset_count = offer.asset_count as usize;
+    let asset_count = offer_asset_count + trade.asset_count as usize;
+    require!(
+        ctx.remaining_accounts.len() >= asset_count,
+        SolTradeError::AutoAcceptInvalidAssetItems
+    );
+    let (asset_items, creators) = ctx.remaining_accounts.split_at(asset_count);
+    let (offer_asset_items, trade_asset_items) = asset_items.split_at(offer_asset_count);
+    let maker_payout = sol_payout_account(
+        maker,
+        Some(&ctx.accounts.maker_profile),
+        ctx.accounts.maker_wsol_account.as_deref(),
+    )?;
+    let taker_payout = sol_payout_account(
+        taker,
+        Some(&ctx.accounts.taker_profile),
+        ctx.accounts.taker_wsol_account.as_deref(),
+    )?;
+
+    // ! @check
+    // |-offer must match the rule, what is left after counter offers is checked
+    let mut offer_assets = Vec::new();
+    for (index, asset_item) in offer_asset_items.iter().enumerate() {
+        if let Some(read) = lot_asset_item(asset_item, index as u32, &offer.key(), None)? {
+            offer_assets.push((read.asset_type, read.mint, read.amount, read.collection));
+        }
+    }
+    let total = |asset_type: AssetItemType, mint: Option<Pubkey>| {
+        offer_assets
+            .iter()
+            .filter(|(t, m, _, _)| *t == asset_type && mint.map_or(true, |mint| *m == mint))
+            .fold(0u64, |total, (_, _, amount, _)| {
+                total.saturating_add(*amount)
+            })
+    };
+    let matched = match rule {
+        AutoAcceptRule::MinLamports => total(AssetItemType::SOL, None) >= trade.auto_accept_amount,
+        AutoAcceptRule::MinTokens => {
+            total(AssetItemType::SPL, Some(trade.auto_accept_mint)) >= trade.auto_accept_amount
+        }
+        AutoAcceptRule::WantedAssets => trade.wanted_assets_met(&offer_assets)?,
+        AutoAcceptRule::Disabled => false,
+    };
+    require!(matched, SolTradeError::AutoAcceptNotMatched);
+    let offer_sol_amount = total(AssetItemType::SOL, None);
+
+    // * accept
+    trade.accepted_offer = offer.key();
+    offer.accepted = true;
+
+    // * assets of the trade to the taker, the lamports of the offer pay the maker and the creators
+    // * of the nfts of the trade. lamports of both sides stay in escrow until paid here
+    let mut creators = creators.iter();
+    let trade_account_info = trade.to_account_info();
+    let offer_account_info = offer.to_account_info();
+    let trade_sol_amount = settle_lot(
+        SettleLotAccounts {
+            lot: trade_asset_items,
+            from: &trade_account_info,
+            receiver: taker,
+            sol_receiver: &taker_payout,
+            payer: &offer_account_info,
+            seller: &maker_payout,
+            system_program: None,
+        },
+        offer_sol_amount,
+        true,
+        &mut creators,
+    )?
+    .iter()
+    .fold(0u64, |total, released| {
+        total.checked_add(released.lamports()).unwrap()
+    });
+    trade.exchanged_count = trade.asset_count;
+
+    // * assets of the offer to the maker, the lamports of the trade pay the taker and the creators
+    // * of the nfts of the offer
+    settle_lot(
+        SettleLotAccounts {
+            lot: offer_asset_items,
+            from: &offer_account_info,
+            receiver: maker,
+            sol_receiver: &maker_payout,
+            payer: &trade_account_info,
+            seller: &taker_payout,
+            system_program: None,
+        },
+        trade_sol_amount,
+        true,
+        &mut creators,
+    )?;
+    offer.exchanged_count = offer.asset_count;
+    let token_program = ctx
+        .accounts
+        .token_program
+        .as_ref()
+        .map(|token_program| token_program.to_account_info());
+    sync_sol_payouts(
+        &[&maker_payout, &taker_payout],
+        &[&trade_account_info, &offer_account_info],
+        token_program.as_ref(),
+    )?;
+    let sol_amount = offer_sol_amount.checked_add(trade_sol_amount).unwrap();
+
+    // * update the profiles of both sides.
+    // * when maker and taker are the same wallet both accounts are the same profile,
+    // * so both copies get the same update and the last one written wins
+    for profile in [
+        &mut ctx.accounts.maker_profile,
+        &mut ctx.accounts.taker_profile,
+    ] {
+        profile.sol_volume = profile.sol_volume.checked_add(sol_amount).unwrap();
+        profile.trades_completed = profile.trades_completed.checked_add(1).unwrap();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use {
+    crate::{program::Soltrade, state::Authority, utils::assert_upgrade_authority},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct UpdateAuthorityConfigContext<'info> {
+    // ? authority: program authority
+    #[account(
+        mut,
+        seeds = [
+            Authority::PREFIX.as_bytes(),
+            crate::id().as_ref(),
+        ],
+        bump,
+    )]
+    pub authority: Account<'info, Authority>,
+
+    // ? program: this program, used to find its program data account
+    pub program: Program<'info, Soltrade>,
+
+    // ? program_data: program data account of this program, holds the upgrade authority
+    pub program_data: Account<'info, ProgramData>,
+
+    // ? user: upgrade authority of the program
+    pub user: Signer<'info>,
+}
+
+pub fn update_authority_config(
+    ctx: Context<UpdateAuthorityConfigContext>,
+    user: Pubkey,
+    pauser: Pubkey,
+    fee_manager: Pubkey,
+) -> Result<()> {
+    // ! @check
+    // |-only the upgrade authority can overwrite the config
+    assert_upgrade_authority(
+        &ctx.accounts.program,
+        &ctx.accounts.program_data,
+        ctx.accounts.user.key,
+    )?;
+
+    let authority = &mut ctx.accounts.authority;
+
+    // * overwrite the roles, this also recovers a lost admin key
+    authority.user = user;
+    authority.pending_user = Pubkey::default();
+    authority.pauser = pauser;
+    authority.fee_manager = fee_manager;
+
+    Ok(())
+}
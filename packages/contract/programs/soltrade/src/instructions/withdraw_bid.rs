@@ -0,0 +1,75 @@
+use {
+    crate::{
+        error::SolTradeError,
+        state::{Offer, Trade},
+        utils::{assert_offer_of_trade, release_bid},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Token, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawBidContext<'info> {
+    // ? trade: auction of the bid
+    pub trade: Box<Account<'info, Trade>>,
+
+    // ? offer: outbid offer, holds the bid in its bid vault
+    #[account(mut)]
+    pub offer: Box<Account<'info, Offer>>,
+
+    // ? bid_vault: escrow of the bid, owned by the offer
+    #[account(mut)]
+    pub bid_vault: Box<Account<'info, TokenAccount>>,
+
+    // ? user_token_account: token account of the owner of the offer, gets the bid back
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    // ? user: owner of the offer
+    pub user: Signer<'info>,
+
+    // ? token_program: token program
+    pub token_program: Program<'info, Token>,
+}
+
+// ? bids in tokens are not pushed back when outbid, the owner of the offer takes them back here.
+// ? not checked against the paused flag, an outbid bid must always be withdrawable
+pub fn withdraw_bid(ctx: Context<WithdrawBidContext>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let trade = &accounts.trade;
+    let offer = &mut accounts.offer;
+
+    // ! @check
+    // |-offer of the auction, withdrawn by its owner
+    assert_offer_of_trade(offer, &trade.key())?;
+    require_keys_eq!(
+        offer.user,
+        accounts.user.key(),
+        SolTradeError::WithdrawBidUnauthorized
+    );
+    // |-a bid in tokens, not paid back yet
+    require!(
+        trade.auction_mint != Pubkey::default() && offer.bid_amount > 0 && !offer.bid_refunded,
+        SolTradeError::WithdrawBidNothingToWithdraw
+    );
+    // |-the leading bid stays until the auction is finalized, where it pays the maker
+    require_keys_neq!(
+        offer.key(),
+        trade.best_offer,
+        SolTradeError::WithdrawBidLeading
+    );
+
+    // * the offer signs the transfer out of its bid vault
+    release_bid(
+        trade,
+        offer,
+        &accounts.user.to_account_info(),
+        Some(&accounts.user_token_account),
+        Some(&accounts.bid_vault),
+        Some(&accounts.token_program),
+        SolTradeError::WithdrawBidInvalidAccounts,
+    )?;
+    offer.bid_refunded = true;
+
+    Ok(())
+}
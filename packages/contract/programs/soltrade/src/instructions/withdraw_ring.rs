@@ -0,0 +1,34 @@
+use {
+    crate::{error::SolTradeError, state::Ring},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawRingContext<'info> {
+    // ? ring: ring to withdraw from
+    #[account(mut)]
+    pub ring: Account<'info, Ring>,
+
+    // ? user: participant withdrawing
+    pub user: Signer<'info>,
+}
+
+// ? not checked against the paused flag, withdrawing is the first step of a refund. the ring is
+// ? cancelled for everyone, every asset item goes back with claim_ring_asset_item
+pub fn withdraw_ring(ctx: Context<WithdrawRingContext>) -> Result<()> {
+    let ring = &mut ctx.accounts.ring;
+
+    // ! @check
+    // |-ring still open, a settled ring can't be undone
+    require!(ring.is_open(), SolTradeError::RingClosed);
+    // |-only participants withdraw
+    require!(
+        ring.participant_bit(&ctx.accounts.user.key()).is_some(),
+        SolTradeError::RingNotParticipant
+    );
+
+    // * cancel
+    ring.cancelled = true;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::instructions::*;
+use crate::state::{SignedOffer, WantedAsset};
 use anchor_lang::prelude::*;
 
 pub mod error;
@@ -9,6 +10,8 @@ pub mod utils;
 declare_id!("AHwXuw8go83wWKF8PSXeMAzfaPhxF5t3GH7n4jQRPpJW");
 
 #[program]
+// ? every handler forwards to its instruction with an explicit return
+#[allow(clippy::needless_return)]
 pub mod soltrade {
     use super::*;
 
@@ -16,20 +19,74 @@ pub mod soltrade {
         return instructions::initialize(ctx);
     }
 
+    pub fn propose_admin(ctx: Context<ProposeAdminContext>, new_user: Pubkey) -> Result<()> {
+        return instructions::propose_admin(ctx, new_user);
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdminContext>) -> Result<()> {
+        return instructions::accept_admin(ctx);
+    }
+
+    pub fn set_roles(
+        ctx: Context<SetRolesContext>,
+        pauser: Pubkey,
+        fee_manager: Pubkey,
+    ) -> Result<()> {
+        return instructions::set_roles(ctx, pauser, fee_manager);
+    }
+
+    pub fn set_paused(ctx: Context<SetPausedContext>, paused: bool) -> Result<()> {
+        return instructions::set_paused(ctx, paused);
+    }
+
+    pub fn update_authority_config(
+        ctx: Context<UpdateAuthorityConfigContext>,
+        user: Pubkey,
+        pauser: Pubkey,
+        fee_manager: Pubkey,
+    ) -> Result<()> {
+        return instructions::update_authority_config(ctx, user, pauser, fee_manager);
+    }
+
+    pub fn set_mint_registry_mode(
+        ctx: Context<SetMintRegistryModeContext>,
+        mode: u8,
+    ) -> Result<()> {
+        return instructions::set_mint_registry_mode(ctx, mode);
+    }
+
+    pub fn set_mint_registry_entry(
+        ctx: Context<SetMintRegistryEntryContext>,
+        mint: Pubkey,
+        status: u8,
+    ) -> Result<()> {
+        return instructions::set_mint_registry_entry(ctx, mint, status);
+    }
+
+    pub fn remove_mint_registry_entry(ctx: Context<RemoveMintRegistryEntryContext>) -> Result<()> {
+        return instructions::remove_mint_registry_entry(ctx);
+    }
+
     pub fn create_trade(
         ctx: Context<CreateTradeContext>,
         trade_index: u32,
         allowed_users: Vec<Pubkey>,
+        wanted_assets: Vec<WantedAsset>,
+        strict: bool,
     ) -> Result<()> {
-        return instructions::create_trade(ctx, trade_index, allowed_users);
+        return instructions::create_trade(ctx, trade_index, allowed_users, wanted_assets, strict);
     }
 
-    pub fn create_offer(ctx: Context<CreateOfferContext>, index: u32) -> Result<()> {
-        return instructions::create_offer(ctx, index);
+    pub fn create_offer(
+        ctx: Context<CreateOfferContext>,
+        index: u32,
+        bid_amount: u64,
+    ) -> Result<()> {
+        return instructions::create_offer(ctx, index, bid_amount);
     }
 
-    pub fn add_asset_item(
-        ctx: Context<AddAssetItemContext>,
+    pub fn add_asset_item<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddAssetItemContext<'info>>,
         from_type: u8,
         index: u32,
         asset_type_index: u32,
@@ -46,12 +103,112 @@ pub mod soltrade {
         );
     }
 
-    pub fn accept_offer(ctx: Context<AcceptOfferContext>) -> Result<()> {
-        return instructions::accept_offer(ctx);
+    pub fn add_asset_items<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddAssetItemsContext<'info>>,
+        from_type: u8,
+        assets: Vec<AssetItemSpec>,
+    ) -> Result<()> {
+        return instructions::add_asset_items(ctx, from_type, assets);
+    }
+
+    pub fn add_asset_item_pnft(
+        ctx: Context<AddAssetItemPnftContext>,
+        from_type: u8,
+        index: u32,
+        asset_type_index: u32,
+    ) -> Result<()> {
+        return instructions::add_asset_item_pnft(ctx, from_type, index, asset_type_index);
     }
 
-    pub fn exchange(
-        ctx: Context<ExchangeContext>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_asset_item_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddAssetItemCnftContext<'info>>,
+        from_type: u8,
+        index: u32,
+        asset_type_index: u32,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        leaf_index: u32,
+    ) -> Result<()> {
+        return instructions::add_asset_item_cnft(
+            ctx,
+            from_type,
+            index,
+            asset_type_index,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            leaf_index,
+        );
+    }
+
+    pub fn add_asset_item_core(
+        ctx: Context<AddAssetItemCoreContext>,
+        from_type: u8,
+        index: u32,
+        asset_type_index: u32,
+    ) -> Result<()> {
+        return instructions::add_asset_item_core(ctx, from_type, index, asset_type_index);
+    }
+
+    pub fn add_asset_item_stake(
+        ctx: Context<AddAssetItemStakeContext>,
+        from_type: u8,
+        index: u32,
+        asset_type_index: u32,
+    ) -> Result<()> {
+        return instructions::add_asset_item_stake(ctx, from_type, index, asset_type_index);
+    }
+
+    pub fn add_asset_item_authority(
+        ctx: Context<AddAssetItemAuthorityContext>,
+        from_type: u8,
+        index: u32,
+        asset_type_index: u32,
+        kind: u8,
+    ) -> Result<()> {
+        return instructions::add_asset_item_authority(
+            ctx,
+            from_type,
+            index,
+            asset_type_index,
+            kind,
+        );
+    }
+
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptOfferContext<'info>>,
+        round: u32,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        return instructions::accept_offer(ctx, round, content_hash);
+    }
+
+    pub fn counter_offer(
+        ctx: Context<CounterOfferContext>,
+        sol_amount: u64,
+        drop_asset_items: Vec<Pubkey>,
+        requested_assets: Vec<WantedAsset>,
+    ) -> Result<()> {
+        return instructions::counter_offer(ctx, sol_amount, drop_asset_items, requested_assets);
+    }
+
+    pub fn accept_counter_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptCounterOfferContext<'info>>,
+        round: u32,
+    ) -> Result<()> {
+        return instructions::accept_counter_offer(ctx, round);
+    }
+
+    pub fn cancel_counter_offer(ctx: Context<CancelCounterOfferContext>) -> Result<()> {
+        return instructions::cancel_counter_offer(ctx);
+    }
+
+    pub fn exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExchangeContext<'info>>,
         trade_index: u32,
         offer_index: u32,
         from_type: u8,
@@ -69,6 +226,200 @@ pub mod soltrade {
             asset_type_index,
         );
     }
+
+    pub fn cancel_trade(ctx: Context<CancelTradeContext>) -> Result<()> {
+        return instructions::cancel_trade(ctx);
+    }
+
+    pub fn refund_asset_item(
+        ctx: Context<RefundAssetItemContext>,
+        from_type: u8,
+        asset_type: u8,
+    ) -> Result<()> {
+        return instructions::refund_asset_item(ctx, from_type, asset_type);
+    }
+
+    pub fn claim_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimCnftContext<'info>>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        return instructions::claim_cnft(ctx, root);
+    }
+
+    pub fn claim_core(ctx: Context<ClaimCoreContext>) -> Result<()> {
+        return instructions::claim_core(ctx);
+    }
+
+    pub fn claim_stake(ctx: Context<ClaimStakeContext>) -> Result<()> {
+        return instructions::claim_stake(ctx);
+    }
+
+    pub fn claim_authority(ctx: Context<ClaimAuthorityContext>) -> Result<()> {
+        return instructions::claim_authority(ctx);
+    }
+
+    pub fn claim_nft(ctx: Context<ClaimNftContext>) -> Result<()> {
+        return instructions::claim_nft(ctx);
+    }
+
+    pub fn claim_pnft(ctx: Context<ClaimPnftContext>) -> Result<()> {
+        return instructions::claim_pnft(ctx);
+    }
+
+    pub fn claim_spl<'info>(ctx: Context<'_, '_, '_, 'info, ClaimSplContext<'info>>) -> Result<()> {
+        return instructions::claim_spl(ctx);
+    }
+
+    pub fn start_auction(
+        ctx: Context<StartAuctionContext>,
+        auction_mint: Pubkey,
+        reserve_price: u64,
+        min_increment: u64,
+        end_time: i64,
+    ) -> Result<()> {
+        return instructions::start_auction(
+            ctx,
+            auction_mint,
+            reserve_price,
+            min_increment,
+            end_time,
+        );
+    }
+
+    pub fn finalize_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeAuctionContext<'info>>,
+    ) -> Result<()> {
+        return instructions::finalize_auction(ctx);
+    }
+
+    pub fn withdraw_bid(ctx: Context<WithdrawBidContext>) -> Result<()> {
+        return instructions::withdraw_bid(ctx);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_dutch_auction(
+        ctx: Context<StartDutchAuctionContext>,
+        price_mint: Pubkey,
+        start_price: u64,
+        end_price: u64,
+        start_time: i64,
+        end_time: i64,
+        decay: u8,
+        step_interval: i64,
+    ) -> Result<()> {
+        return instructions::start_dutch_auction(
+            ctx,
+            price_mint,
+            start_price,
+            end_price,
+            start_time,
+            end_time,
+            decay,
+            step_interval,
+        );
+    }
+
+    pub fn buy_dutch_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyDutchAuctionContext<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        return instructions::buy_dutch_auction(ctx, max_price);
+    }
+
+    pub fn list_fixed_price(
+        ctx: Context<ListFixedPriceContext>,
+        price_mint: Pubkey,
+        price: u64,
+    ) -> Result<()> {
+        return instructions::list_fixed_price(ctx, price_mint, price);
+    }
+
+    pub fn buy<'info>(ctx: Context<'_, '_, '_, 'info, BuyContext<'info>>) -> Result<()> {
+        return instructions::buy(ctx);
+    }
+
+    pub fn set_auto_accept(
+        ctx: Context<SetAutoAcceptContext>,
+        rule: u8,
+        mint: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        return instructions::set_auto_accept(ctx, rule, mint, amount);
+    }
+
+    pub fn try_auto_accept<'info>(
+        ctx: Context<'_, '_, '_, 'info, TryAutoAcceptContext<'info>>,
+    ) -> Result<()> {
+        return instructions::try_auto_accept(ctx);
+    }
+
+    pub fn create_collection_offer(
+        ctx: Context<CreateCollectionOfferContext>,
+        collection: Pubkey,
+        price: u64,
+        quantity: u32,
+    ) -> Result<()> {
+        return instructions::create_collection_offer(ctx, collection, price, quantity);
+    }
+
+    pub fn fill_collection_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, FillCollectionOfferContext<'info>>,
+    ) -> Result<()> {
+        return instructions::fill_collection_offer(ctx);
+    }
+
+    pub fn cancel_collection_offer(ctx: Context<CancelCollectionOfferContext>) -> Result<()> {
+        return instructions::cancel_collection_offer(ctx);
+    }
+
+    pub fn set_sol_payout(ctx: Context<SetSolPayoutContext>, wrapped: bool) -> Result<()> {
+        return instructions::set_sol_payout(ctx, wrapped);
+    }
+
+    pub fn create_ring(ctx: Context<CreateRingContext>, participants: Vec<Pubkey>) -> Result<()> {
+        return instructions::create_ring(ctx, participants);
+    }
+
+    pub fn add_ring_asset_item<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddRingAssetItemContext<'info>>,
+        asset_type: u8,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        return instructions::add_ring_asset_item(ctx, asset_type, recipient, amount);
+    }
+
+    pub fn confirm_ring(ctx: Context<ConfirmRingContext>) -> Result<()> {
+        return instructions::confirm_ring(ctx);
+    }
+
+    pub fn withdraw_ring(ctx: Context<WithdrawRingContext>) -> Result<()> {
+        return instructions::withdraw_ring(ctx);
+    }
+
+    pub fn claim_ring_asset_item<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRingAssetItemContext<'info>>,
+    ) -> Result<()> {
+        return instructions::claim_ring_asset_item(ctx);
+    }
+
+    pub fn direct_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, DirectSwapContext<'info>>,
+        assets: Vec<DirectSwapAsset>,
+    ) -> Result<()> {
+        return instructions::direct_swap(ctx, assets);
+    }
+
+    pub fn fill_signed_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, FillSignedOfferContext<'info>>,
+        offer: SignedOffer,
+    ) -> Result<()> {
+        return instructions::fill_signed_offer(ctx, offer);
+    }
+
+    pub fn cancel_signed_offer(ctx: Context<CancelSignedOfferContext>, nonce: u64) -> Result<()> {
+        return instructions::cancel_signed_offer(ctx, nonce);
+    }
 }
 
 #[derive(Accounts)]
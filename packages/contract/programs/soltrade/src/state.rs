@@ -2,23 +2,101 @@ use {crate::error::SolTradeError, anchor_lang::prelude::*};
 
 #[account]
 pub struct Authority {
-    pub user: Pubkey,     // 32
+    pub user: Pubkey,     // 32 -> admin
     pub trade_count: u32, // 4
+
+    // governance
+    pub pending_user: Pubkey, // 32 -> proposed admin, default pubkey when there is none
+    pub pauser: Pubkey,       // 32 -> who can pause / unpause the program
+    pub fee_manager: Pubkey,  // 32 -> who can manage fees
+    pub paused: bool,         // 1
+
+    // mint registry
+    pub mint_registry_mode: u8, // 1 -> MintRegistryMode code
 }
-// size = 8 + (32) + (4)
+// size = 8 + (32) + (4) + (32) + (32) + (32) + (1) + (1)
 impl Authority {
-    pub const SIZE: usize = 44;
+    pub const SIZE: usize = 142;
     pub const PREFIX: &'static str = "authority";
 }
 
+// ===================================================
+// =================[ MINT REGISTRY ]=================
+// ===================================================
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum MintRegistryMode {
+    Open,
+    Allowlist,
+    Denylist,
+}
+impl MintRegistryMode {
+    pub fn from_code(code: u8) -> Result<MintRegistryMode> {
+        match code {
+            0 => Ok(MintRegistryMode::Open),
+            1 => Ok(MintRegistryMode::Allowlist),
+            2 => Ok(MintRegistryMode::Denylist),
+            unknown_code => {
+                msg!("Unknow MintRegistryMode : {}", unknown_code);
+                Err(SolTradeError::MintRegistryInvalidMode.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            MintRegistryMode::Open => 0,
+            MintRegistryMode::Allowlist => 1,
+            MintRegistryMode::Denylist => 2,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum MintRegistryStatus {
+    Allowed,
+    Denied,
+}
+impl MintRegistryStatus {
+    pub fn from_code(code: u8) -> Result<MintRegistryStatus> {
+        match code {
+            1 => Ok(MintRegistryStatus::Allowed),
+            2 => Ok(MintRegistryStatus::Denied),
+            unknown_code => {
+                msg!("Unknow MintRegistryStatus : {}", unknown_code);
+                Err(SolTradeError::MintRegistryInvalidStatus.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            MintRegistryStatus::Allowed => 1,
+            MintRegistryStatus::Denied => 2,
+        }
+    }
+}
+
+#[account]
+pub struct MintRegistryEntry {
+    pub mint: Pubkey, // 32
+    pub status: u8,   // 1 -> MintRegistryStatus code
+}
+// size = 8 + (32) + (1)
+impl MintRegistryEntry {
+    pub const SIZE: usize = 41;
+    pub const PREFIX: &'static str = "mint_registry";
+}
+
 // ===================================================
 // =====================[ ASSET ]=====================
 // ===================================================
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
 pub enum AssetItemType {
     SOL,
     SPL,
     CNFT,
+    NFT,
+    CORE,
+    STAKE,
+    AUTHORITY,
 }
 impl AssetItemType {
     pub fn from_code(code: u8) -> Result<AssetItemType> {
@@ -26,6 +104,10 @@ impl AssetItemType {
             1 => Ok(AssetItemType::SOL),
             2 => Ok(AssetItemType::SPL),
             3 => Ok(AssetItemType::CNFT),
+            4 => Ok(AssetItemType::NFT),
+            5 => Ok(AssetItemType::CORE),
+            6 => Ok(AssetItemType::STAKE),
+            7 => Ok(AssetItemType::AUTHORITY),
             unknown_code => {
                 msg!("Unknow AssetItemType : {}", unknown_code);
                 Err(SolTradeError::InvalidAssetItemType.into())
@@ -37,9 +119,71 @@ impl AssetItemType {
             AssetItemType::SOL => 1,
             AssetItemType::SPL => 2,
             AssetItemType::CNFT => 3,
+            AssetItemType::NFT => 4,
+            AssetItemType::CORE => 5,
+            AssetItemType::STAKE => 6,
+            AssetItemType::AUTHORITY => 7,
         }
     }
+
+    // ? nfts whose creators get royalties out of the lamports paid for them
+    pub fn has_royalties(&self) -> bool {
+        matches!(self, AssetItemType::NFT | AssetItemType::CORE)
+    }
+}
+
+// ? what a released asset item was worth
+pub struct ReleasedAssetItem {
+    pub asset_type: AssetItemType,
+    pub mint: Pubkey, // default pubkey for SOL
+    pub amount: u64,
+    pub collection: Option<Pubkey>, // verified collection of an nft, collection of a core asset
+}
+impl ReleasedAssetItem {
+    pub fn lamports(&self) -> u64 {
+        match self.asset_type {
+            AssetItemType::SOL => self.amount,
+            _ => 0,
+        }
+    }
+}
+
+// ? what every asset item account has, settlements, refunds and counter offers check and
+// ? release asset items through it
+pub trait AssetItem {
+    fn index(&self) -> u32;
+    fn from(&self) -> Pubkey;
+    fn user(&self) -> Pubkey;
+    fn exchanged(&self) -> bool;
+
+    // ? what the asset item is worth, used by the offer content hash
+    fn content(&self) -> Vec<u8>;
+
+    // ? what the asset item is worth once released
+    fn released(&self) -> ReleasedAssetItem;
+
+    // ? the assets in escrow are claimed by the receiver, lamports are moved by the caller
+    fn release(&mut self, receiver: Pubkey);
 }
+
+// ? the fields every asset item starts with
+macro_rules! asset_item_fields {
+    () => {
+        fn index(&self) -> u32 {
+            self.index
+        }
+        fn from(&self) -> Pubkey {
+            self.from
+        }
+        fn user(&self) -> Pubkey {
+            self.user
+        }
+        fn exchanged(&self) -> bool {
+            self.exchanged
+        }
+    };
+}
+
 // #[account]
 // pub struct BaseAssetItem {
 //     pub index: u32,     // 4
@@ -66,6 +210,28 @@ pub struct AssetItemSOL {
 impl AssetItemSOL {
     pub const SIZE: usize = 90;
     pub const PREFIX: &'static str = "asset_item_sol";
+    // ? seeds = [asset_item_sol, WSOL_UNWRAP_PREFIX], token account unwrapping a wrapped SOL deposit
+    pub const WSOL_UNWRAP_PREFIX: &'static str = "wsol_unwrap";
+}
+impl AssetItem for AssetItemSOL {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        self.amount.to_le_bytes().to_vec()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::SOL,
+            mint: Pubkey::default(),
+            amount: self.amount,
+            collection: None,
+        }
+    }
+
+    fn release(&mut self, _receiver: Pubkey) {
+        self.exchanged = true;
+    }
 }
 #[account]
 pub struct AssetItemSPL {
@@ -77,16 +243,552 @@ pub struct AssetItemSPL {
     pub exchanged: bool, // 1
     pub token: Pubkey,   // 32
     pub mint: Pubkey,    // 32
+    pub amount: u64,     // 8 -> amount of tokens received by the vault, after transfer fees
+    pub decimals: u8,    // 1 -> decimals of the mint, amount / 10^decimals for display
+
+    // tokens are held in escrow, in the associated token account of the trade or offer
+    pub vault: Pubkey,         // 32
+    pub token_program: Pubkey, // 32 -> token program or token 2022 program of the mint
+    pub escrowed: bool,        // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey,      // 32 -> who the asset item was released to
 }
-// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (32)
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (32) + (8) + (1) + (32) + (32) + (1)
+//      + (32)
 impl AssetItemSPL {
-    pub const SIZE: usize = 146;
+    pub const SIZE: usize = 252;
     pub const PREFIX: &'static str = "asset_item_spl";
 }
+impl AssetItem for AssetItemSPL {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [
+            self.mint.as_ref(),
+            self.token.as_ref(),
+            self.amount.to_le_bytes().as_ref(),
+        ]
+        .concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::SPL,
+            mint: self.mint,
+            amount: self.amount,
+            collection: None,
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
+
+// ? creator of an nft, share in percent of the royalties
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RoyaltyCreator {
+    pub address: Pubkey, // 32
+    pub share: u8,       // 1
+}
+// size = (32) + (1)
+impl RoyaltyCreator {
+    pub const SIZE: usize = 33;
+    pub const MAX_LEN: usize = 5;
+}
+
+// ? metaplex nft, royalties are the ones of the metadata when the item was added
+#[account]
+pub struct AssetItemNFT {
+    pub index: u32,         // 4
+    pub type_index: u32,    // 4
+    pub from: Pubkey,       // 32 -> pubkey, and it can be account address of trade or offer
+    pub user: Pubkey,       // 32
+    pub asset_type: u8,     // 1 -> define base asset type
+    pub exchanged: bool,    // 1
+    pub token: Pubkey,      // 32
+    pub mint: Pubkey,       // 32
+    pub collection: Pubkey, // 32 -> verified collection, default pubkey when none
+
+    // royalties
+    pub seller_fee_basis_points: u16,  // 2
+    pub creators: Vec<RoyaltyCreator>, // 4 + (33 * 5)
+    pub royalty_paid: u64,             // 8 -> lamports paid to the creators on settlement
+
+    // nfts are held in escrow, in the associated token account of the trade or offer, token is
+    // that account. programmable nfts are moved in and out by the token metadata program
+    pub programmable: bool, // 1
+    pub escrowed: bool,     // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey,   // 32 -> who the asset item was released to
+}
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (32) + (32) + (2) + (4 + (33 * 5)) + (8)
+//      + (1) + (1) + (32)
+impl AssetItemNFT {
+    pub const SIZE: usize = 391;
+    pub const PREFIX: &'static str = "asset_item_nft";
+
+    pub fn verified_collection(&self) -> Option<Pubkey> {
+        Some(self.collection).filter(|collection| *collection != Pubkey::default())
+    }
+}
+impl AssetItem for AssetItemNFT {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [self.mint.as_ref(), self.token.as_ref()].concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::NFT,
+            mint: self.mint,
+            amount: 1,
+            collection: self.verified_collection(),
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
+
+// ? compressed nft, the leaf is owned by the trade or offer while in escrow. the hashes are the
+// ? ones of the leaf, they don't change when its owner does
+#[account]
+pub struct AssetItemCNFT {
+    pub index: u32,      // 4
+    pub type_index: u32, // 4
+    pub from: Pubkey,    // 32 -> pubkey, and it can be account address of trade or offer
+    pub user: Pubkey,    // 32
+    pub asset_type: u8,  // 1 -> define base asset type
+    pub exchanged: bool, // 1
+
+    // leaf
+    pub tree: Pubkey,           // 32 -> merkle tree of the leaf
+    pub leaf_index: u32,        // 4
+    pub nonce: u64,             // 8
+    pub data_hash: [u8; 32],    // 32
+    pub creator_hash: [u8; 32], // 32
+
+    pub escrowed: bool, // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey, // 32 -> who the asset item was released to
+}
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (4) + (8) + (32) + (32) + (1) + (32)
+impl AssetItemCNFT {
+    pub const SIZE: usize = 223;
+    pub const PREFIX: &'static str = "asset_item_cnft";
+}
+impl AssetItem for AssetItemCNFT {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [
+            self.tree.as_ref(),
+            self.leaf_index.to_le_bytes().as_ref(),
+            self.data_hash.as_ref(),
+            self.creator_hash.as_ref(),
+        ]
+        .concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::CNFT,
+            mint: Pubkey::default(),
+            amount: 1,
+            collection: None,
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
+
+// ? metaplex core asset, a single account owned by the trade or offer while in escrow. it shares
+// ? the type indexes of the metaplex nfts, both are paid royalties in the same order
+#[account]
+pub struct AssetItemCore {
+    pub index: u32,         // 4
+    pub type_index: u32,    // 4 -> next Trade.asset_nft_count / Offer.asset_nft_count
+    pub from: Pubkey,       // 32 -> pubkey, and it can be account address of trade or offer
+    pub user: Pubkey,       // 32
+    pub asset_type: u8,     // 1 -> define base asset type
+    pub exchanged: bool,    // 1
+    pub asset: Pubkey,      // 32
+    pub collection: Pubkey, // 32 -> core collection of the asset, default pubkey when none
+
+    // royalties, the ones of the royalties plugin of the asset or else of its collection
+    pub seller_fee_basis_points: u16,  // 2
+    pub creators: Vec<RoyaltyCreator>, // 4 + (33 * 5)
+    pub royalty_paid: u64,             // 8 -> lamports paid to the creators on settlement
+
+    pub escrowed: bool, // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey, // 32 -> who the asset item was released to
+}
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (32) + (2) + (4 + (33 * 5)) + (8)
+//      + (1) + (32)
+impl AssetItemCore {
+    pub const SIZE: usize = 358;
+    pub const PREFIX: &'static str = "asset_item_core";
+
+    pub fn core_collection(&self) -> Option<Pubkey> {
+        Some(self.collection).filter(|collection| *collection != Pubkey::default())
+    }
+}
+impl AssetItem for AssetItemCore {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [self.asset.as_ref(), self.collection.as_ref()].concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::CORE,
+            mint: self.asset,
+            amount: 1,
+            collection: self.core_collection(),
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum StakeActivation {
+    Inactive,
+    Activating,
+    Active,
+    Deactivating,
+}
+impl StakeActivation {
+    pub fn from_code(code: u8) -> Result<StakeActivation> {
+        match code {
+            0 => Ok(StakeActivation::Inactive),
+            1 => Ok(StakeActivation::Activating),
+            2 => Ok(StakeActivation::Active),
+            3 => Ok(StakeActivation::Deactivating),
+            unknown_code => {
+                msg!("Unknow StakeActivation : {}", unknown_code);
+                Err(SolTradeError::InvalidStakeActivation.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            StakeActivation::Inactive => 0,
+            StakeActivation::Activating => 1,
+            StakeActivation::Active => 2,
+            StakeActivation::Deactivating => 3,
+        }
+    }
+}
+
+// ? native stake account, the trade or offer is its staker and withdrawer while in escrow. the
+// ? delegation and the lamports are the ones when the item was added
+#[account]
+pub struct AssetItemStake {
+    pub index: u32,      // 4
+    pub type_index: u32, // 4
+    pub from: Pubkey,    // 32 -> pubkey, and it can be account address of trade or offer
+    pub user: Pubkey,    // 32
+    pub asset_type: u8,  // 1 -> define base asset type
+    pub exchanged: bool, // 1
+    pub stake: Pubkey,   // 32 -> stake account
+
+    // delegation, default pubkey and zeros when the stake is not delegated
+    pub voter: Pubkey,           // 32 -> vote account of the validator
+    pub delegated_amount: u64,   // 8
+    pub activation_epoch: u64,   // 8
+    pub deactivation_epoch: u64, // 8 -> u64::MAX when not deactivating
+    pub activation: u8,          // 1 -> StakeActivation code, at the epoch the item was added
+    pub lamports: u64,           // 8 -> balance of the stake account, rent and rewards included
+
+    pub escrowed: bool, // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey, // 32 -> who the asset item was released to
+}
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (32) + (32) + (8) + (8) + (8) + (1) + (8)
+//      + (1) + (32)
+impl AssetItemStake {
+    pub const SIZE: usize = 212;
+    pub const PREFIX: &'static str = "asset_item_stake";
+}
+impl AssetItem for AssetItemStake {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [
+            self.stake.as_ref(),
+            self.voter.as_ref(),
+            self.delegated_amount.to_le_bytes().as_ref(),
+            &[self.activation],
+            self.lamports.to_le_bytes().as_ref(),
+        ]
+        .concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::STAKE,
+            mint: self.stake,
+            amount: self.lamports,
+            collection: None,
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum AuthorityKind {
+    MintTokens,
+    FreezeAccount,
+    ProgramUpgrade,
+}
+impl AuthorityKind {
+    pub fn from_code(code: u8) -> Result<AuthorityKind> {
+        match code {
+            0 => Ok(AuthorityKind::MintTokens),
+            1 => Ok(AuthorityKind::FreezeAccount),
+            2 => Ok(AuthorityKind::ProgramUpgrade),
+            unknown_code => {
+                msg!("Unknow AuthorityKind : {}", unknown_code);
+                Err(SolTradeError::InvalidAuthorityKind.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            AuthorityKind::MintTokens => 0,
+            AuthorityKind::FreezeAccount => 1,
+            AuthorityKind::ProgramUpgrade => 2,
+        }
+    }
+}
+
+// ? authority over an on-chain account, the trade or offer holds it while in escrow
+#[account]
+pub struct AssetItemAuthority {
+    pub index: u32,      // 4
+    pub type_index: u32, // 4
+    pub from: Pubkey,    // 32 -> pubkey, and it can be account address of trade or offer
+    pub user: Pubkey,    // 32
+    pub asset_type: u8,  // 1 -> define base asset type
+    pub exchanged: bool, // 1
+    pub kind: u8,        // 1 -> AuthorityKind code
+    pub target: Pubkey,  // 32 -> spl mint, or bpf upgradeable program
+
+    pub escrowed: bool, // 1 -> still in escrow, claimed by the receiver once released
+    pub receiver: Pubkey, // 32 -> who the asset item was released to
+}
+// size = 8 + (4) + (4) + (32) + (32) + (1) + (1) + (1) + (32) + (1) + (32)
+impl AssetItemAuthority {
+    pub const SIZE: usize = 148;
+    pub const PREFIX: &'static str = "asset_item_authority";
+}
+impl AssetItem for AssetItemAuthority {
+    asset_item_fields!();
+
+    fn content(&self) -> Vec<u8> {
+        [self.target.as_ref(), &[self.kind]].concat()
+    }
+
+    fn released(&self) -> ReleasedAssetItem {
+        ReleasedAssetItem {
+            asset_type: AssetItemType::AUTHORITY,
+            mint: self.target,
+            amount: 1,
+            collection: None,
+        }
+    }
+
+    fn release(&mut self, receiver: Pubkey) {
+        self.exchanged = true;
+        self.receiver = receiver;
+    }
+}
 
 // ===================================================
 // =====================[ TRADE ]=====================
 // ===================================================
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum TradeMode {
+    Offer,
+    Auction,
+    DutchAuction,
+    FixedPrice,
+}
+impl TradeMode {
+    pub fn from_code(code: u8) -> Result<TradeMode> {
+        match code {
+            0 => Ok(TradeMode::Offer),
+            1 => Ok(TradeMode::Auction),
+            2 => Ok(TradeMode::DutchAuction),
+            3 => Ok(TradeMode::FixedPrice),
+            unknown_code => {
+                msg!("Unknow TradeMode : {}", unknown_code);
+                Err(SolTradeError::InvalidTradeMode.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            TradeMode::Offer => 0,
+            TradeMode::Auction => 1,
+            TradeMode::DutchAuction => 2,
+            TradeMode::FixedPrice => 3,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum DutchAuctionDecay {
+    Linear,
+    Stepwise,
+}
+impl DutchAuctionDecay {
+    pub fn from_code(code: u8) -> Result<DutchAuctionDecay> {
+        match code {
+            0 => Ok(DutchAuctionDecay::Linear),
+            1 => Ok(DutchAuctionDecay::Stepwise),
+            unknown_code => {
+                msg!("Unknow DutchAuctionDecay : {}", unknown_code);
+                Err(SolTradeError::InvalidDutchAuctionDecay.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            DutchAuctionDecay::Linear => 0,
+            DutchAuctionDecay::Stepwise => 1,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum WantedAssetType {
+    SOL,
+    SPL,
+    Collection,
+}
+impl WantedAssetType {
+    pub fn from_code(code: u8) -> Result<WantedAssetType> {
+        match code {
+            1 => Ok(WantedAssetType::SOL),
+            2 => Ok(WantedAssetType::SPL),
+            3 => Ok(WantedAssetType::Collection),
+            unknown_code => {
+                msg!("Unknow WantedAssetType : {}", unknown_code);
+                Err(SolTradeError::InvalidWantedAssetType.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            WantedAssetType::SOL => 1,
+            WantedAssetType::SPL => 2,
+            WantedAssetType::Collection => 3,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum AutoAcceptRule {
+    Disabled,
+    MinLamports,
+    MinTokens,
+    WantedAssets,
+}
+impl AutoAcceptRule {
+    pub fn from_code(code: u8) -> Result<AutoAcceptRule> {
+        match code {
+            0 => Ok(AutoAcceptRule::Disabled),
+            1 => Ok(AutoAcceptRule::MinLamports),
+            2 => Ok(AutoAcceptRule::MinTokens),
+            3 => Ok(AutoAcceptRule::WantedAssets),
+            unknown_code => {
+                msg!("Unknow AutoAcceptRule : {}", unknown_code);
+                Err(SolTradeError::InvalidAutoAcceptRule.into())
+            }
+        }
+    }
+    pub fn to_code(&self) -> u8 {
+        match self {
+            AutoAcceptRule::Disabled => 0,
+            AutoAcceptRule::MinLamports => 1,
+            AutoAcceptRule::MinTokens => 2,
+            AutoAcceptRule::WantedAssets => 3,
+        }
+    }
+}
+
+// ? what the maker wants for the trade
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct WantedAsset {
+    pub asset_type: u8, // 1 -> WantedAssetType code
+    pub mint: Pubkey,   // 32 -> spl mint or collection mint, default pubkey for SOL
+    pub amount: u64,    // 8 -> minimum lamports or tokens, unused for collections
+}
+// size = (1) + (32) + (8)
+impl WantedAsset {
+    pub const SIZE: usize = 41;
+    pub const MAX_LEN: usize = 5;
+
+    // ? a mint for spl and collections, an amount for sol
+    pub fn is_complete(&self) -> Result<bool> {
+        Ok(match WantedAssetType::from_code(self.asset_type)? {
+            WantedAssetType::SOL => self.amount > 0,
+            WantedAssetType::SPL | WantedAssetType::Collection => self.mint != Pubkey::default(),
+        })
+    }
+
+    // ? whether assets meet every wanted asset, given as
+    // ? (asset type, mint, amount, verified collection of the mint)
+    pub fn all_met(
+        wanted_assets: &[WantedAsset],
+        assets: &[(AssetItemType, Pubkey, u64, Option<Pubkey>)],
+    ) -> Result<bool> {
+        for wanted in wanted_assets.iter() {
+            let wanted_type = WantedAssetType::from_code(wanted.asset_type)?;
+            let met = match wanted_type {
+                WantedAssetType::SOL | WantedAssetType::SPL => {
+                    let asset_type = match wanted_type {
+                        WantedAssetType::SOL => AssetItemType::SOL,
+                        _ => AssetItemType::SPL,
+                    };
+                    let total = assets
+                        .iter()
+                        .filter(|(t, mint, _, _)| {
+                            (*t == asset_type
+                                || (asset_type == AssetItemType::SPL && t.has_royalties()))
+                                && (asset_type == AssetItemType::SOL || *mint == wanted.mint)
+                        })
+                        .fold(0u64, |total, (_, _, amount, _)| {
+                            total.saturating_add(*amount)
+                        });
+                    total >= wanted.amount
+                }
+                WantedAssetType::Collection => assets
+                    .iter()
+                    .any(|(_, _, _, collection)| *collection == Some(wanted.mint)),
+            };
+            if !met {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
 #[account]
 pub struct Trade {
     pub index: u32,       // 4
@@ -107,11 +809,112 @@ pub struct Trade {
     // assets items
     pub asset_sol_count: u32, // 4
     pub asset_spl_count: u32, // 4
+
+    // status
+    pub cancelled: bool, // 1
+
+    // listing, assets of the trade are frozen once it leaves TradeMode::Offer
+    pub mode: u8, // 1 -> TradeMode code
+
+    // auction
+    pub auction_mint: Pubkey, // 32 -> mint of the bids or of the price, default pubkey for SOL
+    pub reserve_price: u64,   // 8
+    pub min_increment: u64,   // 8
+    pub end_time: i64,        // 8 -> unix timestamp
+    pub best_offer: Pubkey,   // 32
+    pub best_bid: u64,        // 8
+
+    // dutch auction, price in Trade.auction_mint, ends at Trade.end_time
+    pub start_price: u64,   // 8
+    pub end_price: u64,     // 8
+    pub start_time: i64,    // 8 -> unix timestamp
+    pub decay: u8,          // 1 -> DutchAuctionDecay code
+    pub step_interval: i64, // 8 -> seconds between price drops, stepwise decay only
+
+    // fixed price, in Trade.auction_mint
+    pub price: u64, // 8
+
+    // settlement without an offer
+    pub buyer: Pubkey, // 32
+
+    // wanted assets, max 5, offers can only hold wanted assets in strict mode
+    pub wanted_assets: Vec<WantedAsset>, // 4 + (41 * 5)
+    pub strict: bool,                    // 1
+
+    // auto accept, the first offer matching the rule is accepted and settled by try_auto_accept
+    pub auto_accept_rule: u8,     // 1 -> AutoAcceptRule code
+    pub auto_accept_mint: Pubkey, // 32 -> MinTokens only
+    pub auto_accept_amount: u64,  // 8 -> minimum lamports or tokens
+
+    // nft asset items
+    pub asset_nft_count: u32,  // 4 -> metaplex nfts and core assets
+    pub asset_cnft_count: u32, // 4
+
+    // stake asset items
+    pub asset_stake_count: u32, // 4
+
+    // authority asset items
+    pub asset_authority_count: u32, // 4
 }
-// size = 8 + (4) + (32) + (4) + (4) + (32) + (4) + (1) + (4 + (32 * 5)) + (4) + (4)
+// size = 8 + (4) + (32) + (4) + (4) + (32) + (4) + (1) + (4 + (32 * 5)) + (4) + (4) + (1)
+//      + (1) + (32) + (8) + (8) + (8) + (32) + (8)
+//      + (8) + (8) + (8) + (1) + (8) + (8) + (32) + (4 + (41 * 5)) + (1)
+//      + (1) + (32) + (8) + (4) + (4) + (4) + (4)
 impl Trade {
-    pub const SIZE: usize = 261;
+    pub const SIZE: usize = 699;
     pub const PREFIX: &'static str = "trade";
+
+    // ? whether an asset item matches one of the wanted assets, collection is the verified
+    // ? collection of the mint when known
+    pub fn wants(
+        &self,
+        asset_type: AssetItemType,
+        mint: &Pubkey,
+        collection: Option<&Pubkey>,
+    ) -> bool {
+        self.wanted_assets.iter().any(|wanted| {
+            match (WantedAssetType::from_code(wanted.asset_type), asset_type) {
+                (Ok(WantedAssetType::SOL), AssetItemType::SOL) => true,
+                (
+                    Ok(WantedAssetType::SPL),
+                    AssetItemType::SPL | AssetItemType::NFT | AssetItemType::CORE,
+                ) => wanted.mint == *mint,
+                (Ok(WantedAssetType::Collection), _) => collection == Some(&wanted.mint),
+                _ => false,
+            }
+        })
+    }
+
+    // ? whether the assets of an offer meet every wanted asset, given as
+    // ? (asset type, mint, amount, verified collection of the mint)
+    pub fn wanted_assets_met(
+        &self,
+        assets: &[(AssetItemType, Pubkey, u64, Option<Pubkey>)],
+    ) -> Result<bool> {
+        WantedAsset::all_met(&self.wanted_assets, assets)
+    }
+
+    // ? current price of a dutch auction, start_price before start_time and end_price after end_time
+    pub fn dutch_auction_price(&self, now: i64) -> Result<u64> {
+        if now <= self.start_time {
+            return Ok(self.start_price);
+        }
+        if now >= self.end_time {
+            return Ok(self.end_price);
+        }
+
+        let duration = (self.end_time - self.start_time) as u128;
+        let elapsed = match DutchAuctionDecay::from_code(self.decay)? {
+            DutchAuctionDecay::Linear => (now - self.start_time) as u128,
+            DutchAuctionDecay::Stepwise => {
+                let step_interval = self.step_interval as u128;
+                (now - self.start_time) as u128 / step_interval * step_interval
+            }
+        };
+        let drop = (self.start_price - self.end_price) as u128 * elapsed / duration;
+
+        Ok(self.start_price - drop as u64)
+    }
 }
 
 // ===================================================
@@ -131,9 +934,271 @@ pub struct Offer {
     // assets items
     pub asset_sol_count: u32, // 4
     pub asset_spl_count: u32, // 4
+
+    // negotiation
+    pub round: u32, // 4 -> bumped by every counter offer, accept_offer binds to it
+    pub counter_pending: bool, // 1
+
+    // running hash of every asset mutation, accept_offer binds to it
+    pub content_hash: [u8; 32], // 32
+
+    // auction bid, in lamports or in Trade.auction_mint, an offer with a bid can't hold assets
+    pub bid_amount: u64,    // 8
+    pub bid_refunded: bool, // 1 -> outbid and paid back, tokens on withdraw_bid
+
+    // nft asset items
+    pub asset_nft_count: u32,  // 4 -> metaplex nfts and core assets
+    pub asset_cnft_count: u32, // 4
+
+    // stake asset items
+    pub asset_stake_count: u32, // 4
+
+    // authority asset items
+    pub asset_authority_count: u32, // 4
 }
-// size = 8 + (4) + (32) + (4) + (1) + (1) + (4) + (4) + (4)
+// size = 8 + (4) + (32) + (4) + (1) + (1) + (4) + (4) + (4) + (4) + (1) + (32) + (8) + (1) + (4)
+//      + (4) + (4) + (4)
 impl Offer {
-    pub const SIZE: usize = 62;
+    pub const SIZE: usize = 124;
     pub const PREFIX: &'static str = "offer";
+    pub const BID_VAULT_PREFIX: &'static str = "bid_vault";
+
+    // content hash actions
+    pub const CONTENT_ADD: u8 = 1;
+    pub const CONTENT_DROP: u8 = 2;
+
+    // ? content_hash = sha256(content_hash, action, asset_item, content)
+    pub fn update_content_hash(&mut self, action: u8, asset_item: &Pubkey, content: &[u8]) {
+        self.content_hash = anchor_lang::solana_program::hash::hashv(&[
+            &self.content_hash,
+            &[action],
+            asset_item.as_ref(),
+            content,
+        ])
+        .to_bytes();
+    }
+}
+
+// ? seeds = [offer, CounterOffer::PREFIX], one pending counter offer per offer
+#[account]
+pub struct CounterOffer {
+    pub offer: Pubkey, // 32
+    pub round: u32,    // 4 -> offer round this counter offer was made in
+
+    // requested modification
+    pub sol_amount: u64, // 8 -> extra lamports wanted from the taker
+
+    // offer asset items the maker does not want, refunded to the taker, max 5
+    pub drop_asset_items: Vec<Pubkey>, // 4 + (32 * 5)
+
+    // assets the offer must hold once the counter offer is accepted, totals like the wanted
+    // assets of a trade, the taker adds what is missing before accepting. max 5
+    pub requested_assets: Vec<WantedAsset>, // 4 + (41 * 5)
+}
+// size = 8 + (32) + (4) + (8) + (4 + (32 * 5)) + (4 + (41 * 5))
+impl CounterOffer {
+    pub const SIZE: usize = 425;
+    pub const PREFIX: &'static str = "counter_offer";
+    pub const MAX_DROP_ASSET_ITEMS: usize = 5;
+}
+
+// ===================================================
+// ================[ COLLECTION OFFER ]===============
+// ===================================================
+// ? seeds = [CollectionOffer::PREFIX, user, nonce], nonce from UserProfile.collection_offer_count
+// ? the lamports of the remaining items are held by the account itself
+#[account]
+pub struct CollectionOffer {
+    pub index: u32,         // 4 -> nonce of the user
+    pub user: Pubkey,       // 32 -> buyer, gets the nfts
+    pub collection: Pubkey, // 32 -> verified metaplex collection mint
+    pub price: u64,         // 8 -> lamports per nft
+    pub quantity: u32,      // 4 -> nfts still wanted
+    pub filled: u32,        // 4 -> nfts received
+    pub royalty_paid: u64,  // 8 -> lamports paid to the creators of the filled nfts
+}
+// size = 8 + (4) + (32) + (32) + (8) + (4) + (4) + (8)
+impl CollectionOffer {
+    pub const SIZE: usize = 100;
+    pub const PREFIX: &'static str = "collection_offer";
+}
+
+// ===================================================
+// ======================[ RING ]=====================
+// ===================================================
+// ? seeds = [Ring::PREFIX, user, nonce], nonce from UserProfile.ring_count
+// ? a trade between three or more participants, each one escrows asset items for another one.
+// ? the lamports of the SOL asset items are held by the account itself
+#[account]
+pub struct Ring {
+    pub index: u32,                // 4 -> nonce of the creator
+    pub user: Pubkey,              // 32 -> creator, one of the participants
+    pub participants: Vec<Pubkey>, // 4 + (32 * 8)
+    pub deposited: u8,             // 1 -> bit per participant, set once it escrowed an asset item
+    pub confirmed: u8,             // 1 -> bit per participant, cleared by every new asset item
+    pub asset_count: u32,          // 4 -> next RingAssetItem index
+    pub settled: bool,             // 1 -> every participant confirmed, items go to their recipients
+    pub cancelled: bool,           // 1 -> a participant withdrew, items go back to their owners
+}
+// size = 8 + (4) + (32) + (4 + 32 * 8) + (1) + (1) + (4) + (1) + (1)
+impl Ring {
+    pub const SIZE: usize = 312;
+    pub const PREFIX: &'static str = "ring";
+    pub const MIN_PARTICIPANTS: usize = 3;
+    pub const MAX_PARTICIPANTS: usize = 8;
+
+    // ? bit of the participant in the deposited and confirmed masks
+    pub fn participant_bit(&self, user: &Pubkey) -> Option<u8> {
+        self.participants
+            .iter()
+            .position(|participant| participant == user)
+            .map(|position| 1 << position)
+    }
+
+    // ? mask with the bit of every participant set
+    pub fn all_participants(&self) -> u8 {
+        ((1u16 << self.participants.len()) - 1) as u8
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.settled && !self.cancelled
+    }
+}
+
+// ? seeds = [ring, RingAssetItem::PREFIX, index]
+#[account]
+pub struct RingAssetItem {
+    pub index: u32,        // 4
+    pub ring: Pubkey,      // 32
+    pub user: Pubkey,      // 32 -> participant escrowing the asset item
+    pub recipient: Pubkey, // 32 -> participant it goes to once the ring is settled
+    pub asset_type: u8,    // 1 -> AssetItemType code, SOL or SPL
+    pub mint: Pubkey,      // 32 -> default pubkey for SOL
+    pub amount: u64,       // 8 -> lamports, or tokens received by the vault after transfer fees
+    pub decimals: u8,      // 1 -> decimals of the mint, 9 for SOL
+
+    // tokens are held in escrow, in the associated token account of the ring
+    pub vault: Pubkey,         // 32
+    pub token_program: Pubkey, // 32 -> token program or token 2022 program of the mint
+    pub escrowed: bool, // 1 -> still in escrow, claimed once the ring is settled or cancelled
+}
+// size = 8 + (4) + (32) + (32) + (32) + (1) + (32) + (8) + (1) + (32) + (32) + (1)
+impl RingAssetItem {
+    pub const SIZE: usize = 215;
+    pub const PREFIX: &'static str = "ring_asset_item";
+
+    // ? who the asset item goes to, none while the ring is open
+    pub fn receiver(&self, ring: &Ring) -> Option<Pubkey> {
+        if ring.settled {
+            Some(self.recipient)
+        } else if ring.cancelled {
+            Some(self.user)
+        } else {
+            None
+        }
+    }
+}
+
+// ===================================================
+// ==================[ SIGNED OFFER ]=================
+// ===================================================
+// ? token given by the taker of a signed offer, pulled from its token account by the delegate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SignedOfferAsset {
+    pub mint: Pubkey, // 32
+    pub amount: u64,  // 8
+}
+
+// ? an offer signed off-chain by the taker, nothing is stored. the maker of the trade submits it
+// ? after an ed25519 program instruction verifying SignedOffer::message
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SignedOffer {
+    pub program: Pubkey, // 32 -> this program, the signature is not valid elsewhere
+    pub trade: Pubkey,   // 32
+    pub taker: Pubkey,   // 32
+    pub assets: Vec<SignedOfferAsset>, // 4 + (40 * 8)
+    pub expiry: i64,     // 8 -> unix timestamp
+    pub nonce: u64,      // 8 -> SignedOfferNonce of the taker
+}
+impl SignedOffer {
+    pub const DOMAIN: &'static [u8] = b"soltrade signed offer:";
+    pub const MAX_ASSETS: usize = 8;
+    // ? seeds = [DELEGATE_PREFIX], delegate the taker approves on the token accounts it gives
+    pub const DELEGATE_PREFIX: &'static str = "signed_offer_delegate";
+
+    // ? what the taker signs, the domain then the borsh encoded offer
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Self::DOMAIN.to_vec();
+        message.extend(self.try_to_vec()?);
+        Ok(message)
+    }
+}
+
+// ? seeds = [SignedOfferNonce::PREFIX, taker, nonce]
+// ? a nonce of a taker, used by a signed offer or cancelled by the taker. the account existing
+// ? is what keeps a signed offer from being filled twice
+#[account]
+pub struct SignedOfferNonce {
+    pub user: Pubkey,  // 32 -> taker
+    pub nonce: u64,    // 8
+    pub trade: Pubkey, // 32 -> trade settled by the offer, default pubkey when cancelled
+}
+// size = 8 + (32) + (8) + (32)
+impl SignedOfferNonce {
+    pub const SIZE: usize = 80;
+    pub const PREFIX: &'static str = "signed_offer_nonce";
+}
+
+// ===================================================
+// ==================[ USER PROFILE ]=================
+// ===================================================
+#[account]
+pub struct UserProfile {
+    pub user: Pubkey, // 32
+
+    // nonces, also the number of trades created / offers made
+    pub trade_count: u32, // 4 -> next UserTrade nonce
+    pub offer_count: u32, // 4 -> next UserOffer nonce
+
+    // history
+    pub trades_completed: u32, // 4
+    pub trades_cancelled: u32, // 4
+    pub sol_volume: u64,       // 8 -> lamports exchanged in completed assets, both directions
+
+    // nonce of the collection offers
+    pub collection_offer_count: u32, // 4 -> next CollectionOffer nonce
+
+    // settlements pay the lamports to the wrapped SOL account of the user instead of its wallet
+    pub wsol_payout: bool, // 1
+
+    // nonce of the rings
+    pub ring_count: u32, // 4 -> next Ring nonce
+}
+// size = 8 + (32) + (4) + (4) + (4) + (4) + (8) + (4) + (1) + (4)
+impl UserProfile {
+    pub const SIZE: usize = 73;
+    pub const PREFIX: &'static str = "user_profile";
+}
+
+// ? seeds = [UserTrade::PREFIX, user, nonce], nonce goes from 0 to UserProfile.trade_count
+#[account]
+pub struct UserTrade {
+    pub trade: Pubkey, // 32
+}
+// size = 8 + (32)
+impl UserTrade {
+    pub const SIZE: usize = 40;
+    pub const PREFIX: &'static str = "user_trade";
+}
+
+// ? seeds = [UserOffer::PREFIX, user, nonce], nonce goes from 0 to UserProfile.offer_count
+#[account]
+pub struct UserOffer {
+    pub trade: Pubkey, // 32
+    pub offer: Pubkey, // 32
+}
+// size = 8 + (32) + (32)
+impl UserOffer {
+    pub const SIZE: usize = 72;
+    pub const PREFIX: &'static str = "user_offer";
 }
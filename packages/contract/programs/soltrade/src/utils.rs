@@ -1,9 +1,1500 @@
-use anchor_lang::prelude::*;
+use {
+    crate::{
+        error::SolTradeError,
+        program::Soltrade,
+        state::{
+            AssetItem, AssetItemAuthority, AssetItemCNFT, AssetItemCore, AssetItemNFT,
+            AssetItemSOL, AssetItemSPL, AssetItemStake, Authority, AuthorityKind,
+            MintRegistryEntry, MintRegistryMode, MintRegistryStatus, Offer, ReleasedAssetItem,
+            RoyaltyCreator, StakeActivation, Trade, UserProfile,
+        },
+    },
+    anchor_lang::{prelude::*, Discriminator},
+    anchor_spl::{
+        associated_token::get_associated_token_address,
+        metadata::MetadataAccount,
+        token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer},
+        token_2022::spl_token_2022,
+        token_interface,
+    },
+    mpl_token_metadata::{
+        instruction::{builders::TransferBuilder, InstructionBuilder, TransferArgs},
+        state::TokenStandard,
+    },
+    solana_program::{
+        borsh0_10::try_from_slice_unchecked,
+        bpf_loader_upgradeable, ed25519_program,
+        instruction::{AccountMeta, Instruction},
+        program::{invoke, invoke_signed},
+        program_pack::Pack,
+        pubkey,
+        stake::{
+            self,
+            state::{Delegation, StakeAuthorize, StakeState},
+        },
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+};
+
+// ? programs of compressed nfts
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// ? program of metaplex core assets
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
 
 pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> Result<()> {
     if account.owner != owner {
         return Err(ProgramError::IllegalOwner.into());
-    } else {
+    }
+    Ok(())
+}
+
+pub fn assert_upgrade_authority<'info>(
+    program: &Program<'info, Soltrade>,
+    program_data: &Account<'info, ProgramData>,
+    user: &Pubkey,
+) -> Result<()> {
+    require!(
+        program.programdata_address()? == Some(program_data.key()),
+        SolTradeError::UpgradeAuthorityInvalidProgramData
+    );
+    require!(
+        program_data.upgrade_authority_address == Some(*user),
+        SolTradeError::UpgradeAuthorityUnauthorized
+    );
+    Ok(())
+}
+
+pub fn assert_offer_of_trade(offer: &Account<Offer>, trade: &Pubkey) -> Result<()> {
+    let (offer_address, _) = Pubkey::find_program_address(
+        &[
+            trade.as_ref(),
+            Offer::PREFIX.as_bytes(),
+            offer.index.to_le_bytes().as_ref(),
+        ],
+        &crate::id(),
+    );
+    require_keys_eq!(offer.key(), offer_address, SolTradeError::OfferInvalidTrade);
+    Ok(())
+}
+
+// ? an empty allowed_users list means the trade is public
+pub fn assert_allowed_user(trade: &Trade, user: &Pubkey) -> Result<()> {
+    require!(
+        trade.allowed_users.is_empty() || trade.allowed_users.contains(user),
+        SolTradeError::TradeUserNotAllowed
+    );
+    Ok(())
+}
+
+// ? a metaplex nft: supply of 1, no decimals, and its own metadata
+pub fn assert_nft(mint: &Account<Mint>, metadata: &Account<MetadataAccount>) -> Result<()> {
+    require!(
+        mint.supply == 1 && mint.decimals == 0,
+        SolTradeError::NftInvalidMint
+    );
+    require_keys_eq!(metadata.mint, mint.key(), SolTradeError::NftInvalidMetadata);
+    Ok(())
+}
+
+// ? the verified collection of an nft, unverified collections can be set by anyone
+pub fn verified_collection(metadata: &MetadataAccount) -> Option<Pubkey> {
+    metadata
+        .collection
+        .as_ref()
+        .filter(|collection| collection.verified)
+        .map(|collection| collection.key)
+}
+
+// ? royalties of an nft, creators without a share get nothing
+pub fn royalty_creators(metadata: &MetadataAccount) -> Vec<RoyaltyCreator> {
+    metadata
+        .data
+        .creators
+        .as_ref()
+        .map(|creators| {
+            creators
+                .iter()
+                .filter(|creator| creator.share > 0)
+                .map(|creator| RoyaltyCreator {
+                    address: creator.address,
+                    share: creator.share,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ? programmable nfts are frozen in their token account, only the token metadata program moves them
+pub fn is_programmable(metadata: &MetadataAccount) -> bool {
+    matches!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+            | Some(TokenStandard::ProgrammableNonFungibleEdition)
+    )
+}
+
+// ? accounts of a token metadata transfer of a programmable nft
+pub struct PnftTransferAccounts<'a, 'info> {
+    pub token: &'a AccountInfo<'info>,
+    pub token_owner: &'a AccountInfo<'info>,
+    pub destination: &'a AccountInfo<'info>,
+    pub destination_owner: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub owner_token_record: &'a AccountInfo<'info>,
+    pub destination_token_record: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>,
+}
+
+// ? moves a programmable nft with the token metadata program, the rule set of the nft is
+// ? enforced there. signer_seeds are set when the authority is a pda of the program
+pub fn transfer_pnft(accounts: PnftTransferAccounts, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let mut builder = TransferBuilder::new();
+    builder
+        .token(accounts.token.key())
+        .token_owner(accounts.token_owner.key())
+        .destination(accounts.destination.key())
+        .destination_owner(accounts.destination_owner.key())
+        .mint(accounts.mint.key())
+        .metadata(accounts.metadata.key())
+        .edition(accounts.edition.key())
+        .owner_token_record(accounts.owner_token_record.key())
+        .destination_token_record(accounts.destination_token_record.key())
+        .authority(accounts.authority.key())
+        .payer(accounts.payer.key())
+        .system_program(accounts.system_program.key())
+        .sysvar_instructions(accounts.sysvar_instructions.key())
+        .spl_token_program(accounts.token_program.key())
+        .spl_ata_program(accounts.associated_token_program.key());
+
+    let mut account_infos = vec![
+        accounts.token.clone(),
+        accounts.token_owner.clone(),
+        accounts.destination.clone(),
+        accounts.destination_owner.clone(),
+        accounts.mint.clone(),
+        accounts.metadata.clone(),
+        accounts.edition.clone(),
+        accounts.owner_token_record.clone(),
+        accounts.destination_token_record.clone(),
+        accounts.authority.clone(),
+        accounts.payer.clone(),
+        accounts.system_program.clone(),
+        accounts.sysvar_instructions.clone(),
+        accounts.token_program.clone(),
+        accounts.associated_token_program.clone(),
+        accounts.token_metadata_program.clone(),
+    ];
+    if let (Some(rules_program), Some(rules)) = (
+        accounts.authorization_rules_program,
+        accounts.authorization_rules,
+    ) {
+        builder
+            .authorization_rules_program(rules_program.key())
+            .authorization_rules(rules.key());
+        account_infos.push(rules_program.clone());
+        account_infos.push(rules.clone());
+    }
+
+    let ix = builder
+        .build(TransferArgs::V1 {
+            amount: 1,
+            authorization_data: None,
+        })
+        .map_err(|_| SolTradeError::PnftInvalidTokenStandard)?
+        .instruction();
+    invoke_signed(&ix, &account_infos, signer_seeds).map_err(Into::into)
+}
+
+pub fn assert_not_paused(authority: &Authority) -> Result<()> {
+    require!(!authority.paused, SolTradeError::ProgramPaused);
+    Ok(())
+}
+
+// ? the instruction right before the current one must be an ed25519 program instruction with a
+// ? single signature of message by signer. the runtime has verified it, only what it verified is
+// ? checked here: the public key and the message, read from the instruction itself
+pub fn assert_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+    require!(current_index > 0, SolTradeError::Ed25519MissingInstruction);
+    let ix = load_instruction_at_checked(current_index - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        ix.program_id,
+        ed25519_program::ID,
+        SolTradeError::Ed25519MissingInstruction
+    );
+
+    // number of signatures, padding, then the offsets of the signature:
+    // signature, signature ix, public key, public key ix, message, message size, message ix
+    let data = &ix.data;
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        SolTradeError::Ed25519InvalidSignature
+    );
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        SolTradeError::Ed25519InvalidSignature
+    );
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    require!(
+        data.get(public_key_offset..public_key_offset + 32) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_size) == Some(message),
+        SolTradeError::Ed25519InvalidSignature
+    );
+    Ok(())
+}
+
+pub fn assert_mint_allowed(
+    authority: &Authority,
+    mint: &Pubkey,
+    mint_registry_entry: Option<&AccountInfo>,
+) -> Result<()> {
+    let mode = MintRegistryMode::from_code(authority.mint_registry_mode)?;
+    if mode == MintRegistryMode::Open {
+        return Ok(());
+    }
+
+    // ? the entry must always be passed, even when it does not exist yet,
+    // ? otherwise a denied mint could skip the check by leaving it out
+    let mint_registry_entry = mint_registry_entry.ok_or(SolTradeError::MintRegistryEntryMissing)?;
+    let (entry_address, _) = Pubkey::find_program_address(
+        &[MintRegistryEntry::PREFIX.as_bytes(), mint.as_ref()],
+        &crate::id(),
+    );
+    require_keys_eq!(
+        mint_registry_entry.key(),
+        entry_address,
+        SolTradeError::MintRegistryInvalidEntry
+    );
+
+    let status =
+        if mint_registry_entry.owner == &crate::id() && !mint_registry_entry.data_is_empty() {
+            let entry =
+                MintRegistryEntry::try_deserialize(&mut &mint_registry_entry.data.borrow()[..])?;
+            Some(MintRegistryStatus::from_code(entry.status)?)
+        } else {
+            None
+        };
+
+    match mode {
+        MintRegistryMode::Allowlist => require!(
+            status == Some(MintRegistryStatus::Allowed),
+            SolTradeError::MintNotAllowlisted
+        ),
+        MintRegistryMode::Denylist => require!(
+            status != Some(MintRegistryStatus::Denied),
+            SolTradeError::MintDenylisted
+        ),
+        MintRegistryMode::Open => {}
+    }
+
+    Ok(())
+}
+
+// ? moves the bid held by an auction offer to the receiver, lamports from the offer account
+// ? or tokens from its bid vault, signed by the offer. the caller checks the receiver
+pub fn release_bid<'info>(
+    trade: &Account<'info, Trade>,
+    offer: &Account<'info, Offer>,
+    receiver: &AccountInfo<'info>,
+    receiver_token_account: Option<&Account<'info, TokenAccount>>,
+    bid_vault: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    invalid_accounts: SolTradeError,
+) -> Result<()> {
+    let amount = offer.bid_amount;
+
+    if trade.auction_mint == Pubkey::default() {
+        **offer.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **receiver.try_borrow_mut_lamports()? += amount;
+        return Ok(());
+    }
+
+    let receiver_token_account = receiver_token_account.ok_or(invalid_accounts)?;
+    let bid_vault = bid_vault.ok_or(invalid_accounts)?;
+    let token_program = token_program.ok_or(invalid_accounts)?;
+    let (bid_vault_address, _) = Pubkey::find_program_address(
+        &[offer.key().as_ref(), Offer::BID_VAULT_PREFIX.as_bytes()],
+        &crate::id(),
+    );
+    require_keys_eq!(bid_vault.key(), bid_vault_address, invalid_accounts);
+    require_keys_eq!(
+        receiver_token_account.owner,
+        receiver.key(),
+        invalid_accounts
+    );
+    require_keys_eq!(
+        receiver_token_account.mint,
+        trade.auction_mint,
+        invalid_accounts
+    );
+
+    let trade_key = trade.key();
+    let offer_index = offer.index.to_le_bytes();
+    let (_, offer_bump) = Pubkey::find_program_address(
+        &[trade_key.as_ref(), Offer::PREFIX.as_bytes(), &offer_index],
+        &crate::id(),
+    );
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        trade_key.as_ref(),
+        Offer::PREFIX.as_bytes(),
+        &offer_index,
+        &[offer_bump],
+    ]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: bid_vault.to_account_info(),
+                to: receiver_token_account.to_account_info(),
+                authority: offer.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+// ? pays a price from the signer to the receiver, lamports or tokens of the mint.
+// ? the caller checks the receiver
+#[allow(clippy::too_many_arguments)]
+pub fn pay_price<'info>(
+    mint: &Pubkey,
+    amount: u64,
+    payer: &Signer<'info>,
+    receiver: &AccountInfo<'info>,
+    payer_token_account: Option<&Account<'info, TokenAccount>>,
+    receiver_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if *mint == Pubkey::default() {
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &payer.key(),
+            &receiver.key(),
+            amount,
+        );
+        return anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                payer.to_account_info(),
+                receiver.clone(),
+                system_program.to_account_info(),
+            ],
+        )
+        .map_err(Into::into);
+    }
+
+    let payer_token_account = payer_token_account.ok_or(SolTradeError::InvalidPaymentAccounts)?;
+    let receiver_token_account =
+        receiver_token_account.ok_or(SolTradeError::InvalidPaymentAccounts)?;
+    let token_program = token_program.ok_or(SolTradeError::InvalidPaymentAccounts)?;
+    require_keys_eq!(
+        receiver_token_account.owner,
+        receiver.key(),
+        SolTradeError::InvalidPaymentAccounts
+    );
+    require_keys_eq!(
+        receiver_token_account.mint,
+        *mint,
+        SolTradeError::InvalidPaymentAccounts
+    );
+
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: payer_token_account.to_account_info(),
+                to: receiver_token_account.to_account_info(),
+                authority: payer.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// ? moves lamports out of an escrow of the program, or from a signer with the system program
+pub fn transfer_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    system_program: Option<&AccountInfo<'info>>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    if from.owner == &crate::id() {
+        **from.try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? += amount;
         return Ok(());
     }
+
+    let system_program = system_program.ok_or(SolTradeError::InvalidPaymentAccounts)?;
+    let ix =
+        anchor_lang::solana_program::system_instruction::transfer(&from.key(), &to.key(), amount);
+    anchor_lang::solana_program::program::invoke(
+        &ix,
+        &[from.clone(), to.clone(), system_program.clone()],
+    )
+    .map_err(Into::into)
+}
+
+pub struct UnwrapSolAccounts<'a, 'info> {
+    // wrapped SOL token account of the user
+    pub wsol_token: &'a AccountInfo<'info>,
+    pub native_mint: &'a AccountInfo<'info>,
+    // pda the lamports go through, created and closed on the spot
+    pub unwrap: &'a AccountInfo<'info>,
+    pub user: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+// ? unwraps lamports of a wrapped SOL token account to the wallet of the user. a token account
+// ? can't be unwrapped in part, so the lamports go through a token account at the pda of
+// ? unwrap_seeds, closed back to the user
+pub fn unwrap_sol(accounts: UnwrapSolAccounts, amount: u64, unwrap_seeds: &[&[u8]]) -> Result<()> {
+    require_keys_eq!(
+        accounts.native_mint.key(),
+        native_mint::ID,
+        SolTradeError::WsolInvalidAccounts
+    );
+    require_keys_eq!(
+        accounts.token_program.key(),
+        token::ID,
+        SolTradeError::WsolInvalidAccounts
+    );
+    let (unwrap_address, unwrap_bump) = Pubkey::find_program_address(unwrap_seeds, &crate::id());
+    require_keys_eq!(
+        accounts.unwrap.key(),
+        unwrap_address,
+        SolTradeError::WsolInvalidAccounts
+    );
+    let unwrap_bump = [unwrap_bump];
+    let mut unwrap_signer = unwrap_seeds.to_vec();
+    unwrap_signer.push(&unwrap_bump);
+
+    anchor_lang::system_program::create_account(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            anchor_lang::system_program::CreateAccount {
+                from: accounts.user.clone(),
+                to: accounts.unwrap.clone(),
+            },
+            &[&unwrap_signer],
+        ),
+        Rent::get()?.minimum_balance(TokenAccount::LEN),
+        TokenAccount::LEN as u64,
+        &token::ID,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        accounts.token_program.clone(),
+        token::InitializeAccount3 {
+            account: accounts.unwrap.clone(),
+            mint: accounts.native_mint.clone(),
+            authority: accounts.user.clone(),
+        },
+    ))?;
+    token::transfer_checked(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            token::TransferChecked {
+                from: accounts.wsol_token.clone(),
+                mint: accounts.native_mint.clone(),
+                to: accounts.unwrap.clone(),
+                authority: accounts.user.clone(),
+            },
+        ),
+        amount,
+        native_mint::DECIMALS,
+    )?;
+    token::close_account(CpiContext::new(
+        accounts.token_program.clone(),
+        token::CloseAccount {
+            account: accounts.unwrap.clone(),
+            destination: accounts.user.clone(),
+            authority: accounts.user.clone(),
+        },
+    ))
+}
+
+// ? account taking the lamports of a recipient: its wallet, or its wrapped SOL account when its
+// ? profile asks for it. sync_sol_payouts runs once every lamport is in
+pub fn sol_payout_account<'info>(
+    recipient: &AccountInfo<'info>,
+    profile: Option<&UserProfile>,
+    wsol_account: Option<&AccountInfo<'info>>,
+) -> Result<AccountInfo<'info>> {
+    let wrapped = profile.map_or(false, |profile| {
+        profile.user == recipient.key() && profile.wsol_payout
+    });
+    if !wrapped {
+        return Ok(recipient.clone());
+    }
+
+    let wsol_account = wsol_account.ok_or(SolTradeError::SolPayoutInvalidWsolAccount)?;
+    require_keys_eq!(
+        wsol_account.key(),
+        get_associated_token_address(&recipient.key(), &native_mint::ID),
+        SolTradeError::SolPayoutInvalidWsolAccount
+    );
+    require!(
+        *wsol_account.owner == token::ID && !wsol_account.data_is_empty(),
+        SolTradeError::SolPayoutInvalidWsolAccount
+    );
+    Ok(wsol_account.clone())
+}
+
+// ? counts the lamports paid to wrapped SOL accounts as tokens, nothing for a wallet. lamports
+// ? moved directly are only checked by the runtime for the accounts of a cpi, so every payout and
+// ? every escrow paid from directly go with each sync to keep its balances even
+pub fn sync_sol_payouts<'info>(
+    payouts: &[&AccountInfo<'info>],
+    escrows: &[&AccountInfo<'info>],
+    token_program: Option<&AccountInfo<'info>>,
+) -> Result<()> {
+    let accounts: Vec<AccountInfo<'info>> = payouts
+        .iter()
+        .chain(escrows)
+        .map(|account| (*account).clone())
+        .collect();
+    for payout in payouts {
+        if *payout.owner != token::ID {
+            continue;
+        }
+        let token_program = token_program.ok_or(SolTradeError::SolPayoutInvalidWsolAccount)?;
+        let mut ix = token::spl_token::instruction::sync_native(&token::ID, payout.key)?;
+        ix.accounts.extend(
+            accounts
+                .iter()
+                .filter(|account| account.key != payout.key)
+                .map(|account| AccountMeta::new(account.key(), false)),
+        );
+        let mut account_infos = accounts.clone();
+        account_infos.push(token_program.clone());
+        invoke(&ix, &account_infos)?;
+    }
+    Ok(())
+}
+
+// ? pays lamports for nfts to the seller, less the royalties of every nft on its equal share of
+// ? the lamports. creator accounts are passed for each nft in order, then for each of its
+// ? creators with a share. returns the royalties paid for each nft
+pub fn pay_with_royalties<'a, 'info: 'a>(
+    lamports: u64,
+    royalties: &[(u16, Vec<RoyaltyCreator>)],
+    payer: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    creator_accounts: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    system_program: Option<&AccountInfo<'info>>,
+) -> Result<Vec<u64>> {
+    if lamports == 0 {
+        return Ok(vec![0; royalties.len()]);
+    }
+
+    let mut paid = Vec::with_capacity(royalties.len());
+    let mut total: u64 = 0;
+
+    if !royalties.is_empty() {
+        let nft_lamports = lamports / royalties.len() as u64;
+        for (seller_fee_basis_points, creators) in royalties.iter() {
+            let royalty = nft_lamports as u128 * *seller_fee_basis_points as u128 / 10_000;
+            let mut nft_paid: u64 = 0;
+            for creator in creators.iter() {
+                let creator_account = creator_accounts
+                    .next()
+                    .ok_or(SolTradeError::RoyaltyInvalidCreators)?;
+                require_keys_eq!(
+                    creator_account.key(),
+                    creator.address,
+                    SolTradeError::RoyaltyInvalidCreators
+                );
+                let amount = (royalty * creator.share as u128 / 100) as u64;
+                transfer_lamports(payer, creator_account, amount, system_program)?;
+                nft_paid = nft_paid.checked_add(amount).unwrap();
+            }
+            total = total.checked_add(nft_paid).unwrap();
+            paid.push(nft_paid);
+        }
+    }
+
+    transfer_lamports(
+        payer,
+        seller,
+        lamports.checked_sub(total).unwrap(),
+        system_program,
+    )?;
+    Ok(paid)
+}
+
+// ? an asset item paying royalties, a metaplex nft or a core asset
+pub enum RoyaltyAssetItem<'info> {
+    Nft(Account<'info, AssetItemNFT>),
+    Core(Account<'info, AssetItemCore>),
+}
+impl<'info> RoyaltyAssetItem<'info> {
+    pub fn try_from(asset_item: &AccountInfo<'info>) -> Result<RoyaltyAssetItem<'info>> {
+        if asset_item.try_borrow_data()?.get(..8) == Some(&AssetItemCore::DISCRIMINATOR[..]) {
+            Ok(RoyaltyAssetItem::Core(Account::try_from(asset_item)?))
+        } else {
+            Ok(RoyaltyAssetItem::Nft(Account::try_from(asset_item)?))
+        }
+    }
+
+    pub fn from(&self) -> Pubkey {
+        match self {
+            RoyaltyAssetItem::Nft(asset_item_nft) => asset_item_nft.from,
+            RoyaltyAssetItem::Core(asset_item_core) => asset_item_core.from,
+        }
+    }
+
+    pub fn type_index(&self) -> u32 {
+        match self {
+            RoyaltyAssetItem::Nft(asset_item_nft) => asset_item_nft.type_index,
+            RoyaltyAssetItem::Core(asset_item_core) => asset_item_core.type_index,
+        }
+    }
+
+    pub fn royalties(&self) -> (u16, Vec<RoyaltyCreator>) {
+        match self {
+            RoyaltyAssetItem::Nft(asset_item_nft) => (
+                asset_item_nft.seller_fee_basis_points,
+                asset_item_nft.creators.clone(),
+            ),
+            RoyaltyAssetItem::Core(asset_item_core) => (
+                asset_item_core.seller_fee_basis_points,
+                asset_item_core.creators.clone(),
+            ),
+        }
+    }
+
+    // ? records the royalties paid and writes the item back
+    pub fn add_royalty_paid(&mut self, amount: u64) -> Result<()> {
+        match self {
+            RoyaltyAssetItem::Nft(asset_item_nft) => {
+                asset_item_nft.royalty_paid =
+                    asset_item_nft.royalty_paid.checked_add(amount).unwrap();
+                asset_item_nft.exit(&crate::id())
+            }
+            RoyaltyAssetItem::Core(asset_item_core) => {
+                asset_item_core.royalty_paid =
+                    asset_item_core.royalty_paid.checked_add(amount).unwrap();
+                asset_item_core.exit(&crate::id())
+            }
+        }
+    }
+}
+
+// ? same as pay_with_royalties for nft and core asset items, the royalties paid are recorded
+// ? on the items. returns the total royalties
+pub fn pay_nft_royalties<'a, 'info: 'a>(
+    lamports: u64,
+    nft_items: &[AccountInfo<'info>],
+    payer: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    creator_accounts: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+    system_program: Option<&AccountInfo<'info>>,
+) -> Result<u64> {
+    let mut royalty_items = nft_items
+        .iter()
+        .map(RoyaltyAssetItem::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let royalties: Vec<(u16, Vec<RoyaltyCreator>)> = royalty_items
+        .iter()
+        .map(RoyaltyAssetItem::royalties)
+        .collect();
+
+    let paid = pay_with_royalties(
+        lamports,
+        &royalties,
+        payer,
+        seller,
+        creator_accounts,
+        system_program,
+    )?;
+
+    let mut total: u64 = 0;
+    for (royalty_item, amount) in royalty_items.iter_mut().zip(paid) {
+        royalty_item.add_royalty_paid(amount)?;
+        total = total.checked_add(amount).unwrap();
+    }
+    Ok(total)
+}
+
+// ? token 2022 mint extensions, by their tlv type. transfer hooks are newer than the token 2022
+// ? crate, the extensions are read from the raw tlv data
+const MINT_EXTENSION_CONFIDENTIAL_TRANSFER: u16 = 4;
+const MINT_EXTENSION_NON_TRANSFERABLE: u16 = 9;
+const MINT_EXTENSION_PERMANENT_DELEGATE: u16 = 12;
+
+// ? tlv types of the extensions of a token 2022 mint, none for the token program
+pub fn mint_extension_types(mint: &AccountInfo) -> Result<Vec<u16>> {
+    // base mint, padded to the size of a token account, then the account type
+    const TLV_START: usize = <spl_token_2022::state::Account as Pack>::LEN + 1;
+
+    let data = mint.try_borrow_data()?;
+    let mut types = vec![];
+    if *mint.owner != spl_token_2022::ID || data.len() <= TLV_START {
+        return Ok(types);
+    }
+    let mut offset = TLV_START;
+    while offset + 4 <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if extension_type == 0 {
+            break;
+        }
+        types.push(extension_type);
+        offset += 4 + length;
+    }
+    Ok(types)
+}
+
+// ? extensions that would break the escrow: tokens that can't move, amounts the program can't
+// ? read, or a delegate able to drain the vault
+pub fn assert_mint_supported(mint: &AccountInfo) -> Result<()> {
+    for extension_type in mint_extension_types(mint)? {
+        match extension_type {
+            MINT_EXTENSION_NON_TRANSFERABLE => {
+                return Err(SolTradeError::SplNonTransferableUnsupported.into())
+            }
+            MINT_EXTENSION_CONFIDENTIAL_TRANSFER => {
+                return Err(SolTradeError::SplConfidentialTransferUnsupported.into())
+            }
+            MINT_EXTENSION_PERMANENT_DELEGATE => {
+                return Err(SolTradeError::SplPermanentDelegateUnsupported.into())
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// ? transfer_checked with the token program of the mint. extra_accounts are the accounts of the
+// ? transfer hook of the mint, resolved by the client, forwarded as they are
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_spl<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    extra_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    ix.accounts
+        .extend(extra_accounts.iter().map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: false,
+            is_writable: account.is_writable,
+        }));
+
+    let mut account_infos = vec![
+        from.clone(),
+        mint.clone(),
+        to.clone(),
+        authority.clone(),
+        token_program.clone(),
+    ];
+    account_infos.extend(extra_accounts.iter().cloned());
+    invoke_signed(&ix, &account_infos, signer_seeds).map_err(Into::into)
+}
+
+// ? balance of a token account of either token program
+pub fn token_amount(token_account: &AccountInfo) -> Result<u64> {
+    let token_account: InterfaceAccount<token_interface::TokenAccount> =
+        InterfaceAccount::try_from(token_account)?;
+    Ok(token_account.amount)
+}
+
+// ? accounts of a bubblegum transfer of a compressed nft, the proof goes after them
+pub struct CnftTransferAccounts<'a, 'info> {
+    pub tree_authority: &'a AccountInfo<'info>,
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub new_leaf_owner: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+}
+
+// ? moves the leaf of a compressed nft to a new owner, bubblegum verifies the proof against the
+// ? root. signer_seeds are set when the owner is a pda of the program
+pub fn transfer_cnft<'info>(
+    accounts: CnftTransferAccounts<'_, 'info>,
+    asset_item_cnft: &AssetItemCNFT,
+    root: [u8; 32],
+    proof: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    // * bubblegum `transfer`, the delegate of the leaf is its owner
+    let mut data = BUBBLEGUM_TRANSFER_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&root);
+    data.extend_from_slice(&asset_item_cnft.data_hash);
+    data.extend_from_slice(&asset_item_cnft.creator_hash);
+    data.extend_from_slice(&asset_item_cnft.nonce.to_le_bytes());
+    data.extend_from_slice(&asset_item_cnft.leaf_index.to_le_bytes());
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.tree_authority.key(), false),
+        AccountMeta::new_readonly(accounts.leaf_owner.key(), true),
+        AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+        AccountMeta::new_readonly(accounts.new_leaf_owner.key(), false),
+        AccountMeta::new(accounts.merkle_tree.key(), false),
+        AccountMeta::new_readonly(accounts.log_wrapper.key(), false),
+        AccountMeta::new_readonly(accounts.compression_program.key(), false),
+        AccountMeta::new_readonly(accounts.system_program.key(), false),
+    ];
+    metas.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(node.key(), false)),
+    );
+
+    let mut account_infos = vec![
+        accounts.tree_authority.clone(),
+        accounts.leaf_owner.clone(),
+        accounts.new_leaf_owner.clone(),
+        accounts.merkle_tree.clone(),
+        accounts.log_wrapper.clone(),
+        accounts.compression_program.clone(),
+        accounts.system_program.clone(),
+        accounts.bubblegum_program.clone(),
+    ];
+    account_infos.extend(proof.iter().cloned());
+
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&ix, &account_infos, signer_seeds).map_err(Into::into)
+}
+const BUBBLEGUM_TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+// ? asset id of a compressed nft, the bubblegum pda of its tree and nonce
+pub fn cnft_asset_id(tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", tree.as_ref(), nonce.to_le_bytes().as_ref()],
+        &BUBBLEGUM_PROGRAM_ID,
+    )
+    .0
+}
+
+// ? account keys and plugin types of metaplex core, there is no core crate for this anchor
+// ? version, the accounts are read from their borsh layout
+const CORE_KEY_ASSET: u8 = 1;
+const CORE_KEY_PLUGIN_HEADER: u8 = 3;
+const CORE_KEY_PLUGIN_REGISTRY: u8 = 4;
+const CORE_KEY_COLLECTION: u8 = 5;
+const CORE_PLUGIN_ROYALTIES: u8 = 0;
+const CORE_PLUGIN_FREEZE_DELEGATE: u8 = 1;
+const CORE_PLUGIN_PERMANENT_FREEZE_DELEGATE: u8 = 5;
+const CORE_PLUGIN_PERMANENT_TRANSFER_DELEGATE: u8 = 7;
+const CORE_PLUGIN_PERMANENT_BURN_DELEGATE: u8 = 8;
+const CORE_UPDATE_AUTHORITY_COLLECTION: u8 = 2;
+const CORE_AUTHORITY_ADDRESS: u8 = 3;
+const CORE_TRANSFER_V1: u8 = 14;
+
+// ? plugins of a core asset or collection the escrow cares about
+#[derive(Default)]
+pub struct CorePlugins {
+    pub royalties: Option<(u16, Vec<RoyaltyCreator>)>,
+    pub frozen: bool,
+    pub permanent_delegate: bool,
+}
+
+pub struct CoreAsset {
+    pub owner: Pubkey,
+    pub collection: Option<Pubkey>,
+    pub plugins: CorePlugins,
+}
+
+// ? cursor over the borsh data of a core account, out of bounds reads are invalid assets
+struct CoreReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+impl<'a> CoreReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + len)
+            .ok_or(SolTradeError::CoreInvalidAsset)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+    fn skip_string(&mut self) -> Result<()> {
+        let len = self.u32()? as usize;
+        self.take(len).map(|_| ())
+    }
+}
+
+// ? plugins of the registry following the base account, when there is one
+fn read_core_plugins(data: &[u8], base_end: usize) -> Result<CorePlugins> {
+    let mut plugins = CorePlugins::default();
+    if data.len() <= base_end {
+        return Ok(plugins);
+    }
+
+    let mut header = CoreReader {
+        data,
+        offset: base_end,
+    };
+    require!(
+        header.u8()? == CORE_KEY_PLUGIN_HEADER,
+        SolTradeError::CoreInvalidAsset
+    );
+    let mut registry = CoreReader {
+        data,
+        offset: header.u64()? as usize,
+    };
+    require!(
+        registry.u8()? == CORE_KEY_PLUGIN_REGISTRY,
+        SolTradeError::CoreInvalidAsset
+    );
+    for _ in 0..registry.u32()? {
+        let plugin_type = registry.u8()?;
+        if registry.u8()? == CORE_AUTHORITY_ADDRESS {
+            registry.pubkey()?;
+        }
+        let mut plugin = CoreReader {
+            data,
+            offset: registry.u64()? as usize,
+        };
+        require!(plugin.u8()? == plugin_type, SolTradeError::CoreInvalidAsset);
+
+        match plugin_type {
+            CORE_PLUGIN_ROYALTIES => {
+                let basis_points = plugin.u16()?;
+                let mut creators = vec![];
+                for _ in 0..plugin.u32()? {
+                    let address = plugin.pubkey()?;
+                    let share = plugin.u8()?;
+                    if share > 0 {
+                        creators.push(RoyaltyCreator { address, share });
+                    }
+                }
+                plugins.royalties = Some((basis_points, creators));
+            }
+            CORE_PLUGIN_FREEZE_DELEGATE | CORE_PLUGIN_PERMANENT_FREEZE_DELEGATE => {
+                plugins.frozen |= plugin.u8()? != 0;
+                plugins.permanent_delegate |= plugin_type == CORE_PLUGIN_PERMANENT_FREEZE_DELEGATE;
+            }
+            CORE_PLUGIN_PERMANENT_TRANSFER_DELEGATE | CORE_PLUGIN_PERMANENT_BURN_DELEGATE => {
+                plugins.permanent_delegate = true;
+            }
+            _ => {}
+        }
+    }
+    Ok(plugins)
+}
+
+// ? owner, collection and plugins of a core asset
+pub fn read_core_asset(asset: &AccountInfo) -> Result<CoreAsset> {
+    require_keys_eq!(
+        *asset.owner,
+        MPL_CORE_PROGRAM_ID,
+        SolTradeError::CoreInvalidAsset
+    );
+    let data = asset.try_borrow_data()?;
+    let mut reader = CoreReader {
+        data: &data,
+        offset: 0,
+    };
+    require!(
+        reader.u8()? == CORE_KEY_ASSET,
+        SolTradeError::CoreInvalidAsset
+    );
+    let owner = reader.pubkey()?;
+    let collection = match reader.u8()? {
+        CORE_UPDATE_AUTHORITY_COLLECTION => Some(reader.pubkey()?),
+        0 => None,
+        _ => {
+            reader.pubkey()?;
+            None
+        }
+    };
+    reader.skip_string()?;
+    reader.skip_string()?;
+    if reader.u8()? == 1 {
+        reader.u64()?;
+    }
+
+    Ok(CoreAsset {
+        owner,
+        collection,
+        plugins: read_core_plugins(&data, reader.offset)?,
+    })
+}
+
+// ? plugins of a core collection, the ones of its assets fall back to them
+pub fn read_core_collection(collection: &AccountInfo) -> Result<CorePlugins> {
+    require_keys_eq!(
+        *collection.owner,
+        MPL_CORE_PROGRAM_ID,
+        SolTradeError::CoreInvalidCollection
+    );
+    let data = collection.try_borrow_data()?;
+    let mut reader = CoreReader {
+        data: &data,
+        offset: 0,
+    };
+    require!(
+        reader.u8()? == CORE_KEY_COLLECTION,
+        SolTradeError::CoreInvalidCollection
+    );
+    reader.pubkey()?;
+    reader.skip_string()?;
+    reader.skip_string()?;
+    reader.u32()?;
+    reader.u32()?;
+
+    read_core_plugins(&data, reader.offset)
+}
+
+// ? accounts of a core transfer, the collection is required when the asset has one
+pub struct CoreTransferAccounts<'a, 'info> {
+    pub asset: &'a AccountInfo<'info>,
+    pub collection: Option<&'a AccountInfo<'info>>,
+    pub payer: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub new_owner: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub mpl_core_program: &'a AccountInfo<'info>,
+}
+
+// ? moves a core asset with the core program, the plugins of the asset and of its collection
+// ? are enforced there. signer_seeds are set when the owner is a pda of the program
+pub fn transfer_core(accounts: CoreTransferAccounts, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    // * core `TransferV1` without a compression proof, missing optional accounts are the program
+    let ix = Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.asset.key(), false),
+            AccountMeta::new_readonly(
+                accounts
+                    .collection
+                    .map_or(MPL_CORE_PROGRAM_ID, |collection| collection.key()),
+                false,
+            ),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.new_owner.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+        ],
+        data: vec![CORE_TRANSFER_V1, 0],
+    };
+
+    let mut account_infos = vec![
+        accounts.asset.clone(),
+        accounts.payer.clone(),
+        accounts.authority.clone(),
+        accounts.new_owner.clone(),
+        accounts.system_program.clone(),
+        accounts.mpl_core_program.clone(),
+    ];
+    if let Some(collection) = accounts.collection {
+        account_infos.push(collection.clone());
+    }
+    invoke_signed(&ix, &account_infos, signer_seeds).map_err(Into::into)
+}
+
+// ? state of a native stake account, anchor-spl reads it with another borsh version
+pub fn read_stake_state(stake_account: &AccountInfo) -> Result<StakeState> {
+    require_keys_eq!(
+        *stake_account.owner,
+        stake::program::ID,
+        SolTradeError::StakeInvalidAccount
+    );
+    try_from_slice_unchecked::<StakeState>(&stake_account.try_borrow_data()?)
+        .map_err(|_| SolTradeError::StakeInvalidAccount.into())
+}
+
+// ? activation of a delegation at an epoch. the stake history is not read, a stake is taken as
+// ? active or inactive from the epoch after it was activated or deactivated
+pub fn stake_activation(delegation: Option<&Delegation>, epoch: u64) -> StakeActivation {
+    match delegation {
+        None => StakeActivation::Inactive,
+        Some(delegation) if delegation.deactivation_epoch != u64::MAX => {
+            if epoch > delegation.deactivation_epoch {
+                StakeActivation::Inactive
+            } else {
+                StakeActivation::Deactivating
+            }
+        }
+        Some(delegation) => {
+            if delegation.activation_epoch == u64::MAX || epoch > delegation.activation_epoch {
+                StakeActivation::Active
+            } else {
+                StakeActivation::Activating
+            }
+        }
+    }
+}
+
+// ? hands the staker and the withdrawer of a stake account to a new authority, the staker first
+// ? as the withdrawer can set both. signer_seeds are set when the authority is a pda of the program
+pub fn authorize_stake<'info>(
+    stake: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    new_authority: &AccountInfo<'info>,
+    clock: &AccountInfo<'info>,
+    stake_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        let ix = stake::instruction::authorize(
+            stake.key,
+            authority.key,
+            new_authority.key,
+            stake_authorize,
+            None,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                stake.clone(),
+                clock.clone(),
+                authority.clone(),
+                stake_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+    }
+    Ok(())
+}
+
+// ? holder of an authority over a mint or an upgradeable program, none when it was revoked.
+// ? program_data is required for the upgrade authority
+pub fn read_held_authority(
+    kind: AuthorityKind,
+    target: &AccountInfo,
+    program_data: Option<&AccountInfo>,
+) -> Result<Option<Pubkey>> {
+    match kind {
+        AuthorityKind::MintTokens | AuthorityKind::FreezeAccount => {
+            let mint: InterfaceAccount<token_interface::Mint> = InterfaceAccount::try_from(target)
+                .map_err(|_| SolTradeError::AuthorityInvalidTarget)?;
+            let authority = match kind {
+                AuthorityKind::MintTokens => mint.mint_authority,
+                _ => mint.freeze_authority,
+            };
+            Ok(authority.into())
+        }
+        AuthorityKind::ProgramUpgrade => {
+            require!(
+                *target.owner == bpf_loader_upgradeable::ID && target.executable,
+                SolTradeError::AuthorityInvalidTarget
+            );
+            let program_data = program_data.ok_or(SolTradeError::AuthorityInvalidProgramData)?;
+            let (program_data_address, _) =
+                Pubkey::find_program_address(&[target.key.as_ref()], &bpf_loader_upgradeable::ID);
+            require_keys_eq!(
+                program_data.key(),
+                program_data_address,
+                SolTradeError::AuthorityInvalidProgramData
+            );
+            let program_data: Account<ProgramData> = Account::try_from(program_data)
+                .map_err(|_| SolTradeError::AuthorityInvalidProgramData)?;
+            Ok(program_data.upgrade_authority_address)
+        }
+    }
+}
+
+pub struct SetHeldAuthorityAccounts<'a, 'info> {
+    pub target: &'a AccountInfo<'info>,
+    pub program_data: Option<&'a AccountInfo<'info>>,
+    pub authority: &'a AccountInfo<'info>,
+    pub new_authority: &'a AccountInfo<'info>,
+    // token program of the mint, or the upgradeable loader
+    pub program: &'a AccountInfo<'info>,
+}
+
+// ? hands an authority over a mint or an upgradeable program to a new authority. signer_seeds are
+// ? set when the authority is a pda of the program
+pub fn set_held_authority(
+    kind: AuthorityKind,
+    accounts: SetHeldAuthorityAccounts,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    match kind {
+        AuthorityKind::MintTokens | AuthorityKind::FreezeAccount => {
+            let authority_type = match kind {
+                AuthorityKind::MintTokens => spl_token_2022::instruction::AuthorityType::MintTokens,
+                _ => spl_token_2022::instruction::AuthorityType::FreezeAccount,
+            };
+            token_interface::set_authority(
+                CpiContext::new_with_signer(
+                    accounts.program.clone(),
+                    token_interface::SetAuthority {
+                        current_authority: accounts.authority.clone(),
+                        account_or_mint: accounts.target.clone(),
+                    },
+                    signer_seeds,
+                ),
+                authority_type,
+                Some(accounts.new_authority.key()),
+            )
+        }
+        AuthorityKind::ProgramUpgrade => {
+            let program_data = accounts
+                .program_data
+                .ok_or(SolTradeError::AuthorityInvalidProgramData)?;
+            let ix = bpf_loader_upgradeable::set_upgrade_authority(
+                accounts.target.key,
+                accounts.authority.key,
+                Some(accounts.new_authority.key),
+            );
+            invoke_signed(
+                &ix,
+                &[
+                    program_data.clone(),
+                    accounts.authority.clone(),
+                    accounts.new_authority.clone(),
+                    accounts.program.clone(),
+                ],
+                signer_seeds,
+            )
+            .map_err(Into::into)
+        }
+    }
+}
+
+// ? loads an asset item passed as a remaining account, whatever its type, and runs f on it. the
+// ? asset item is written back when it is writable
+pub fn update_asset_item<R>(
+    asset_item: &AccountInfo,
+    f: impl FnOnce(&mut dyn AssetItem) -> Result<R>,
+) -> Result<R> {
+    let discriminator: [u8; 8] = asset_item
+        .try_borrow_data()?
+        .get(..8)
+        .and_then(|data| data.try_into().ok())
+        .ok_or(SolTradeError::InvalidAssetItemType)?;
+
+    match discriminator {
+        AssetItemSOL::DISCRIMINATOR => update_typed_asset_item::<AssetItemSOL, R>(asset_item, f),
+        AssetItemSPL::DISCRIMINATOR => update_typed_asset_item::<AssetItemSPL, R>(asset_item, f),
+        AssetItemNFT::DISCRIMINATOR => update_typed_asset_item::<AssetItemNFT, R>(asset_item, f),
+        AssetItemCNFT::DISCRIMINATOR => update_typed_asset_item::<AssetItemCNFT, R>(asset_item, f),
+        AssetItemCore::DISCRIMINATOR => update_typed_asset_item::<AssetItemCore, R>(asset_item, f),
+        AssetItemStake::DISCRIMINATOR => {
+            update_typed_asset_item::<AssetItemStake, R>(asset_item, f)
+        }
+        AssetItemAuthority::DISCRIMINATOR => {
+            update_typed_asset_item::<AssetItemAuthority, R>(asset_item, f)
+        }
+        _ => Err(SolTradeError::InvalidAssetItemType.into()),
+    }
+}
+
+fn update_typed_asset_item<T, R>(
+    asset_item: &AccountInfo,
+    f: impl FnOnce(&mut dyn AssetItem) -> Result<R>,
+) -> Result<R>
+where
+    T: AssetItem + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let mut typed: Account<T> = Account::try_from(asset_item)?;
+    let result = f(&mut *typed)?;
+    if asset_item.is_writable {
+        typed.exit(&crate::id())?;
+    }
+    Ok(result)
+}
+
+// ? an asset item account of the instruction accounts, whatever its type
+pub fn as_asset_item<'a, T>(asset_item: &'a mut Account<'_, T>) -> &'a mut dyn AssetItem
+where
+    T: AssetItem + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    &mut **asset_item
+}
+
+// ? why an asset item is released, each instruction reports its own errors
+#[derive(Clone, Copy)]
+pub enum AssetItemRelease {
+    Exchange,
+    Refund,
+    CounterOfferDrop,
+}
+
+// ? checks an asset item is held by from and not released yet, and when user is given that it
+// ? deposited it, then releases it to the receiver. lamports are moved by the caller
+pub fn release_asset_item(
+    asset_item: &mut dyn AssetItem,
+    release: AssetItemRelease,
+    from: &Pubkey,
+    user: Option<&Pubkey>,
+    receiver: &Pubkey,
+) -> Result<()> {
+    let (invalid_from, already_released, invalid_user) = match release {
+        AssetItemRelease::Exchange => (
+            SolTradeError::ExchangeInvalidFrom,
+            SolTradeError::ExchangeAssetItemAlreadyExchanged,
+            SolTradeError::ExchangeInvalidUser,
+        ),
+        AssetItemRelease::Refund => (
+            SolTradeError::RefundAssetItemInvalidFrom,
+            SolTradeError::RefundAssetItemAlreadyReleased,
+            SolTradeError::RefundAssetItemInvalidReceiver,
+        ),
+        AssetItemRelease::CounterOfferDrop => (
+            SolTradeError::CounterOfferInvalidDropAssetItems,
+            SolTradeError::CounterOfferAssetItemAlreadyReleased,
+            SolTradeError::CounterOfferInvalidDropAssetItemUser,
+        ),
+    };
+
+    // ! @check
+    // |-asset item must belong to the trade or offer
+    require_keys_eq!(asset_item.from(), *from, invalid_from);
+    // |-asset item must still be in escrow
+    if asset_item.exchanged() {
+        return Err(already_released.into());
+    }
+    // |-asset item must be the one of the user
+    if let Some(user) = user {
+        require_keys_eq!(asset_item.user(), *user, invalid_user);
+    }
+
+    asset_item.release(*receiver);
+    Ok(())
+}
+
+// ? what an asset item of a trade or offer is worth, released to the receiver when one is
+// ? given. the items must be passed ordered by index, items released before, like the ones
+// ? dropped by a counter offer, are skipped. lamports stay on from, for the caller to pay them out
+pub fn lot_asset_item(
+    asset_item: &AccountInfo,
+    index: u32,
+    from: &Pubkey,
+    receiver: Option<&Pubkey>,
+) -> Result<Option<ReleasedAssetItem>> {
+    update_asset_item(asset_item, |item| {
+        // ! @check
+        // |-asset item of the trade or offer, in order
+        require!(
+            item.from() == *from && item.index() == index,
+            SolTradeError::LotInvalidAssetItems
+        );
+        if item.exchanged() {
+            return Ok(None);
+        }
+
+        if let Some(receiver) = receiver {
+            item.release(*receiver);
+        }
+        Ok(Some(item.released()))
+    })
+}
+
+// ? accounts of a lot settled for a price. lamports of the lot go to sol_receiver, the receiver
+// ? or its wrapped SOL account
+pub struct SettleLotAccounts<'a, 'info> {
+    // asset items of the trade or offer, ordered by index
+    pub lot: &'a [AccountInfo<'info>],
+    pub from: &'a AccountInfo<'info>,
+    pub receiver: &'a AccountInfo<'info>,
+    pub sol_receiver: &'a AccountInfo<'info>,
+    // pays the price, a wallet with system_program or an escrow of the program without
+    pub payer: &'a AccountInfo<'info>,
+    pub seller: &'a AccountInfo<'info>,
+    pub system_program: Option<&'a AccountInfo<'info>>,
+}
+
+// ? moves the lot to the receiver, then the payer pays price lamports to the seller and the
+// ? creators of the nfts of the lot get their royalties out of it. a price in tokens is paid by
+// ? the caller, before settling the lot. with hold_lamports the lamports of the lot stay on from,
+// ? for the caller to pay them out. returns what was released
+pub fn settle_lot<'a, 'info: 'a>(
+    accounts: SettleLotAccounts<'_, 'info>,
+    price: u64,
+    hold_lamports: bool,
+    creator_accounts: &mut impl Iterator<Item = &'a AccountInfo<'info>>,
+) -> Result<Vec<ReleasedAssetItem>> {
+    let mut released_items = Vec::new();
+    let mut nft_items = Vec::new();
+    for (index, asset_item) in accounts.lot.iter().enumerate() {
+        if let Some(released) = lot_asset_item(
+            asset_item,
+            index as u32,
+            accounts.from.key,
+            Some(accounts.receiver.key),
+        )? {
+            if released.asset_type.has_royalties() {
+                nft_items.push(asset_item.clone());
+            }
+            released_items.push(released);
+        }
+    }
+
+    pay_nft_royalties(
+        price,
+        &nft_items,
+        accounts.payer,
+        accounts.seller,
+        creator_accounts,
+        accounts.system_program,
+    )?;
+
+    // * lamports of the lot move last, a cpi after a direct move would only carry one side of it
+    if !hold_lamports {
+        let lamports = released_items.iter().fold(0u64, |total, released| {
+            total.checked_add(released.lamports()).unwrap()
+        });
+        **accounts.from.try_borrow_mut_lamports()? -= lamports;
+        **accounts.sol_receiver.try_borrow_mut_lamports()? += lamports;
+    }
+    Ok(released_items)
 }
@@ -0,0 +1,132 @@
+mod common;
+
+use {
+    anchor_lang::{prelude::Pubkey, system_program, InstructionData, ToAccountMetas},
+    common::*,
+    solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, signature::Signer},
+    soltrade::{error::SolTradeError, state::AssetItemCNFT},
+};
+
+// ? anyone pushes the claim, the leaf only goes to the receiver
+fn claim_instruction(
+    tree: &CnftTree,
+    leaf_index: u32,
+    trade: &Pubkey,
+    asset_item: &Pubkey,
+    receiver: &Pubkey,
+    user: &Pubkey,
+) -> Instruction {
+    let (root, proof) = tree.proof(leaf_index);
+    let mut accounts = soltrade::accounts::ClaimCnftContext {
+        authority: authority_address(),
+        trade: *trade,
+        offer: None,
+        asset_item_cnft: *asset_item,
+        tree_authority: tree.tree_authority,
+        merkle_tree: tree.tree,
+        receiver: *receiver,
+        user: *user,
+        log_wrapper: NOOP.1,
+        compression_program: ACCOUNT_COMPRESSION.1,
+        bubblegum_program: BUBBLEGUM.1,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(proof);
+    Instruction {
+        program_id: soltrade::ID,
+        accounts,
+        data: soltrade::instruction::ClaimCnft { root }.data(),
+    }
+}
+
+#[tokio::test]
+async fn cnft_is_escrowed_and_claimed_by_the_buyer() {
+    let mut context = start_with(&[BUBBLEGUM, ACCOUNT_COMPRESSION, NOOP]).await;
+    let maker = funded_keypair(&mut context).await;
+    let buyer = funded_keypair(&mut context).await;
+    let mut tree = create_cnft_tree(&mut context).await;
+    let cnft = tree.mint(&mut context, &maker.pubkey()).await;
+    let trade = create_trade(&mut context, &maker).await;
+    let asset_item = asset_item_address(&trade, AssetItemCNFT::PREFIX, 0);
+
+    // the leaf moves to the trade, with a proof against the current root
+    let (root, proof) = tree.proof(cnft.leaf_index);
+    let mut accounts = soltrade::accounts::AddAssetItemCnftContext {
+        authority: authority_address(),
+        trade_or_offer: trade,
+        asset_item_cnft: asset_item,
+        tree_authority: tree.tree_authority,
+        merkle_tree: tree.tree,
+        mint_registry_entry: None,
+        trade: None,
+        user: maker.pubkey(),
+        log_wrapper: NOOP.1,
+        compression_program: ACCOUNT_COMPRESSION.1,
+        bubblegum_program: BUBBLEGUM.1,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(proof);
+    let add_cnft = Instruction {
+        program_id: soltrade::ID,
+        accounts,
+        data: soltrade::instruction::AddAssetItemCnft {
+            from_type: 1,
+            index: 0,
+            asset_type_index: 0,
+            root,
+            data_hash: cnft.data_hash,
+            creator_hash: cnft.creator_hash,
+            nonce: cnft.nonce,
+            leaf_index: cnft.leaf_index,
+        }
+        .data(),
+    };
+    process(&mut context, &[add_cnft], &[&maker]).await.unwrap();
+    tree.set_owner(&cnft, &trade);
+    let escrowed: AssetItemCNFT = fetch(&mut context, &asset_item).await;
+    assert!(escrowed.escrowed);
+    assert_eq!(escrowed.tree, tree.tree);
+
+    // not released yet
+    let payer = context.payer.pubkey();
+    let claim = claim_instruction(
+        &tree,
+        cnft.leaf_index,
+        &trade,
+        &asset_item,
+        &buyer.pubkey(),
+        &payer,
+    );
+    assert_error(
+        process(&mut context, &[claim], &[]).await,
+        SolTradeError::CnftNotReleased,
+    );
+
+    // the cnft has no creators, the lot is the asset item alone
+    list_fixed_price(&mut context, &trade, &maker, LAMPORTS_PER_SOL).await;
+    let buy = buy_instruction(
+        &trade,
+        &maker.pubkey(),
+        &buyer.pubkey(),
+        &[asset_item],
+        (None, None),
+    );
+    process(&mut context, &[buy], &[&buyer]).await.unwrap();
+    let sold: AssetItemCNFT = fetch(&mut context, &asset_item).await;
+    assert_eq!(sold.receiver, buyer.pubkey());
+
+    // the leaf only goes to the buyer, bubblegum checks the proof of the leaf owned by the trade
+    let claim = claim_instruction(
+        &tree,
+        cnft.leaf_index,
+        &trade,
+        &asset_item,
+        &buyer.pubkey(),
+        &payer,
+    );
+    process(&mut context, &[claim], &[]).await.unwrap();
+    let claimed: AssetItemCNFT = fetch(&mut context, &asset_item).await;
+    assert!(!claimed.escrowed);
+}
@@ -0,0 +1,726 @@
+// ? in-process test environment: soltrade runs in a solana-program-test bank, with the spl token and
+// ? associated token programs it ships. the metaplex and compression programs are the mainnet
+// ? binaries dumped by `bun run fixtures`, the ones the validator of the ts tests loads
+#![allow(dead_code)]
+
+use {
+    anchor_lang::{
+        prelude::{borsh, AccountInfo, AccountMeta, Pubkey},
+        solana_program::entrypoint::ProgramResult,
+        system_program, AccountDeserialize, AccountSerialize, AnchorSerialize, InstructionData,
+        ToAccountMetas,
+    },
+    anchor_spl::{
+        associated_token::get_associated_token_address,
+        token::spl_token::{self, solana_program::program_pack::Pack},
+    },
+    mpl_token_metadata::{
+        instruction::{
+            builders::{CreateBuilder, MintBuilder},
+            CreateArgs, InstructionBuilder, MintArgs,
+        },
+        pda::{find_master_edition_account, find_metadata_account, find_token_record_account},
+        state::{AssetData, PrintSupply, TokenStandard},
+    },
+    solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        bpf_loader,
+        instruction::{Instruction, InstructionError},
+        keccak,
+        native_token::LAMPORTS_PER_SOL,
+        rent::Rent,
+        signature::{Keypair, Signer},
+        system_instruction, sysvar,
+        transaction::{Transaction, TransactionError},
+    },
+    soltrade::{
+        error::SolTradeError,
+        state::{Authority, MintRegistryMode, Offer, Trade, UserOffer, UserProfile, UserTrade},
+        utils::cnft_asset_id,
+    },
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    std::path::Path,
+};
+
+// ? programs loaded from tests/fixtures, by file name and address
+pub type Fixture = (&'static str, Pubkey);
+pub const TOKEN_METADATA: Fixture = ("mpl_token_metadata", mpl_token_metadata::ID);
+pub const BUBBLEGUM: Fixture = ("mpl_bubblegum", soltrade::utils::BUBBLEGUM_PROGRAM_ID);
+pub const ACCOUNT_COMPRESSION: Fixture = (
+    "spl_account_compression",
+    soltrade::utils::SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+);
+pub const NOOP: Fixture = ("spl_noop", soltrade::utils::SPL_NOOP_PROGRAM_ID);
+
+// ? anchor ties the accounts to the lifetime of their infos, the bank lends them for less
+fn process_soltrade(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    soltrade::entry(program_id, accounts, data)
+}
+
+// ? starts a bank with the program initialized, the admin is a throwaway key
+pub async fn start() -> ProgramTestContext {
+    start_with(&[]).await
+}
+
+// ? same as start, with the fixtures the test needs. a missing fixture fails the test, a test
+// ? against another build of the program would not prove anything
+pub async fn start_with(fixtures: &[Fixture]) -> ProgramTestContext {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program("soltrade", soltrade::ID, processor!(process_soltrade));
+
+    for (name, program_id) in fixtures {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/fixtures")
+            .join(format!("{name}.so"));
+        let data = std::fs::read(&path).unwrap_or_else(|error| {
+            panic!(
+                "{} is missing, run `bun run fixtures` first: {error}",
+                path.display()
+            )
+        });
+        program_test.add_account(
+            *program_id,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let admin = Pubkey::new_unique();
+    let authority = Authority {
+        user: admin,
+        trade_count: 0,
+        pending_user: Pubkey::default(),
+        pauser: admin,
+        fee_manager: admin,
+        paused: false,
+        mint_registry_mode: MintRegistryMode::Open.to_code(),
+    };
+    let mut data = Vec::with_capacity(Authority::SIZE);
+    authority.try_serialize(&mut data).unwrap();
+    data.resize(Authority::SIZE, 0);
+    program_test.add_account(
+        authority_address(),
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: soltrade::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    program_test.start_with_context().await
+}
+
+// ======================================================================
+// ===========================[ BANK ]===================================
+// ======================================================================
+// ? the payer of the context pays the fees, signers are the other signers of the instructions
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub fn assert_error(result: Result<(), BanksClientError>, error: SolTradeError) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, u32::from(error), "expected {error}"),
+        other => panic!("expected {error}, got {other:?}"),
+    }
+}
+
+pub async fn funded_keypair(context: &mut ProgramTestContext) -> Keypair {
+    let keypair = Keypair::new();
+    let transfer = system_instruction::transfer(
+        &context.payer.pubkey(),
+        &keypair.pubkey(),
+        10 * LAMPORTS_PER_SOL,
+    );
+    process(context, &[transfer], &[]).await.unwrap();
+    keypair
+}
+
+pub async fn fetch<T: AccountDeserialize>(context: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("{address} not found"));
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn exists(context: &mut ProgramTestContext, address: &Pubkey) -> bool {
+    context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+// ? writes a soltrade account as is, for states the instructions can't reach without the
+// ? programs of the fixtures. size is the SIZE of the account type
+pub fn set_state<T: AccountSerialize>(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    state: &T,
+    size: usize,
+) {
+    let mut data = Vec::with_capacity(size);
+    state.try_serialize(&mut data).unwrap();
+    data.resize(size, 0);
+    context.set_account(
+        address,
+        &Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: soltrade::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+// ? changes a soltrade account in place, its lamports are kept
+pub async fn update_state<T: AccountSerialize + AccountDeserialize>(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    update: impl FnOnce(&mut T),
+) {
+    let mut account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("{address} not found"));
+    let mut state = T::try_deserialize(&mut account.data.as_slice()).unwrap();
+    update(&mut state);
+    let mut data = Vec::with_capacity(account.data.len());
+    state.try_serialize(&mut data).unwrap();
+    data.resize(account.data.len(), 0);
+    account.data = data;
+    context.set_account(address, &account.into());
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub async fn token_amount(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("{address} not found"));
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+// ======================================================================
+// ============================[ PDA ]===================================
+// ======================================================================
+pub fn authority_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[Authority::PREFIX.as_bytes(), soltrade::ID.as_ref()],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn trade_address(index: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[Trade::PREFIX.as_bytes(), &index.to_le_bytes()],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn user_profile_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[UserProfile::PREFIX.as_bytes(), user.as_ref()],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn user_offer_address(user: &Pubkey, nonce: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            UserOffer::PREFIX.as_bytes(),
+            user.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn offer_address(trade: &Pubkey, index: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            trade.as_ref(),
+            Offer::PREFIX.as_bytes(),
+            &index.to_le_bytes(),
+        ],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn user_trade_address(user: &Pubkey, nonce: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            UserTrade::PREFIX.as_bytes(),
+            user.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &soltrade::ID,
+    )
+    .0
+}
+
+// ? prefix is the one of the asset item type, AssetItemSOL::PREFIX, AssetItemNFT::PREFIX, ...
+pub fn asset_item_address(trade_or_offer: &Pubkey, prefix: &str, type_index: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            trade_or_offer.as_ref(),
+            prefix.as_bytes(),
+            &type_index.to_le_bytes(),
+        ],
+        &soltrade::ID,
+    )
+    .0
+}
+
+// ======================================================================
+// ==========================[ SOLTRADE ]================================
+// ======================================================================
+pub async fn create_trade(context: &mut ProgramTestContext, user: &Keypair) -> Pubkey {
+    let authority: Authority = fetch(context, &authority_address()).await;
+    let user_profile = user_profile_address(&user.pubkey());
+    let nonce = if exists(context, &user_profile).await {
+        fetch::<UserProfile>(context, &user_profile)
+            .await
+            .trade_count
+    } else {
+        0
+    };
+    let trade = trade_address(authority.trade_count);
+    let instruction = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::CreateTradeContext {
+            authority: authority_address(),
+            trade,
+            user_profile,
+            user_trade: user_trade_address(&user.pubkey(), nonce),
+            user: user.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::CreateTrade {
+            trade_index: authority.trade_count,
+            allowed_users: vec![],
+            wanted_assets: vec![],
+            strict: false,
+        }
+        .data(),
+    };
+    process(context, &[instruction], &[user]).await.unwrap();
+    trade
+}
+
+pub async fn create_offer(
+    context: &mut ProgramTestContext,
+    trade: &Pubkey,
+    user: &Keypair,
+) -> Pubkey {
+    let index = fetch::<Trade>(context, trade).await.offer_count;
+    let user_profile = user_profile_address(&user.pubkey());
+    let nonce = if exists(context, &user_profile).await {
+        fetch::<UserProfile>(context, &user_profile)
+            .await
+            .offer_count
+    } else {
+        0
+    };
+    let offer = offer_address(trade, index);
+    let instruction = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::CreateOfferContext {
+            authority: authority_address(),
+            offer,
+            trade: *trade,
+            user_profile,
+            user_offer: user_offer_address(&user.pubkey(), nonce),
+            user: user.pubkey(),
+            system_program: system_program::ID,
+            previous_offer: None,
+            previous_user: None,
+            bid_mint: None,
+            user_token_account: None,
+            bid_vault: None,
+            token_program: None,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::CreateOffer {
+            index,
+            bid_amount: 0,
+        }
+        .data(),
+    };
+    process(context, &[instruction], &[user]).await.unwrap();
+    offer
+}
+
+pub async fn list_fixed_price(
+    context: &mut ProgramTestContext,
+    trade: &Pubkey,
+    user: &Keypair,
+    price: u64,
+) {
+    let instruction = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::ListFixedPriceContext {
+            authority: authority_address(),
+            trade: *trade,
+            user: user.pubkey(),
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::ListFixedPrice {
+            price_mint: Pubkey::default(),
+            price,
+        }
+        .data(),
+    };
+    process(context, &[instruction], &[user]).await.unwrap();
+}
+
+// ? buy of a listing in SOL, lot is every asset item of the trade ordered by index then the
+// ? creators of its nfts. the wrapped SOL accounts are the ones of the profiles asking for it
+pub fn buy_instruction(
+    trade: &Pubkey,
+    maker: &Pubkey,
+    user: &Pubkey,
+    lot: &[Pubkey],
+    wsol_accounts: (Option<Pubkey>, Option<Pubkey>),
+) -> Instruction {
+    let (user_wsol_account, maker_wsol_account) = wsol_accounts;
+    let mut accounts = soltrade::accounts::BuyContext {
+        authority: authority_address(),
+        trade: *trade,
+        maker: *maker,
+        maker_profile: user_profile_address(maker),
+        user_profile: user_profile_address(user),
+        user: *user,
+        system_program: system_program::ID,
+        user_token_account: None,
+        maker_token_account: None,
+        token_program: Some(spl_token::ID),
+        user_wsol_account,
+        maker_wsol_account,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        lot.iter()
+            .map(|address| anchor_lang::prelude::AccountMeta::new(*address, false)),
+    );
+    Instruction {
+        program_id: soltrade::ID,
+        accounts,
+        data: soltrade::instruction::Buy {}.data(),
+    }
+}
+
+// ======================================================================
+// =======================[ TOKEN METADATA ]=============================
+// ======================================================================
+pub struct Pnft {
+    pub mint: Pubkey,
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+    pub token: Pubkey,
+    pub token_record: Pubkey,
+}
+
+// ? token metadata creates the token accounts it transfers to without passing the system and token
+// ? programs, which the bank only allows to a binary. they are created up front, as a wallet would
+pub async fn create_associated_token_account(
+    context: &mut ProgramTestContext,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Pubkey {
+    let instruction = create_associated_token_account_idempotent(
+        &context.payer.pubkey(),
+        owner,
+        mint,
+        &spl_token::ID,
+    );
+    process(context, &[instruction], &[]).await.unwrap();
+    get_associated_token_address(owner, mint)
+}
+
+// ? programmable nft without rule set nor creators, minted to the associated token account of owner
+pub async fn create_pnft(context: &mut ProgramTestContext, owner: &Pubkey) -> Pnft {
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let (metadata, _) = find_metadata_account(&mint.pubkey());
+    let (edition, _) = find_master_edition_account(&mint.pubkey());
+    let token = get_associated_token_address(owner, &mint.pubkey());
+    let (token_record, _) = find_token_record_account(&mint.pubkey(), &token);
+
+    let mut asset_data = AssetData::new(
+        TokenStandard::ProgrammableNonFungible,
+        "Soltrade Test".to_string(),
+        "STT".to_string(),
+        String::new(),
+    );
+    asset_data.seller_fee_basis_points = 0;
+    let create = CreateBuilder::new()
+        .metadata(metadata)
+        .master_edition(edition)
+        .mint(mint.pubkey())
+        .authority(payer)
+        .payer(payer)
+        .update_authority(payer)
+        .initialize_mint(true)
+        .update_authority_as_signer(true)
+        .build(CreateArgs::V1 {
+            asset_data,
+            decimals: Some(0),
+            print_supply: Some(PrintSupply::Zero),
+        })
+        .unwrap()
+        .instruction();
+    let mint_to = MintBuilder::new()
+        .token(token)
+        .token_owner(*owner)
+        .metadata(metadata)
+        .master_edition(edition)
+        .token_record(token_record)
+        .mint(mint.pubkey())
+        .authority(payer)
+        .payer(payer)
+        .build(MintArgs::V1 {
+            amount: 1,
+            authorization_data: None,
+        })
+        .unwrap()
+        .instruction();
+    process(context, &[create], &[&mint]).await.unwrap();
+    create_associated_token_account(context, owner, &mint.pubkey()).await;
+    process(context, &[mint_to], &[]).await.unwrap();
+
+    Pnft {
+        mint: mint.pubkey(),
+        metadata,
+        edition,
+        token,
+        token_record,
+    }
+}
+
+pub fn token_record_address(mint: &Pubkey, token: &Pubkey) -> Pubkey {
+    find_token_record_account(mint, token).0
+}
+
+pub const SYSVAR_INSTRUCTIONS: Pubkey = sysvar::instructions::ID;
+
+// ======================================================================
+// ==========================[ BUBBLEGUM ]===============================
+// ======================================================================
+const TREE_MAX_DEPTH: usize = 3;
+const TREE_MAX_BUFFER_SIZE: u32 = 8;
+// ? header, then sequence number, active index and buffer size, the changelogs and the rightmost
+// ? path, no canopy
+const TREE_SIZE: usize = 56
+    + 24
+    + TREE_MAX_BUFFER_SIZE as usize * (32 + TREE_MAX_DEPTH * 32 + 8)
+    + (TREE_MAX_DEPTH * 32 + 40);
+const BUBBLEGUM_CREATE_TREE_DISCRIMINATOR: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+const BUBBLEGUM_MINT_V1_DISCRIMINATOR: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+
+// ? MetadataArgs of bubblegum, the enums are their borsh codes
+#[derive(AnchorSerialize)]
+struct CnftMetadata {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<u8>,
+    collection: Option<()>,
+    uses: Option<()>,
+    token_program_version: u8,
+    creators: Vec<()>,
+}
+
+// ? a merkle tree of compressed nfts and the mirror of its leaves, there is no indexer in the
+// ? bank to build the proofs. the payer of the context is the tree creator
+pub struct CnftTree {
+    pub tree: Pubkey,
+    pub tree_authority: Pubkey,
+    leaves: Vec<[u8; 32]>,
+}
+
+pub struct Cnft {
+    pub asset_id: Pubkey,
+    pub leaf_index: u32,
+    pub nonce: u64,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+}
+
+pub async fn create_cnft_tree(context: &mut ProgramTestContext) -> CnftTree {
+    let payer = context.payer.pubkey();
+    let tree = Keypair::new();
+    let tree_authority = Pubkey::find_program_address(&[tree.pubkey().as_ref()], &BUBBLEGUM.1).0;
+    let allocate = system_instruction::create_account(
+        &payer,
+        &tree.pubkey(),
+        Rent::default().minimum_balance(TREE_SIZE),
+        TREE_SIZE as u64,
+        &ACCOUNT_COMPRESSION.1,
+    );
+    let mut data = BUBBLEGUM_CREATE_TREE_DISCRIMINATOR.to_vec();
+    (TREE_MAX_DEPTH as u32, TREE_MAX_BUFFER_SIZE, Some(false))
+        .serialize(&mut data)
+        .unwrap();
+    let create_tree = Instruction {
+        program_id: BUBBLEGUM.1,
+        accounts: vec![
+            AccountMeta::new(tree_authority, false),
+            AccountMeta::new(tree.pubkey(), false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(NOOP.1, false),
+            AccountMeta::new_readonly(ACCOUNT_COMPRESSION.1, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    };
+    process(context, &[allocate, create_tree], &[&tree])
+        .await
+        .unwrap();
+    CnftTree {
+        tree: tree.pubkey(),
+        tree_authority,
+        leaves: vec![],
+    }
+}
+
+impl CnftTree {
+    // ? mints a compressed nft without creators to the owner
+    pub async fn mint(&mut self, context: &mut ProgramTestContext, owner: &Pubkey) -> Cnft {
+        let payer = context.payer.pubkey();
+        let metadata = CnftMetadata {
+            name: "Soltrade Test".to_string(),
+            symbol: "STT".to_string(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(0), // NonFungible
+            collection: None,
+            uses: None,
+            token_program_version: 0, // Original
+            creators: vec![],
+        };
+        let mut data = BUBBLEGUM_MINT_V1_DISCRIMINATOR.to_vec();
+        metadata.serialize(&mut data).unwrap();
+        let mint = Instruction {
+            program_id: BUBBLEGUM.1,
+            accounts: vec![
+                AccountMeta::new(self.tree_authority, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new(self.tree, false),
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new_readonly(NOOP.1, false),
+                AccountMeta::new_readonly(ACCOUNT_COMPRESSION.1, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        };
+        process(context, &[mint], &[]).await.unwrap();
+
+        let nonce = self.leaves.len() as u64;
+        let metadata_hash = keccak::hashv(&[&metadata.try_to_vec().unwrap()]);
+        let cnft = Cnft {
+            asset_id: cnft_asset_id(&self.tree, nonce),
+            leaf_index: nonce as u32,
+            nonce,
+            data_hash: keccak::hashv(&[
+                metadata_hash.as_ref(),
+                &metadata.seller_fee_basis_points.to_le_bytes(),
+            ])
+            .to_bytes(),
+            creator_hash: keccak::hashv(&[]).to_bytes(),
+        };
+        self.leaves.push([0; 32]);
+        self.set_owner(&cnft, owner);
+        cnft
+    }
+
+    // ? the leaf changes with its owner, the delegate is reset to the owner by a transfer
+    pub fn set_owner(&mut self, cnft: &Cnft, owner: &Pubkey) {
+        self.leaves[cnft.leaf_index as usize] = keccak::hashv(&[
+            &[1], // LeafSchema V1
+            cnft.asset_id.as_ref(),
+            owner.as_ref(),
+            owner.as_ref(),
+            &cnft.nonce.to_le_bytes(),
+            &cnft.data_hash,
+            &cnft.creator_hash,
+        ])
+        .to_bytes();
+    }
+
+    // ? current root and proof of a leaf, the proof nodes are passed as remaining accounts
+    pub fn proof(&self, leaf_index: u32) -> ([u8; 32], Vec<AccountMeta>) {
+        let mut level = self.leaves.clone();
+        level.resize(1 << TREE_MAX_DEPTH, [0; 32]);
+        let mut index = leaf_index as usize;
+        let mut proof = vec![];
+        while level.len() > 1 {
+            proof.push(AccountMeta::new_readonly(
+                Pubkey::new_from_array(level[index ^ 1]),
+                false,
+            ));
+            level = level
+                .chunks(2)
+                .map(|pair| keccak::hashv(&[&pair[0], &pair[1]]).to_bytes())
+                .collect();
+            index /= 2;
+        }
+        (level[0], proof)
+    }
+}
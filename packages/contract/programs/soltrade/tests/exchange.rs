@@ -0,0 +1,465 @@
+mod common;
+
+use {
+    anchor_lang::{
+        prelude::Pubkey, system_program, AnchorSerialize, InstructionData, ToAccountMetas,
+    },
+    common::*,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
+        signature::{Keypair, Signer},
+    },
+    soltrade::{
+        error::SolTradeError,
+        instructions::OptionsAssetItemSOL,
+        state::{
+            AssetItemAuthority, AssetItemCNFT, AssetItemCore, AssetItemNFT, AssetItemSOL,
+            AssetItemSPL, AssetItemStake, AssetItemType, AuthorityKind, Offer, Trade,
+        },
+    },
+};
+
+// ? a trade and its offer, each side of the exchange has its own wallet
+struct TradeWithOffer {
+    maker: Keypair,
+    taker: Keypair,
+    trade: Pubkey,
+    offer: Pubkey,
+}
+
+async fn trade_with_offer(context: &mut ProgramTestContext) -> TradeWithOffer {
+    let maker = funded_keypair(context).await;
+    let taker = funded_keypair(context).await;
+    let trade = create_trade(context, &maker).await;
+    let offer = create_offer(context, &trade, &taker).await;
+    TradeWithOffer {
+        maker,
+        taker,
+        trade,
+        offer,
+    }
+}
+
+async fn accepted_offer(context: &mut ProgramTestContext) -> TradeWithOffer {
+    let swap = trade_with_offer(context).await;
+    swap.accept(context).await;
+    swap
+}
+
+impl TradeWithOffer {
+    // ? accepted as is, accept_offer is covered by the ts tests
+    async fn accept(&self, context: &mut ProgramTestContext) {
+        let offer = self.offer;
+        update_state(context, &self.trade, |trade: &mut Trade| {
+            trade.accepted_offer = offer;
+        })
+        .await;
+        update_state(context, &offer, |offer: &mut Offer| {
+            offer.accepted = true;
+        })
+        .await;
+    }
+
+    // ? the side the asset item comes from, 1 for the trade and 2 for the offer
+    fn from(&self, from_type: u8) -> Pubkey {
+        if from_type == 1 {
+            self.trade
+        } else {
+            self.offer
+        }
+    }
+
+    // ? the wallet of the other side, the only one allowed to exchange the asset item
+    fn receiver(&self, from_type: u8) -> &Keypair {
+        if from_type == 1 {
+            &self.taker
+        } else {
+            &self.maker
+        }
+    }
+
+    // ? the wallet of the side the asset item comes from
+    fn giver(&self, from_type: u8) -> &Keypair {
+        if from_type == 1 {
+            &self.maker
+        } else {
+            &self.taker
+        }
+    }
+
+    fn exchange(
+        &self,
+        from_type: u8,
+        asset_type: AssetItemType,
+        asset_item: Pubkey,
+        user: &Pubkey,
+    ) -> Instruction {
+        let field = |item_type: AssetItemType| Some(asset_item).filter(|_| item_type == asset_type);
+        Instruction {
+            program_id: soltrade::ID,
+            accounts: soltrade::accounts::ExchangeContext {
+                authority: authority_address(),
+                trade: self.trade,
+                offer: self.offer,
+                asset_item_sol: field(AssetItemType::SOL),
+                asset_item_spl: field(AssetItemType::SPL),
+                asset_item_nft: field(AssetItemType::NFT),
+                asset_item_cnft: field(AssetItemType::CNFT),
+                asset_item_core: field(AssetItemType::CORE),
+                asset_item_stake: field(AssetItemType::STAKE),
+                asset_item_authority: field(AssetItemType::AUTHORITY),
+                maker_profile: user_profile_address(&self.maker.pubkey()),
+                taker_profile: user_profile_address(&self.taker.pubkey()),
+                user_from: self.giver(from_type).pubkey(),
+                user: *user,
+                system_program: system_program::ID,
+                user_wsol_account: None,
+                token_program: None,
+            }
+            .to_account_metas(None),
+            data: soltrade::instruction::Exchange {
+                trade_index: 0,
+                offer_index: 0,
+                from_type,
+                asset_type: asset_type.to_code(),
+                asset_index: 0,
+                asset_type_index: 0,
+            }
+            .data(),
+        }
+    }
+
+    // ? a stranger and the wallet of the giving side are both rejected, then the receiver
+    // ? exchanges the asset item
+    async fn assert_only_receiver_exchanges(
+        &self,
+        context: &mut ProgramTestContext,
+        from_type: u8,
+        asset_type: AssetItemType,
+        asset_item: Pubkey,
+    ) {
+        let stranger = funded_keypair(context).await;
+        for user in [&stranger, self.giver(from_type)] {
+            let exchange = self.exchange(from_type, asset_type, asset_item, &user.pubkey());
+            assert_error(
+                process(context, &[exchange], &[user]).await,
+                SolTradeError::ExchangeInvalidUser,
+            );
+        }
+
+        let receiver = self.receiver(from_type);
+        let exchange = self.exchange(from_type, asset_type, asset_item, &receiver.pubkey());
+        process(context, &[exchange], &[receiver]).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn sol_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = trade_with_offer(&mut context).await;
+    let asset_item = asset_item_address(&swap.trade, AssetItemSOL::PREFIX, 0);
+    let add_sol = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::AddAssetItemContext {
+            authority: authority_address(),
+            trade_or_offer: swap.trade,
+            asset_item_sol: Some(asset_item),
+            asset_item_spl: None,
+            asset_item_nft: None,
+            nft_mint: None,
+            nft_metadata: None,
+            spl_mint: None,
+            spl_token: None,
+            spl_vault: None,
+            token_program: None,
+            associated_token_program: None,
+            mint_registry_entry: None,
+            wsol_unwrap: None,
+            trade: None,
+            user: swap.maker.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::AddAssetItem {
+            from_type: 1,
+            index: 0,
+            asset_type_index: 0,
+            asset_type: AssetItemType::SOL.to_code(),
+            options: OptionsAssetItemSOL {
+                amount: LAMPORTS_PER_SOL,
+            }
+            .try_to_vec()
+            .unwrap(),
+        }
+        .data(),
+    };
+    process(&mut context, &[add_sol], &[&swap.maker])
+        .await
+        .unwrap();
+    swap.accept(&mut context).await;
+
+    let taker_before = lamports(&mut context, &swap.taker.pubkey()).await;
+    swap.assert_only_receiver_exchanges(&mut context, 1, AssetItemType::SOL, asset_item)
+        .await;
+
+    let taker_after = lamports(&mut context, &swap.taker.pubkey()).await;
+    assert_eq!(taker_after - taker_before, LAMPORTS_PER_SOL);
+    let item: AssetItemSOL = fetch(&mut context, &asset_item).await;
+    assert!(item.exchanged);
+}
+
+#[tokio::test]
+async fn nft_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemNFT::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemNFT {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::NFT.to_code(),
+                exchanged: false,
+                token: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                collection: Pubkey::default(),
+                seller_fee_basis_points: 0,
+                creators: vec![],
+                royalty_paid: 0,
+                programmable: false,
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemNFT::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::NFT,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemNFT = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
+
+#[tokio::test]
+async fn spl_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemSPL::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemSPL {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::SPL.to_code(),
+                exchanged: false,
+                token: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                amount: 10,
+                decimals: 0,
+                vault: Pubkey::new_unique(),
+                token_program: anchor_spl::token::ID,
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemSPL::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::SPL,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemSPL = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
+
+#[tokio::test]
+async fn cnft_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemCNFT::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemCNFT {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::CNFT.to_code(),
+                exchanged: false,
+                tree: Pubkey::new_unique(),
+                leaf_index: 0,
+                nonce: 0,
+                data_hash: [0; 32],
+                creator_hash: [0; 32],
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemCNFT::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::CNFT,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemCNFT = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
+
+#[tokio::test]
+async fn core_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemCore::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemCore {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::CORE.to_code(),
+                exchanged: false,
+                asset: Pubkey::new_unique(),
+                collection: Pubkey::default(),
+                seller_fee_basis_points: 0,
+                creators: vec![],
+                royalty_paid: 0,
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemCore::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::CORE,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemCore = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
+
+#[tokio::test]
+async fn stake_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemStake::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemStake {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::STAKE.to_code(),
+                exchanged: false,
+                stake: Pubkey::new_unique(),
+                voter: Pubkey::default(),
+                delegated_amount: 0,
+                activation_epoch: 0,
+                deactivation_epoch: u64::MAX,
+                activation: 0,
+                lamports: LAMPORTS_PER_SOL,
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemStake::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::STAKE,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemStake = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
+
+#[tokio::test]
+async fn authority_asset_item_is_only_exchanged_by_the_other_side() {
+    let mut context = start().await;
+    let swap = accepted_offer(&mut context).await;
+    for from_type in [1, 2] {
+        let from = swap.from(from_type);
+        let asset_item = asset_item_address(&from, AssetItemAuthority::PREFIX, 0);
+        set_state(
+            &mut context,
+            &asset_item,
+            &AssetItemAuthority {
+                index: 0,
+                type_index: 0,
+                from,
+                user: swap.giver(from_type).pubkey(),
+                asset_type: AssetItemType::AUTHORITY.to_code(),
+                exchanged: false,
+                kind: AuthorityKind::MintTokens.to_code(),
+                target: Pubkey::new_unique(),
+                escrowed: true,
+                receiver: Pubkey::default(),
+            },
+            AssetItemAuthority::SIZE,
+        );
+
+        swap.assert_only_receiver_exchanges(
+            &mut context,
+            from_type,
+            AssetItemType::AUTHORITY,
+            asset_item,
+        )
+        .await;
+
+        let item: AssetItemAuthority = fetch(&mut context, &asset_item).await;
+        assert!(item.exchanged);
+        assert_eq!(item.receiver, swap.receiver(from_type).pubkey());
+    }
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use {
+    anchor_lang::{system_program, AnchorSerialize, InstructionData, ToAccountMetas},
+    anchor_spl::token::spl_token,
+    common::*,
+    solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, signature::Signer},
+    soltrade::{
+        error::SolTradeError,
+        instructions::OptionsAssetItemNFT,
+        state::{AssetItemNFT, AssetItemType},
+    },
+};
+
+#[tokio::test]
+async fn pnft_is_escrowed_and_claimed_by_the_buyer() {
+    let mut context = start_with(&[TOKEN_METADATA]).await;
+    let maker = funded_keypair(&mut context).await;
+    let buyer = funded_keypair(&mut context).await;
+    let pnft = create_pnft(&mut context, &maker.pubkey()).await;
+    let trade = create_trade(&mut context, &maker).await;
+    let asset_item = asset_item_address(&trade, AssetItemNFT::PREFIX, 0);
+    let escrow_token = create_associated_token_account(&mut context, &trade, &pnft.mint).await;
+    let escrow_token_record = token_record_address(&pnft.mint, &escrow_token);
+
+    // a pnft can't be added as a plain nft
+    let add_nft = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::AddAssetItemContext {
+            authority: authority_address(),
+            trade_or_offer: trade,
+            asset_item_sol: None,
+            asset_item_spl: None,
+            asset_item_nft: Some(asset_item),
+            nft_mint: Some(pnft.mint),
+            nft_metadata: Some(pnft.metadata),
+            spl_mint: None,
+            spl_token: Some(pnft.token),
+            spl_vault: Some(escrow_token),
+            token_program: Some(spl_token::ID),
+            associated_token_program: Some(anchor_spl::associated_token::ID),
+            mint_registry_entry: None,
+            wsol_unwrap: None,
+            trade: None,
+            user: maker.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::AddAssetItem {
+            from_type: 1,
+            index: 0,
+            asset_type_index: 0,
+            asset_type: AssetItemType::NFT.to_code(),
+            options: OptionsAssetItemNFT { token: pnft.token }
+                .try_to_vec()
+                .unwrap(),
+        }
+        .data(),
+    };
+    assert_error(
+        process(&mut context, &[add_nft], &[&maker]).await,
+        SolTradeError::PnftInvalidDeposit,
+    );
+
+    // the pnft moves to the escrow of the trade
+    let add_pnft = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::AddAssetItemPnftContext {
+            authority: authority_address(),
+            trade_or_offer: trade,
+            asset_item_nft: asset_item,
+            nft_mint: pnft.mint,
+            nft_metadata: pnft.metadata,
+            nft_edition: pnft.edition,
+            user_token: pnft.token,
+            user_token_record: pnft.token_record,
+            escrow_token,
+            escrow_token_record,
+            authorization_rules_program: None,
+            authorization_rules: None,
+            mint_registry_entry: None,
+            trade: None,
+            user: maker.pubkey(),
+            sysvar_instructions: SYSVAR_INSTRUCTIONS,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::AddAssetItemPnft {
+            from_type: 1,
+            index: 0,
+            asset_type_index: 0,
+        }
+        .data(),
+    };
+    process(&mut context, &[add_pnft], &[&maker]).await.unwrap();
+    assert_eq!(token_amount(&mut context, &pnft.token).await, 0);
+    assert_eq!(token_amount(&mut context, &escrow_token).await, 1);
+    let escrowed: AssetItemNFT = fetch(&mut context, &asset_item).await;
+    assert!(escrowed.programmable && escrowed.escrowed);
+
+    // not released yet
+    let buyer_token =
+        create_associated_token_account(&mut context, &buyer.pubkey(), &pnft.mint).await;
+    let claim = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::ClaimPnftContext {
+            authority: authority_address(),
+            trade,
+            offer: None,
+            asset_item_nft: asset_item,
+            nft_mint: pnft.mint,
+            nft_metadata: pnft.metadata,
+            nft_edition: pnft.edition,
+            escrow_token,
+            escrow_token_record,
+            receiver: buyer.pubkey(),
+            receiver_token: buyer_token,
+            receiver_token_record: token_record_address(&pnft.mint, &buyer_token),
+            authorization_rules_program: None,
+            authorization_rules: None,
+            user: context.payer.pubkey(),
+            sysvar_instructions: SYSVAR_INSTRUCTIONS,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::ClaimPnft {}.data(),
+    };
+    assert_error(
+        process(&mut context, std::slice::from_ref(&claim), &[]).await,
+        SolTradeError::PnftNotReleased,
+    );
+
+    // the pnft has no creators, the lot is the asset item alone
+    list_fixed_price(&mut context, &trade, &maker, LAMPORTS_PER_SOL).await;
+    let buy = buy_instruction(
+        &trade,
+        &maker.pubkey(),
+        &buyer.pubkey(),
+        &[asset_item],
+        (None, None),
+    );
+    process(&mut context, &[buy], &[&buyer]).await.unwrap();
+    let sold: AssetItemNFT = fetch(&mut context, &asset_item).await;
+    assert_eq!(sold.receiver, buyer.pubkey());
+
+    // anyone pushes the claim, the pnft only goes to the buyer
+    process(&mut context, &[claim], &[]).await.unwrap();
+    assert_eq!(token_amount(&mut context, &buyer_token).await, 1);
+    assert_eq!(token_amount(&mut context, &escrow_token).await, 0);
+    let claimed: AssetItemNFT = fetch(&mut context, &asset_item).await;
+    assert!(!claimed.escrowed);
+}
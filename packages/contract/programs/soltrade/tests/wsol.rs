@@ -0,0 +1,159 @@
+mod common;
+
+use {
+    anchor_lang::{
+        prelude::Pubkey, system_program, AnchorSerialize, InstructionData, ToAccountMetas,
+    },
+    anchor_spl::token::spl_token::{self, native_mint},
+    common::*,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
+        signature::{Keypair, Signer},
+        system_instruction,
+    },
+    soltrade::{
+        error::SolTradeError,
+        instructions::OptionsAssetItemSOL,
+        state::{AssetItemSOL, AssetItemType, UserProfile},
+    },
+};
+
+async fn set_sol_payout(context: &mut ProgramTestContext, user: &Keypair, wrapped: bool) {
+    let instruction = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::SetSolPayoutContext {
+            authority: authority_address(),
+            user_profile: user_profile_address(&user.pubkey()),
+            user: user.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::SetSolPayout { wrapped }.data(),
+    };
+    process(context, &[instruction], &[user]).await.unwrap();
+}
+
+#[tokio::test]
+async fn wrapped_sol_is_unwrapped_on_deposit_and_paid_out_on_request() {
+    let mut context = start().await;
+    let maker = funded_keypair(&mut context).await;
+    let buyer = funded_keypair(&mut context).await;
+    let maker_wsol =
+        create_associated_token_account(&mut context, &maker.pubkey(), &native_mint::ID).await;
+    let buyer_wsol =
+        create_associated_token_account(&mut context, &buyer.pubkey(), &native_mint::ID).await;
+
+    // 0.5 SOL wrapped by the maker
+    let wrap = [
+        system_instruction::transfer(&maker.pubkey(), &maker_wsol, LAMPORTS_PER_SOL / 2),
+        spl_token::instruction::sync_native(&spl_token::ID, &maker_wsol).unwrap(),
+    ];
+    process(&mut context, &wrap, &[&maker]).await.unwrap();
+
+    let trade = create_trade(&mut context, &maker).await;
+    let asset_item = asset_item_address(&trade, AssetItemSOL::PREFIX, 0);
+    let wsol_unwrap = Pubkey::find_program_address(
+        &[
+            asset_item.as_ref(),
+            AssetItemSOL::WSOL_UNWRAP_PREFIX.as_bytes(),
+        ],
+        &soltrade::ID,
+    )
+    .0;
+
+    // 0.2 SOL deposited from wrapped SOL, held as lamports by the trade
+    let trade_before = lamports(&mut context, &trade).await;
+    let wsol_before = token_amount(&mut context, &maker_wsol).await;
+    let add_sol = Instruction {
+        program_id: soltrade::ID,
+        accounts: soltrade::accounts::AddAssetItemContext {
+            authority: authority_address(),
+            trade_or_offer: trade,
+            asset_item_sol: Some(asset_item),
+            asset_item_spl: None,
+            asset_item_nft: None,
+            nft_mint: None,
+            nft_metadata: None,
+            spl_mint: Some(native_mint::ID),
+            spl_token: Some(maker_wsol),
+            spl_vault: None,
+            token_program: Some(spl_token::ID),
+            associated_token_program: None,
+            mint_registry_entry: None,
+            wsol_unwrap: Some(wsol_unwrap),
+            trade: None,
+            user: maker.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: soltrade::instruction::AddAssetItem {
+            from_type: 1,
+            index: 0,
+            asset_type_index: 0,
+            asset_type: AssetItemType::SOL.to_code(),
+            options: OptionsAssetItemSOL {
+                amount: LAMPORTS_PER_SOL / 5,
+            }
+            .try_to_vec()
+            .unwrap(),
+        }
+        .data(),
+    };
+    process(&mut context, &[add_sol], &[&maker]).await.unwrap();
+    assert_eq!(
+        wsol_before - token_amount(&mut context, &maker_wsol).await,
+        LAMPORTS_PER_SOL / 5
+    );
+    assert_eq!(
+        lamports(&mut context, &trade).await - trade_before,
+        LAMPORTS_PER_SOL / 5
+    );
+    assert!(!exists(&mut context, &wsol_unwrap).await);
+    let item: AssetItemSOL = fetch(&mut context, &asset_item).await;
+    assert_eq!(item.amount, LAMPORTS_PER_SOL / 5);
+
+    // both sides take wrapped SOL
+    set_sol_payout(&mut context, &maker, true).await;
+    set_sol_payout(&mut context, &buyer, true).await;
+    let profile: UserProfile = fetch(&mut context, &user_profile_address(&buyer.pubkey())).await;
+    assert!(profile.wsol_payout);
+
+    // 1 SOL, the wrapped SOL account of the maker must be passed
+    list_fixed_price(&mut context, &trade, &maker, LAMPORTS_PER_SOL).await;
+    let buy_without_maker_wsol = buy_instruction(
+        &trade,
+        &maker.pubkey(),
+        &buyer.pubkey(),
+        &[asset_item],
+        (Some(buyer_wsol), None),
+    );
+    assert_error(
+        process(&mut context, &[buy_without_maker_wsol], &[&buyer]).await,
+        SolTradeError::SolPayoutInvalidWsolAccount,
+    );
+    let maker_wsol_before = token_amount(&mut context, &maker_wsol).await;
+    let buy = buy_instruction(
+        &trade,
+        &maker.pubkey(),
+        &buyer.pubkey(),
+        &[asset_item],
+        (Some(buyer_wsol), Some(maker_wsol)),
+    );
+    process(&mut context, &[buy], &[&buyer]).await.unwrap();
+
+    assert_eq!(
+        token_amount(&mut context, &maker_wsol).await - maker_wsol_before,
+        LAMPORTS_PER_SOL
+    );
+    assert_eq!(
+        token_amount(&mut context, &buyer_wsol).await,
+        LAMPORTS_PER_SOL / 5
+    );
+
+    // back to native SOL
+    set_sol_payout(&mut context, &maker, false).await;
+    let profile: UserProfile = fetch(&mut context, &user_profile_address(&maker.pubkey())).await;
+    assert!(!profile.wsol_payout);
+}
@@ -0,0 +1,317 @@
+// ? rust helpers for soltrade clients. a direct swap is built by the maker, signed by it first and
+// ? passed to the taker, which checks it and signs last. the transaction is paid by the taker and
+// ? must be completed before its blockhash expires
+use {
+    anchor_lang::{AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas},
+    solana_sdk::{
+        compute_budget::{self, ComputeBudgetInstruction},
+        hash::Hash,
+        instruction::{AccountMeta, CompiledInstruction, Instruction},
+        message::Message,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+        transaction::Transaction,
+    },
+    soltrade::{
+        instructions::DirectSwapAsset,
+        state::{AssetItemType, Authority, MintRegistryEntry},
+    },
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::create_associated_token_account_idempotent,
+    },
+    thiserror::Error,
+};
+
+// ? same limit as the ts sdk, a swap of nfts with creators is heavy
+pub const DIRECT_SWAP_COMPUTE_UNITS: u32 = 1_400_000;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DirectSwapError {
+    #[error("transaction is not a direct swap")]
+    NotADirectSwap,
+    #[error("transaction holds an instruction other than the swap")]
+    UnexpectedInstruction,
+    #[error("transaction is not paid by the taker")]
+    InvalidFeePayer,
+    #[error("transaction is not signed by the maker")]
+    MissingMakerSignature,
+}
+
+// ? direct swap read back from a transaction, what the taker is asked to sign
+#[derive(Debug)]
+pub struct DirectSwap {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub assets: Vec<DirectSwapAsset>,
+    // remaining accounts of the swap, see DirectSwapContext
+    pub accounts: Vec<Pubkey>,
+}
+
+pub fn authority_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[Authority::PREFIX.as_bytes(), soltrade::ID.as_ref()],
+        &soltrade::ID,
+    )
+    .0
+}
+
+pub fn mint_registry_entry_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[MintRegistryEntry::PREFIX.as_bytes(), mint.as_ref()],
+        &soltrade::ID,
+    )
+    .0
+}
+
+// ? builds a direct swap, the tokens go to the associated token accounts of the other side,
+// ? created by the taker if needed
+pub struct DirectSwapBuilder {
+    maker: Pubkey,
+    taker: Pubkey,
+    assets: Vec<DirectSwapAsset>,
+    accounts: Vec<AccountMeta>,
+    // creators of the nfts given by the maker and by the taker
+    maker_creators: Vec<AccountMeta>,
+    taker_creators: Vec<AccountMeta>,
+    create_token_accounts: Vec<Instruction>,
+}
+
+impl DirectSwapBuilder {
+    pub fn new(maker: Pubkey, taker: Pubkey) -> Self {
+        Self {
+            maker,
+            taker,
+            assets: vec![],
+            accounts: vec![],
+            maker_creators: vec![],
+            taker_creators: vec![],
+            create_token_accounts: vec![],
+        }
+    }
+
+    // ? lamports given by the maker or the taker, they pay the royalties of the nfts it receives
+    pub fn sol(&mut self, from_maker: bool, lamports: u64) -> &mut Self {
+        self.assets.push(DirectSwapAsset {
+            asset_type: AssetItemType::SOL.to_code(),
+            from_maker,
+            amount: lamports,
+        });
+        self
+    }
+
+    // ? tokens given from from_token, a token account of the giver
+    pub fn spl(
+        &mut self,
+        from_maker: bool,
+        mint: Pubkey,
+        from_token: Pubkey,
+        token_program: Pubkey,
+        amount: u64,
+    ) -> &mut Self {
+        let to_token = self.receiver_token(from_maker, &mint, &token_program);
+        self.assets.push(DirectSwapAsset {
+            asset_type: AssetItemType::SPL.to_code(),
+            from_maker,
+            amount,
+        });
+        self.accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(from_token, false),
+            AccountMeta::new(to_token, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(mint_registry_entry_address(&mint), false),
+        ]);
+        self
+    }
+
+    // ? nft given from from_token, a token account of the giver. creators are the ones of its
+    // ? metadata with a share, in order
+    pub fn nft(
+        &mut self,
+        from_maker: bool,
+        mint: Pubkey,
+        metadata: Pubkey,
+        from_token: Pubkey,
+        token_program: Pubkey,
+        creators: &[Pubkey],
+    ) -> &mut Self {
+        let to_token = self.receiver_token(from_maker, &mint, &token_program);
+        self.assets.push(DirectSwapAsset {
+            asset_type: AssetItemType::NFT.to_code(),
+            from_maker,
+            amount: 0,
+        });
+        self.accounts.extend([
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(metadata, false),
+            AccountMeta::new(from_token, false),
+            AccountMeta::new(to_token, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(mint_registry_entry_address(&mint), false),
+        ]);
+        let creators = creators
+            .iter()
+            .map(|creator| AccountMeta::new(*creator, false));
+        if from_maker {
+            self.maker_creators.extend(creators);
+        } else {
+            self.taker_creators.extend(creators);
+        }
+        self
+    }
+
+    fn receiver_token(
+        &mut self,
+        from_maker: bool,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        let to = if from_maker { self.taker } else { self.maker };
+        self.create_token_accounts
+            .push(create_associated_token_account_idempotent(
+                &self.taker,
+                &to,
+                mint,
+                token_program,
+            ));
+        get_associated_token_address_with_program_id(&to, mint, token_program)
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        // * the lamports of a side pay the royalties of the nfts of the other side
+        let gives_sol = |from_maker: bool| {
+            self.assets.iter().any(|asset| {
+                asset.from_maker == from_maker && asset.asset_type == AssetItemType::SOL.to_code()
+            })
+        };
+        let mut accounts = soltrade::accounts::DirectSwapContext {
+            authority: authority_address(),
+            maker: self.maker,
+            taker: self.taker,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(self.accounts.iter().cloned());
+        if gives_sol(true) {
+            accounts.extend(self.taker_creators.iter().cloned());
+        }
+        if gives_sol(false) {
+            accounts.extend(self.maker_creators.iter().cloned());
+        }
+
+        Instruction {
+            program_id: soltrade::ID,
+            accounts,
+            data: soltrade::instruction::DirectSwap {
+                assets: self.assets.clone(),
+            }
+            .data(),
+        }
+    }
+
+    // ? unsigned transaction paid by the taker, see sign_as_maker
+    pub fn transaction(&self, recent_blockhash: Hash) -> Transaction {
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            DIRECT_SWAP_COMPUTE_UNITS,
+        )];
+        instructions.extend(self.create_token_accounts.iter().cloned());
+        instructions.push(self.instruction());
+        Transaction::new_unsigned(Message::new_with_blockhash(
+            &instructions,
+            Some(&self.taker),
+            &recent_blockhash,
+        ))
+    }
+}
+
+// ? the maker signs first, the transaction then goes to the taker
+pub fn sign_as_maker(transaction: &mut Transaction, maker: &Keypair) {
+    let recent_blockhash = transaction.message.recent_blockhash;
+    transaction.partial_sign(&[maker], recent_blockhash);
+}
+
+// ? what the taker is asked to sign: the transaction must hold only the swap, a compute unit
+// ? limit and the creation of token accounts, be paid by the taker and signed by the maker
+pub fn read_direct_swap(transaction: &Transaction) -> Result<DirectSwap, DirectSwapError> {
+    let message = &transaction.message;
+    let program_id = |ix: &CompiledInstruction| message.account_keys[ix.program_id_index as usize];
+
+    let mut swaps = vec![];
+    for ix in message.instructions.iter() {
+        let allowed = match program_id(ix) {
+            // a compute unit price would be paid by the taker
+            id if id == compute_budget::ID => matches!(
+                ix.data.first(),
+                Some(2) // SetComputeUnitLimit
+            ),
+            id if id == spl_associated_token_account::ID => ix.data == [1], // CreateIdempotent
+            id if id == soltrade::ID => {
+                swaps.push(ix);
+                true
+            }
+            _ => false,
+        };
+        if !allowed {
+            return Err(DirectSwapError::UnexpectedInstruction);
+        }
+    }
+    let [swap] = swaps[..] else {
+        return Err(DirectSwapError::NotADirectSwap);
+    };
+
+    let data = swap
+        .data
+        .strip_prefix(&soltrade::instruction::DirectSwap::DISCRIMINATOR)
+        .ok_or(DirectSwapError::NotADirectSwap)?;
+    let assets = soltrade::instruction::DirectSwap::try_from_slice(data)
+        .map_err(|_| DirectSwapError::NotADirectSwap)?
+        .assets;
+    let accounts: Vec<Pubkey> = swap
+        .accounts
+        .iter()
+        .map(|index| message.account_keys[*index as usize])
+        .collect();
+    // authority, maker, taker, system_program, then the remaining accounts
+    if accounts.len() < 4 {
+        return Err(DirectSwapError::NotADirectSwap);
+    }
+    let (maker, taker) = (accounts[1], accounts[2]);
+
+    // ! @check
+    // |-paid by the taker
+    if message.account_keys.first() != Some(&taker) {
+        return Err(DirectSwapError::InvalidFeePayer);
+    }
+    // |-signed by the maker
+    let signed = transaction.verify_with_results();
+    let maker_signed = message.account_keys[..message.header.num_required_signatures as usize]
+        .iter()
+        .position(|key| *key == maker)
+        .map_or(false, |index| signed[index]);
+    if !maker_signed {
+        return Err(DirectSwapError::MissingMakerSignature);
+    }
+
+    Ok(DirectSwap {
+        maker,
+        taker,
+        assets,
+        accounts: accounts[4..].to_vec(),
+    })
+}
+
+// ? the taker checks the swap and signs last, the transaction can then be sent
+pub fn sign_as_taker(
+    transaction: &mut Transaction,
+    taker: &Keypair,
+) -> Result<DirectSwap, DirectSwapError> {
+    let swap = read_direct_swap(transaction)?;
+    if swap.taker != taker.pubkey() {
+        return Err(DirectSwapError::InvalidFeePayer);
+    }
+    let recent_blockhash = transaction.message.recent_blockhash;
+    transaction.partial_sign(&[taker], recent_blockhash);
+    Ok(swap)
+}
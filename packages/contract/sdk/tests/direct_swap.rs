@@ -0,0 +1,128 @@
+#[path = "../../programs/soltrade/tests/common/mod.rs"]
+mod common;
+
+use {
+    anchor_spl::token::spl_token,
+    common::*,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::Transaction,
+    },
+    soltrade_sdk::{
+        read_direct_swap, sign_as_maker, sign_as_taker, DirectSwapBuilder, DirectSwapError,
+    },
+    spl_associated_token_account::get_associated_token_address,
+};
+
+// ? a mint of 6 decimals, amount minted to an associated token account of the owner
+async fn create_mint(context: &mut ProgramTestContext, owner: &Keypair, amount: u64) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let token = get_associated_token_address(&owner.pubkey(), &mint.pubkey());
+    let instructions = [
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &owner.pubkey(),
+            None,
+            6,
+        )
+        .unwrap(),
+        spl_associated_token_account::instruction::create_associated_token_account(
+            &context.payer.pubkey(),
+            &owner.pubkey(),
+            &mint.pubkey(),
+            &spl_token::ID,
+        ),
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &token,
+            &owner.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap(),
+    ];
+    process(context, &instructions, &[&mint, owner])
+        .await
+        .unwrap();
+    mint.pubkey()
+}
+
+#[tokio::test]
+async fn direct_swap_is_signed_by_the_maker_then_the_taker() {
+    let mut context = start().await;
+    let maker = funded_keypair(&mut context).await;
+    let taker = funded_keypair(&mut context).await;
+    let mint = create_mint(&mut context, &taker, 1_000_000).await;
+    let taker_token = get_associated_token_address(&taker.pubkey(), &mint);
+    let maker_token = get_associated_token_address(&maker.pubkey(), &mint);
+
+    // 0.3 SOL of the maker for 1 token of the taker
+    let mut builder = DirectSwapBuilder::new(maker.pubkey(), taker.pubkey());
+    builder.sol(true, LAMPORTS_PER_SOL * 3 / 10).spl(
+        false,
+        mint,
+        taker_token,
+        spl_token::ID,
+        1_000_000,
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+
+    // the maker signs first, the taker can't sign before
+    let mut transaction = builder.transaction(recent_blockhash);
+    assert_eq!(
+        read_direct_swap(&transaction).unwrap_err(),
+        DirectSwapError::MissingMakerSignature
+    );
+    sign_as_maker(&mut transaction, &maker);
+
+    // passed serialized to the taker
+    let bytes = bincode::serialize(&transaction).unwrap();
+    let mut transaction: Transaction = bincode::deserialize(&bytes).unwrap();
+
+    // anything else than the swap is refused
+    let mut tampered = transaction.clone();
+    tampered
+        .message
+        .instructions
+        .push(tampered.message.instructions[0].clone());
+    tampered.message.instructions.last_mut().unwrap().data =
+        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(1_000_000)
+            .data;
+    assert_eq!(
+        read_direct_swap(&tampered).unwrap_err(),
+        DirectSwapError::UnexpectedInstruction
+    );
+
+    let swap = sign_as_taker(&mut transaction, &taker).unwrap();
+    assert_eq!(swap.maker, maker.pubkey());
+    assert_eq!(swap.assets.len(), 2);
+    assert_eq!(swap.assets[1].amount, 1_000_000);
+
+    let maker_before = lamports(&mut context, &maker.pubkey()).await;
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        maker_before - lamports(&mut context, &maker.pubkey()).await,
+        LAMPORTS_PER_SOL * 3 / 10
+    );
+    assert_eq!(token_amount(&mut context, &maker_token).await, 1_000_000);
+    assert_eq!(token_amount(&mut context, &taker_token).await, 0);
+}
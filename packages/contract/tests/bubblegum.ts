@@ -0,0 +1,138 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, Transaction } from "@solana/web3.js";
+import {
+  PROGRAM_ID as BUBBLEGUM_PROGRAM_ID,
+  MetadataArgs,
+  TokenProgramVersion,
+  TokenStandard,
+  computeCompressedNFTHash,
+  computeCreatorHash,
+  computeDataHash,
+  createCreateTreeInstruction,
+  createMintV1Instruction,
+  getLeafAssetId,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  MerkleTree,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  createAllocTreeIx,
+} from "@solana/spl-account-compression";
+
+// Local compressed nfts for the tests, bubblegum and account compression are loaded from
+// tests/fixtures by Anchor.toml, run `bun run fixtures` once to dump them. There is no
+// indexer on the local validator, the leaves are mirrored here to build the proofs.
+
+export { BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID }
+
+export const getTreeAuthority = (merkleTree: PublicKey) => PublicKey.findProgramAddressSync(
+  [merkleTree.toBuffer()],
+  BUBBLEGUM_PROGRAM_ID,
+)[0]
+
+// ? asset id of a compressed nft, checked against the mint registry
+export const getAssetId = (merkleTree: PublicKey, nonce: number) => PublicKey.findProgramAddressSync(
+  [Buffer.from('asset'), merkleTree.toBuffer(), new anchor.BN(nonce).toArrayLike(Buffer, 'le', 8)],
+  BUBBLEGUM_PROGRAM_ID,
+)[0]
+
+const MAX_DEPTH = 3
+const MAX_BUFFER_SIZE = 8
+
+// ? a merkle tree of compressed nfts and the mirror of its leaves, the provider wallet is the
+// ? tree creator
+export const createTree = async () => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const merkleTree = Keypair.generate()
+  const treeAuthority = getTreeAuthority(merkleTree.publicKey)
+  const tx = new Transaction()
+  tx.add(await createAllocTreeIx(
+    provider.connection,
+    merkleTree.publicKey,
+    payer.publicKey,
+    { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE },
+    0,
+  ))
+  tx.add(createCreateTreeInstruction(
+    {
+      treeAuthority,
+      merkleTree: merkleTree.publicKey,
+      payer: payer.publicKey,
+      treeCreator: payer.publicKey,
+      logWrapper: SPL_NOOP_PROGRAM_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    },
+    { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE, public: false },
+  ))
+  await provider.sendAndConfirm(tx, [payer, merkleTree])
+
+  const leaves: Buffer[] = []
+  const items: { assetId: PublicKey, metadata: MetadataArgs }[] = []
+
+  return {
+    merkleTree: merkleTree.publicKey,
+    treeAuthority,
+
+    // ? mints a compressed nft to the owner
+    mint: async (owner: PublicKey) => {
+      const metadata: MetadataArgs = {
+        name: "Soltrade Test",
+        symbol: "STT",
+        uri: "",
+        sellerFeeBasisPoints: 0,
+        primarySaleHappened: false,
+        isMutable: true,
+        editionNonce: null,
+        tokenStandard: TokenStandard.NonFungible,
+        collection: null,
+        uses: null,
+        tokenProgramVersion: TokenProgramVersion.Original,
+        creators: [],
+      }
+      await provider.sendAndConfirm(new Transaction().add(createMintV1Instruction(
+        {
+          treeAuthority,
+          leafOwner: owner,
+          leafDelegate: owner,
+          merkleTree: merkleTree.publicKey,
+          payer: payer.publicKey,
+          treeDelegate: payer.publicKey,
+          logWrapper: SPL_NOOP_PROGRAM_ID,
+          compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        },
+        { message: metadata },
+      )), [payer])
+
+      const leafIndex = leaves.length
+      const assetId = await getLeafAssetId(merkleTree.publicKey, new anchor.BN(leafIndex))
+      items.push({ assetId, metadata })
+      leaves.push(computeCompressedNFTHash(assetId, owner, owner, new anchor.BN(leafIndex), metadata))
+
+      return {
+        tree: merkleTree.publicKey,
+        assetId,
+        leafIndex,
+        nonce: leafIndex,
+        dataHash: computeDataHash(metadata),
+        creatorHash: computeCreatorHash(metadata.creators),
+      }
+    },
+
+    // ? the leaf changes with its owner, the delegate is reset to the owner by a transfer
+    setOwner: (leafIndex: number, owner: PublicKey) => {
+      const { assetId, metadata } = items[leafIndex]
+      leaves[leafIndex] = computeCompressedNFTHash(assetId, owner, owner, new anchor.BN(leafIndex), metadata)
+    },
+
+    // ? current root and proof of a leaf, the proof nodes are passed as remaining accounts
+    getProof: (leafIndex: number) => {
+      const { root, proof } = MerkleTree.sparseMerkleTreeFromLeaves(leaves, MAX_DEPTH).getProof(leafIndex)
+      return {
+        root: Array.from(root),
+        proof: proof.map(node => ({ pubkey: new PublicKey(node), isWritable: false, isSigner: false })),
+      }
+    },
+  }
+}
@@ -0,0 +1,116 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
+
+// Local metaplex core assets for the tests, the core program is loaded from tests/fixtures by
+// Anchor.toml, run `bun run fixtures` once to dump it. The instructions are encoded here, the
+// core client is umi based and does not mix with web3.js 1.x.
+
+export const MPL_CORE_PROGRAM_ID = new PublicKey("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d")
+
+const CREATE_V1 = 0
+const CREATE_COLLECTION_V1 = 1
+
+const u16 = (value: number) => {
+  const buffer = Buffer.alloc(2)
+  buffer.writeUInt16LE(value)
+  return buffer
+}
+const u32 = (value: number) => {
+  const buffer = Buffer.alloc(4)
+  buffer.writeUInt32LE(value)
+  return buffer
+}
+const string = (value: string) => Buffer.concat([u32(Buffer.byteLength(value)), Buffer.from(value)])
+
+export type CorePlugin =
+  | { type: 'royalties', basisPoints: number, creators: { address: PublicKey, percentage: number }[] }
+  | { type: 'permanentFreezeDelegate', frozen: boolean }
+
+// ? Option<Vec<PluginAuthorityPair>>, every plugin with its default authority
+const plugins = (plugins: CorePlugin[]) => Buffer.concat([
+  Buffer.from([1]),
+  u32(plugins.length),
+  ...plugins.map(plugin => {
+    const data = plugin.type == 'royalties'
+      ? Buffer.concat([
+        Buffer.from([0]),
+        u16(plugin.basisPoints),
+        u32(plugin.creators.length),
+        ...plugin.creators.map(creator => Buffer.concat([creator.address.toBuffer(), Buffer.from([creator.percentage])])),
+        Buffer.from([0]), // rule set: none
+      ])
+      : Buffer.from([5, plugin.frozen ? 1 : 0])
+    return Buffer.concat([data, Buffer.from([0])])
+  }),
+])
+
+const optional = (account?: PublicKey, isWritable = false, isSigner = false) => ({
+  pubkey: account || MPL_CORE_PROGRAM_ID,
+  isWritable: !!account && isWritable,
+  isSigner: !!account && isSigner,
+})
+
+// ? a core collection, the provider wallet is its update authority
+export const createCoreCollection = async (opts?: { plugins?: CorePlugin[] }) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider
+  const payer = (provider.wallet as anchor.Wallet).payer
+  const collection = Keypair.generate()
+
+  const ix = new TransactionInstruction({
+    programId: MPL_CORE_PROGRAM_ID,
+    keys: [
+      { pubkey: collection.publicKey, isWritable: true, isSigner: true },
+      optional(),
+      { pubkey: payer.publicKey, isWritable: true, isSigner: true },
+      { pubkey: SystemProgram.programId, isWritable: false, isSigner: false },
+    ],
+    data: Buffer.concat([
+      Buffer.from([CREATE_COLLECTION_V1]),
+      string("Soltrade Core Collection"),
+      string(""),
+      plugins(opts?.plugins || []),
+    ]),
+  })
+  await provider.sendAndConfirm(new Transaction().add(ix), [payer, collection])
+  return collection.publicKey
+}
+
+// ? a core asset of the owner, in the collection when given
+export const createCoreAsset = async (opts: {
+  owner: PublicKey,
+  collection?: PublicKey,
+  plugins?: CorePlugin[],
+}) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider
+  const payer = (provider.wallet as anchor.Wallet).payer
+  const asset = Keypair.generate()
+
+  const ix = new TransactionInstruction({
+    programId: MPL_CORE_PROGRAM_ID,
+    keys: [
+      { pubkey: asset.publicKey, isWritable: true, isSigner: true },
+      optional(opts.collection, true),
+      optional(opts.collection ? payer.publicKey : undefined, false, true),
+      { pubkey: payer.publicKey, isWritable: true, isSigner: true },
+      optional(opts.owner),
+      optional(),
+      { pubkey: SystemProgram.programId, isWritable: false, isSigner: false },
+      optional(),
+    ],
+    data: Buffer.concat([
+      Buffer.from([CREATE_V1]),
+      Buffer.from([0]), // data state: account state
+      string("Soltrade Core"),
+      string(""),
+      plugins(opts.plugins || []),
+    ]),
+  })
+  await provider.sendAndConfirm(new Transaction().add(ix), [payer, asset])
+  return asset.publicKey
+}
+
+// ? owner of a core asset, right after the account key
+export const getCoreOwner = async (asset: PublicKey) => {
+  const account = await anchor.getProvider().connection.getAccountInfo(asset)
+  return new PublicKey(account.data.subarray(1, 33))
+}
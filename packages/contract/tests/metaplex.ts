@@ -0,0 +1,205 @@
+import * as anchor from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, Keypair, PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY, Transaction } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID,
+  Creator,
+  TokenStandard,
+  createCreateInstruction,
+  createCreateMasterEditionV3Instruction,
+  createCreateMetadataAccountV3Instruction,
+  createMintInstruction,
+  createVerifySizedCollectionItemInstruction,
+} from "@metaplex-foundation/mpl-token-metadata";
+
+// Local metaplex nfts for the tests, the token metadata program is loaded from
+// tests/fixtures by Anchor.toml, run `bun run fixtures` once to dump it.
+
+export { TOKEN_METADATA_PROGRAM_ID }
+
+export const getMetadata = (mint: PublicKey) => PublicKey.findProgramAddressSync(
+  [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+  TOKEN_METADATA_PROGRAM_ID,
+)[0]
+
+export const getMasterEdition = (mint: PublicKey) => PublicKey.findProgramAddressSync(
+  [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("edition")],
+  TOKEN_METADATA_PROGRAM_ID,
+)[0]
+
+// ? token record of a programmable nft in a token account
+export const getTokenRecord = (mint: PublicKey, token: PublicKey) => PublicKey.findProgramAddressSync(
+  [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("token_record"), token.toBuffer()],
+  TOKEN_METADATA_PROGRAM_ID,
+)[0]
+
+// ? mints a 1/1 nft with metadata and master edition to the owner, the provider wallet
+// ? is the update authority. a collection nft is created with isCollection, an item of
+// ? a collection is verified right away
+export const createNft = async (opts: {
+  owner: PublicKey,
+  isCollection?: boolean,
+  collection?: PublicKey,
+  sellerFeeBasisPoints?: number,
+  creators?: Creator[],
+}) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider
+  const connection = provider.connection
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const mint = await createMint(connection, payer, payer.publicKey, payer.publicKey, 0)
+  const tokenAccount = await getOrCreateAssociatedTokenAccount(connection, payer, mint, opts.owner)
+  await mintTo(connection, payer, mint, tokenAccount.address, payer, 1)
+
+  const metadata = getMetadata(mint)
+  const masterEdition = getMasterEdition(mint)
+  const tx = new Transaction()
+  tx.add(createCreateMetadataAccountV3Instruction(
+    {
+      metadata,
+      mint,
+      mintAuthority: payer.publicKey,
+      payer: payer.publicKey,
+      updateAuthority: payer.publicKey,
+    },
+    {
+      createMetadataAccountArgsV3: {
+        data: {
+          name: "Soltrade Test",
+          symbol: "STT",
+          uri: "",
+          sellerFeeBasisPoints: opts.sellerFeeBasisPoints || 0,
+          creators: opts.creators || null,
+          collection: opts.collection ? { key: opts.collection, verified: false } : null,
+          uses: null,
+        },
+        isMutable: true,
+        collectionDetails: opts.isCollection ? { __kind: "V1", size: 0 } : null,
+      },
+    },
+  ))
+  tx.add(createCreateMasterEditionV3Instruction(
+    {
+      edition: masterEdition,
+      mint,
+      updateAuthority: payer.publicKey,
+      mintAuthority: payer.publicKey,
+      payer: payer.publicKey,
+      metadata,
+    },
+    { createMasterEditionArgs: { maxSupply: 0 } },
+  ))
+  if (opts.collection) {
+    tx.add(createVerifySizedCollectionItemInstruction({
+      metadata,
+      collectionAuthority: payer.publicKey,
+      payer: payer.publicKey,
+      collectionMint: opts.collection,
+      collection: getMetadata(opts.collection),
+      collectionMasterEditionAccount: getMasterEdition(opts.collection),
+    }))
+  }
+  await provider.sendAndConfirm(tx, [payer])
+
+  return {
+    mint,
+    metadata,
+    masterEdition,
+    tokenAccount: tokenAccount.address,
+  }
+}
+
+// ? a collection nft and its verified items
+export const createCollection = async (owner: PublicKey) => {
+  const collection = await createNft({ owner, isCollection: true })
+  return {
+    ...collection,
+    createItem: (itemOwner: PublicKey, opts?: Omit<Parameters<typeof createNft>[0], 'owner' | 'collection'>) =>
+      createNft({ ...opts, owner: itemOwner, collection: collection.mint }),
+  }
+}
+
+// ? mints a programmable nft, without rule set, to the associated token account of the owner
+export const createPnft = async (opts: {
+  owner: PublicKey,
+  sellerFeeBasisPoints?: number,
+  creators?: Creator[],
+}) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const mint = Keypair.generate()
+  const metadata = getMetadata(mint.publicKey)
+  const masterEdition = getMasterEdition(mint.publicKey)
+  const tokenAccount = getAssociatedTokenAddressSync(mint.publicKey, opts.owner, true)
+
+  const create = createCreateInstruction(
+    {
+      metadata,
+      masterEdition,
+      mint: mint.publicKey,
+      authority: payer.publicKey,
+      payer: payer.publicKey,
+      updateAuthority: payer.publicKey,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      splTokenProgram: TOKEN_PROGRAM_ID,
+    },
+    {
+      createArgs: {
+        __kind: "V1",
+        assetData: {
+          name: "Soltrade Test",
+          symbol: "STT",
+          uri: "",
+          sellerFeeBasisPoints: opts.sellerFeeBasisPoints || 0,
+          creators: opts.creators || null,
+          primarySaleHappened: false,
+          isMutable: true,
+          tokenStandard: TokenStandard.ProgrammableNonFungible,
+          collection: null,
+          uses: null,
+          collectionDetails: null,
+          ruleSet: null,
+        },
+        decimals: 0,
+        printSupply: { __kind: "Zero" },
+      },
+    },
+  )
+  // the mint is created by the instruction, it signs
+  create.keys.find(key => key.pubkey.equals(mint.publicKey)).isSigner = true
+
+  const tx = new Transaction()
+  tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }))
+  tx.add(create)
+  tx.add(createMintInstruction(
+    {
+      token: tokenAccount,
+      tokenOwner: opts.owner,
+      metadata,
+      masterEdition,
+      tokenRecord: getTokenRecord(mint.publicKey, tokenAccount),
+      mint: mint.publicKey,
+      authority: payer.publicKey,
+      payer: payer.publicKey,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      splTokenProgram: TOKEN_PROGRAM_ID,
+      splAtaProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    },
+    { mintArgs: { __kind: "V1", amount: 1, authorizationData: null } },
+  ))
+  await provider.sendAndConfirm(tx, [payer, mint])
+
+  return {
+    mint: mint.publicKey,
+    metadata,
+    masterEdition,
+    tokenAccount,
+  }
+}
@@ -1,27 +1,96 @@
 import * as borsh from 'borsh'
 
 import * as anchor from "@coral-xyz/anchor";
-import { PublicKey, Struct } from "@solana/web3.js";
+import { ComputeBudgetProgram, Ed25519Program, Keypair, PublicKey, StakeProgram, Struct, SYSVAR_CLOCK_PUBKEY, SYSVAR_INSTRUCTIONS_PUBKEY, Transaction } from "@solana/web3.js";
 import { Soltrade } from "../target/types/soltrade";
 import type { MethodsBuilder } from '@coral-xyz/anchor/dist/cjs/program/namespace/methods'
-import { OptionAssetItemSOL, OptionAssetItemSOLSchema, OptionAssetItemSPL, OptionAssetItemSPLSchema } from './sdk_struct';
+import { Metadata } from '@metaplex-foundation/mpl-token-metadata';
+import { OptionAssetItemNFT, OptionAssetItemNFTSchema, OptionAssetItemSOL, OptionAssetItemSOLSchema, OptionAssetItemSPL, OptionAssetItemSPLSchema } from './sdk_struct';
+import { NATIVE_MINT, createApproveInstruction, createAssociatedTokenAccountIdempotentInstruction, getAssociatedTokenAddressSync } from '@solana/spl-token';
+import { TOKEN_METADATA_PROGRAM_ID, getMasterEdition, getMetadata, getTokenRecord } from './metaplex';
+import { BUBBLEGUM_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, SPL_NOOP_PROGRAM_ID, getAssetId, getTreeAuthority } from './bubblegum';
+import { MPL_CORE_PROGRAM_ID } from './core';
+
+
+// Programs
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+
+
+// Mint registry, matches MintRegistryMode / MintRegistryStatus codes
+export const MINT_REGISTRY_MODE = { open: 0, allowlist: 1, denylist: 2 }
+export const MINT_REGISTRY_STATUS = { allowed: 1, denied: 2 }
+
+
+// Wanted assets, matches WantedAssetType codes
+export const WANTED_ASSET_TYPE = { sol: 1, spl: 2, collection: 3 }
+export type WantedAsset =
+  | { type: 'sol', amount: number | anchor.BN }
+  | { type: 'spl', mint: PublicKey, amount: number | anchor.BN }
+  | { type: 'collection', mint: PublicKey }
+export function toWantedAssets(wantedAssets: WantedAsset[]) {
+  return wantedAssets.map(wanted => ({
+    assetType: WANTED_ASSET_TYPE[wanted.type],
+    mint: wanted.type == 'sol' ? PublicKey.default : wanted.mint,
+    amount: new anchor.BN(wanted.type == 'collection' ? 0 : wanted.amount),
+  }))
+}
+
+
+
+// Auto accept, matches AutoAcceptRule codes
+export const AUTO_ACCEPT_RULE = { disabled: 0, minLamports: 1, minTokens: 2, wantedAssets: 3 }
+
+
+// Dutch auction, matches DutchAuctionDecay codes
+export const DUTCH_AUCTION_DECAY = { linear: 0, stepwise: 1 }
+
+
+// Authority asset items, matches AuthorityKind codes
+export const AUTHORITY_KIND = { mintTokens: 0, freezeAccount: 1, programUpgrade: 2 }
 
 
 // SDK Interface
-export type AssetType = 'sol' | 'spl'
+export type AssetType = 'sol' | 'spl' | 'nft' | 'cnft' | 'core' | 'stake' | 'authority'
 export interface AddAssetsToTradeAssetBase {
   // type: 'sol' | 'spl' | 'token',
 }
 export interface AddAssetsToTradeAssetSOL {
   type: 'sol',
   amount: number,
+  // wrapped SOL account the lamports are unwrapped from, instead of the wallet
+  wsolToken?: PublicKey,
 }
 export interface AddAssetsToTradeAssetSPL {
   type: 'spl',
   mint: PublicKey,
   token: PublicKey,
+  amount?: number | anchor.BN,
+  // accounts of the transfer hook of a token 2022 mint
+  extraAccounts?: anchor.web3.AccountMeta[],
+}
+export interface AddAssetsToTradeAssetNFT {
+  type: 'nft',
+  mint: PublicKey,
+  token: PublicKey,
+}
+export type AddAssetsToTradeAssets = AddAssetsToTradeAssetSOL | AddAssetsToTradeAssetSPL | AddAssetsToTradeAssetNFT
+// asset given by one side of a direct swap, the token account is the one of the giver
+export interface DirectSwapAsset {
+  from: 'maker' | 'taker',
+  item: AddAssetsToTradeAssets,
 }
-export type AddAssetsToTradeAssets = AddAssetsToTradeAssetSOL | AddAssetsToTradeAssetSPL
+
+
+// offer signed off-chain by the taker, matches SignedOffer
+export interface SignedOffer {
+  program: PublicKey,
+  trade: PublicKey,
+  taker: PublicKey,
+  assets: { mint: PublicKey, amount: anchor.BN }[],
+  expiry: anchor.BN,
+  nonce: anchor.BN,
+}
+export const SIGNED_OFFER_DOMAIN = Buffer.from('soltrade signed offer:')
 
 
 // SDK
@@ -39,26 +108,427 @@ export class SolTradeSDK {
   async createTrade(
     opts?: {
       allowedUsers?: PublicKey[],
+      wantedAssets?: WantedAsset[],
+      strict?: boolean,
+      user?: PublicKey,
     }
   ) {
+    const user = opts?.user || this.program.provider.publicKey
     const authority = await this.getAuthority()
+    const profile = await this.getUserProfile(user)
     const trade = this.PDA.getTrade(authority.tradeCount);
+    const userProfile = this.PDA.getUserProfile(user);
+    const userTrade = this.PDA.getUserTrade(user, profile?.tradeCount || 0);
     const builder = this.program.methods
       .createTrade(
         authority.tradeCount,
         opts?.allowedUsers || [],
+        toWantedAssets(opts?.wantedAssets || []),
+        opts?.strict || false,
       )
       .accounts({
         trade: trade.address,
         authority: this.PDA.getAuthority().address,
+        userProfile: userProfile.address,
+        userTrade: userTrade.address,
+        user,
+      })
+    return {
+      pda: {
+        trade,
+        userProfile,
+        userTrade,
+      },
+      builder,
+    }
+  }
+  async createOffer(
+    tradeAddress: PublicKey,
+    opts?: {
+      user?: PublicKey,
+      // auction only, token accounts are needed when the auction is not in SOL
+      bid?: number | anchor.BN,
+      userTokenAccount?: PublicKey,
+    }
+  ) {
+    const user = opts?.user || this.program.provider.publicKey
+    const trade = await this.getTrade(tradeAddress)
+    const profile = await this.getUserProfile(user)
+    const offer = this.PDA.getTradeOffer(tradeAddress, trade.offerCount);
+    const userProfile = this.PDA.getUserProfile(user);
+    const userOffer = this.PDA.getUserOffer(user, profile?.offerCount || 0);
+
+    // auction: the best offer is outbid, and refunded when bids are in SOL. tokens are taken back
+    // with withdrawBid
+    const isBid = !!opts?.bid
+    const isSplBid = isBid && !trade.auctionMint.equals(PublicKey.default)
+    const hasPreviousBid = isBid && !isSplBid && !trade.bestOffer.equals(PublicKey.default)
+    const previousOffer = hasPreviousBid ? await this.getOffer(trade.bestOffer) : null
+    const bidVault = this.PDA.getBidVault(offer.address);
+
+    const builder = this.program.methods
+      .createOffer(trade.offerCount, new anchor.BN(opts?.bid || 0))
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: offer.address,
+        userProfile: userProfile.address,
+        userOffer: userOffer.address,
+        user,
+        previousOffer: hasPreviousBid ? trade.bestOffer : null,
+        previousUser: hasPreviousBid ? previousOffer.user : null,
+        bidMint: isSplBid ? trade.auctionMint : null,
+        userTokenAccount: isSplBid ? opts.userTokenAccount : null,
+        bidVault: isSplBid ? bidVault.address : null,
+        tokenProgram: isSplBid ? anchor.utils.token.TOKEN_PROGRAM_ID : null,
       })
     return {
       pda: {
-        trade
+        offer,
+        userProfile,
+        userOffer,
+        bidVault,
       },
       builder,
     }
   }
+  async startAuction(
+    tradeAddress: PublicKey,
+    opts: {
+      endTime: number,
+      reservePrice?: number | anchor.BN,
+      minIncrement?: number | anchor.BN,
+      auctionMint?: PublicKey,
+    }
+  ) {
+    return this.program.methods
+      .startAuction(
+        opts.auctionMint || PublicKey.default,
+        new anchor.BN(opts.reservePrice || 0),
+        new anchor.BN(opts.minIncrement || 0),
+        new anchor.BN(opts.endTime),
+      )
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+      })
+  }
+  async finalizeAuction(
+    tradeAddress: PublicKey,
+    opts?: {
+      // needed when the auction is not in SOL
+      makerTokenAccount?: PublicKey,
+    }
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const hasWinner = !trade.bestOffer.equals(PublicKey.default)
+    const isSplBid = hasWinner && !trade.auctionMint.equals(PublicKey.default)
+    const bestOffer = hasWinner ? await this.getOffer(trade.bestOffer) : null
+
+    return this.program.methods
+      .finalizeAuction()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        maker: trade.user,
+        makerProfile: this.PDA.getUserProfile(trade.user).address,
+        bestOffer: hasWinner ? trade.bestOffer : null,
+        winner: hasWinner ? bestOffer.user : null,
+        winnerProfile: hasWinner ? this.PDA.getUserProfile(bestOffer.user).address : null,
+        bidVault: isSplBid ? this.PDA.getBidVault(trade.bestOffer).address : null,
+        makerTokenAccount: isSplBid ? opts.makerTokenAccount : null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        winnerWsolAccount: hasWinner ? await this.getSolPayoutAccount(bestOffer.user) : null,
+        makerWsolAccount: await this.getSolPayoutAccount(trade.user),
+      })
+      .remainingAccounts([
+        ...(await this.getLotRemainingAccounts(tradeAddress)),
+        ...(!hasWinner || isSplBid ? [] : await this.getRoyaltyRemainingAccounts(tradeAddress)),
+      ])
+  }
+  async startDutchAuction(
+    tradeAddress: PublicKey,
+    opts: {
+      startPrice: number | anchor.BN,
+      endPrice: number | anchor.BN,
+      startTime: number,
+      endTime: number,
+      decay?: 'linear' | 'stepwise',
+      stepInterval?: number,
+      priceMint?: PublicKey,
+    }
+  ) {
+    return this.program.methods
+      .startDutchAuction(
+        opts.priceMint || PublicKey.default,
+        new anchor.BN(opts.startPrice),
+        new anchor.BN(opts.endPrice),
+        new anchor.BN(opts.startTime),
+        new anchor.BN(opts.endTime),
+        DUTCH_AUCTION_DECAY[opts.decay || 'linear'],
+        new anchor.BN(opts.stepInterval || 0),
+      )
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+      })
+  }
+  async buyDutchAuction(
+    tradeAddress: PublicKey,
+    maxPrice: number | anchor.BN,
+    opts?: {
+      user?: PublicKey,
+      // needed when the price is not in SOL
+      userTokenAccount?: PublicKey,
+      makerTokenAccount?: PublicKey,
+    }
+  ) {
+    const user = opts?.user || this.program.provider.publicKey
+    const trade = await this.getTrade(tradeAddress)
+    const isSplPrice = !trade.auctionMint.equals(PublicKey.default)
+    return this.program.methods
+      .buyDutchAuction(new anchor.BN(maxPrice))
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        maker: trade.user,
+        makerProfile: this.PDA.getUserProfile(trade.user).address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        user,
+        userTokenAccount: isSplPrice ? opts.userTokenAccount : null,
+        makerTokenAccount: isSplPrice ? opts.makerTokenAccount : null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        userWsolAccount: await this.getSolPayoutAccount(user),
+        makerWsolAccount: await this.getSolPayoutAccount(trade.user),
+      })
+      .remainingAccounts([
+        ...(await this.getLotRemainingAccounts(tradeAddress)),
+        ...(isSplPrice ? [] : await this.getRoyaltyRemainingAccounts(tradeAddress)),
+      ])
+  }
+  async listFixedPrice(
+    tradeAddress: PublicKey,
+    price: number | anchor.BN,
+    priceMint?: PublicKey,
+  ) {
+    return this.program.methods
+      .listFixedPrice(priceMint || PublicKey.default, new anchor.BN(price))
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+      })
+  }
+  async buy(
+    tradeAddress: PublicKey,
+    opts?: {
+      user?: PublicKey,
+      // needed when the price is not in SOL
+      userTokenAccount?: PublicKey,
+      makerTokenAccount?: PublicKey,
+    }
+  ) {
+    const user = opts?.user || this.program.provider.publicKey
+    const trade = await this.getTrade(tradeAddress)
+    const isSplPrice = !trade.auctionMint.equals(PublicKey.default)
+    return this.program.methods
+      .buy()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        maker: trade.user,
+        makerProfile: this.PDA.getUserProfile(trade.user).address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        user,
+        userTokenAccount: isSplPrice ? opts.userTokenAccount : null,
+        makerTokenAccount: isSplPrice ? opts.makerTokenAccount : null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        userWsolAccount: await this.getSolPayoutAccount(user),
+        makerWsolAccount: await this.getSolPayoutAccount(trade.user),
+      })
+      .remainingAccounts([
+        ...(await this.getLotRemainingAccounts(tradeAddress)),
+        ...(isSplPrice ? [] : await this.getRoyaltyRemainingAccounts(tradeAddress)),
+      ])
+  }
+  async setAutoAccept(
+    tradeAddress: PublicKey,
+    rule: keyof typeof AUTO_ACCEPT_RULE,
+    opts?: {
+      mint?: PublicKey,
+      amount?: number | anchor.BN,
+    }
+  ) {
+    return this.program.methods
+      .setAutoAccept(
+        AUTO_ACCEPT_RULE[rule],
+        opts?.mint || PublicKey.default,
+        new anchor.BN(opts?.amount || 0),
+      )
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+      })
+  }
+  async tryAutoAccept(tradeAddress: PublicKey, offerAddress: PublicKey) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = await this.getOffer(offerAddress)
+    return this.program.methods
+      .tryAutoAccept()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: offerAddress,
+        maker: trade.user,
+        taker: offer.user,
+        makerProfile: this.PDA.getUserProfile(trade.user).address,
+        takerProfile: this.PDA.getUserProfile(offer.user).address,
+        makerWsolAccount: await this.getSolPayoutAccount(trade.user),
+        takerWsolAccount: await this.getSolPayoutAccount(offer.user),
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        ...(await this.getLotRemainingAccounts(offerAddress)),
+        ...(await this.getLotRemainingAccounts(tradeAddress)),
+        ...(await this.getRoyaltyRemainingAccounts(tradeAddress)),
+        ...(await this.getRoyaltyRemainingAccounts(offerAddress)),
+      ])
+  }
+  async createCollectionOffer(
+    collection: PublicKey,
+    price: number | anchor.BN,
+    quantity: number,
+    user?: PublicKey,
+  ) {
+    user = user || this.program.provider.publicKey
+    const profile = await this.getUserProfile(user)
+    const collectionOffer = this.PDA.getCollectionOffer(user, profile?.collectionOfferCount || 0)
+    const builder = this.program.methods
+      .createCollectionOffer(collection, new anchor.BN(price), quantity)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        collectionOffer: collectionOffer.address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        user,
+      })
+    return {
+      pda: {
+        collectionOffer,
+      },
+      builder,
+    }
+  }
+  async fillCollectionOffer(
+    collectionOfferAddress: PublicKey,
+    nft: {
+      mint: PublicKey,
+      metadata: PublicKey,
+      tokenAccount: PublicKey,
+    },
+    user?: PublicKey,
+  ) {
+    user = user || this.program.provider.publicKey
+    const collectionOffer = await this.program.account.collectionOffer.fetch(collectionOfferAddress)
+    const metadata = await Metadata.fromAccountAddress(this.program.provider.connection, nft.metadata)
+    return this.program.methods
+      .fillCollectionOffer()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        collectionOffer: collectionOfferAddress,
+        mint: nft.mint,
+        metadata: nft.metadata,
+        userTokenAccount: nft.tokenAccount,
+        buyer: collectionOffer.user,
+        buyerTokenAccount: anchor.utils.token.associatedAddress({ mint: nft.mint, owner: collectionOffer.user }),
+        buyerProfile: this.PDA.getUserProfile(collectionOffer.user).address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        user,
+        userWsolAccount: await this.getSolPayoutAccount(user),
+      })
+      .remainingAccounts((metadata.data.creators || [])
+        .filter(creator => creator.share > 0)
+        .map(creator => ({ pubkey: creator.address, isWritable: true, isSigner: false })))
+  }
+  // every asset item of the trade or offer, ordered by index
+  async getLotRemainingAccounts(tradeOrOfferAddress: PublicKey) {
+    const lot = (await this.getTradeOrOfferAssets(tradeOrOfferAddress))
+      .sort((a, b) => a.account.index - b.account.index)
+    return lot.map(asset => ({
+      pubkey: asset.publicKey,
+      isWritable: true,
+      isSigner: false,
+    }))
+  }
+  // outbid offers of an auction in tokens take their bid back
+  async withdrawBid(
+    tradeAddress: PublicKey,
+    offerAddress: PublicKey,
+    userTokenAccount: PublicKey,
+  ) {
+    const offer = await this.getOffer(offerAddress)
+    return this.program.methods
+      .withdrawBid()
+      .accounts({
+        trade: tradeAddress,
+        offer: offerAddress,
+        bidVault: this.PDA.getBidVault(offerAddress).address,
+        userTokenAccount,
+        user: offer.user,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+  }
+  // nft and core asset items of the trade or offer, they share the type indexes
+  async getNftAssetItems(tradeOrOfferAddress: PublicKey) {
+    const filters = [
+      { memcmp: { offset: 8 + 4 + 4, bytes: tradeOrOfferAddress.toBase58() } }
+    ]
+    return [
+      ...(await this.program.account.assetItemNft.all(filters)),
+      ...(await this.program.account.assetItemCore.all(filters)),
+    ]
+      .sort((a, b) => a.account.typeIndex - b.account.typeIndex)
+  }
+  // creators paid with royalties, for each nft then each of its creators
+  async getRoyaltyRemainingAccounts(tradeOrOfferAddress: PublicKey) {
+    const nfts = (await this.getNftAssetItems(tradeOrOfferAddress))
+      .sort((a, b) => a.account.index - b.account.index)
+    return nfts.flatMap(nft => nft.account.creators.map(creator => ({
+      pubkey: creator.address,
+      isWritable: true,
+      isSigner: false,
+    })))
+  }
+  getUserProfile = (user: PublicKey) => this.program.account.userProfile.fetchNullable(this.PDA.getUserProfile(user).address)
+  // wrapped SOL account settlements pay the user to, null when it takes native SOL
+  async getSolPayoutAccount(user: PublicKey) {
+    const profile = await this.getUserProfile(user)
+    return profile?.wsolPayout ? getAssociatedTokenAddressSync(NATIVE_MINT, user, true) : null
+  }
+  // wrapped = true pays settlements to the wrapped SOL account of the user, created here if needed
+  async setSolPayout(wrapped: boolean, user?: PublicKey) {
+    user = user || this.program.provider.publicKey
+    return this.program.methods
+      .setSolPayout(wrapped)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        user,
+      })
+      .preInstructions(wrapped ? [
+        createAssociatedTokenAccountIdempotentInstruction(user, getAssociatedTokenAddressSync(NATIVE_MINT, user, true), user, NATIVE_MINT),
+      ] : [])
+  }
+  async getUserTrades(user: PublicKey) {
+    const profile = await this.getUserProfile(user)
+    const addresses = new Array(profile?.tradeCount || 0).fill(0).map((_, i) => this.PDA.getUserTrade(user, i).address)
+    const userTrades = await this.program.account.userTrade.fetchMultiple(addresses)
+    return userTrades.filter(e => !!e).map(e => e.trade)
+  }
+  async getUserOffers(user: PublicKey) {
+    const profile = await this.getUserProfile(user)
+    const addresses = new Array(profile?.offerCount || 0).fill(0).map((_, i) => this.PDA.getUserOffer(user, i).address)
+    const userOffers = await this.program.account.userOffer.fetchMultiple(addresses)
+    return userOffers.filter(e => !!e).map(e => ({ trade: e.trade, offer: e.offer }))
+  }
   getAuthority = () => this.program.account.authority.fetch(this.PDA.getAuthority().address)
   getTrade = (address: PublicKey) => this.program.account.trade.fetch(address)
   getOffer = (address: PublicKey) => this.program.account.offer.fetch(address)
@@ -67,13 +537,136 @@ export class SolTradeSDK {
       return 1
     } else if (asset_type == 'spl') {
       return 2
+    } else if (asset_type == 'cnft') {
+      return 3
+    } else if (asset_type == 'nft') {
+      return 4
+    } else if (asset_type == 'core') {
+      return 5
+    } else if (asset_type == 'stake') {
+      return 6
+    } else if (asset_type == 'authority') {
+      return 7
     }
     throw new Error('Invalid asset type')
   }
+  // options of add_asset_item for the asset
+  serializeAssetOptions(item: AddAssetsToTradeAssets) {
+    let serialized: Uint8Array|undefined
+    if (item.type == 'sol') {
+      const data = new OptionAssetItemSOL({
+        amount: new anchor.BN(item.amount),
+      })
+      serialized = borsh.serialize(OptionAssetItemSOLSchema, data)
+      // console.log('serialized', serialized)
+      // const deserialized = borsh.deserialize(OptionAssetItemSOLSchema, OptionAssetItemSOL, Buffer.from(serialized))
+      // console.log('deserialized', typeof deserialized, deserialized)
+    } else if (item.type == 'spl') {
+      const data = new OptionAssetItemSPL({
+        token: item.token,
+        mint: item.mint,
+        amount: new anchor.BN(item.amount || 0),
+      })
+      serialized = borsh.serialize(OptionAssetItemSPLSchema, data)
+      // console.log('serialized', serialized)
+      // const deserialized = borsh.deserialize(OptionAssetItemSPLSchema, OptionAssetItemSPL, Buffer.from(serialized))
+      // console.log('deserialized', typeof deserialized, deserialized)
+    } else if (item.type == 'nft') {
+      const data = new OptionAssetItemNFT({
+        token: item.token,
+      })
+      serialized = borsh.serialize(OptionAssetItemNFTSchema, data)
+    }
+
+    // check
+    if (!serialized) {
+      throw new Error('Invalid asset type')
+    }
+    return serialized
+  }
+  // every asset in one instruction, up to 10. the remaining accounts can go in an address lookup
+  // table, lookupAddresses are the ones shared by every batch
+  async addAssetItems(
+    tradeAddress: PublicKey,
+    assets: AddAssetsToTradeAssets[],
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const from_type = offer ? 2 : 1
+    const tradeOrOffer = offer ? offerAddress : tradeAddress
+
+    const assetTypeIndex = {
+      sol: !offer ? trade.assetSolCount : offer.assetSolCount,
+      spl: !offer ? trade.assetSplCount : offer.assetSplCount,
+      nft: !offer ? trade.assetNftCount : offer.assetNftCount,
+    }
+    const assetItems: PublicKey[] = []
+    const remainingAccounts: anchor.web3.AccountMeta[] = []
+    const account = (pubkey: PublicKey, isWritable = false) => ({ pubkey, isWritable, isSigner: false })
+    for (const item of assets) {
+      const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, item.type, assetTypeIndex[item.type]++).address
+      assetItems.push(assetItem)
+      remainingAccounts.push(account(assetItem, true))
+      if (item.type == 'spl') {
+        const tokenProgram = (await this.program.provider.connection.getAccountInfo(item.mint))?.owner
+        remainingAccounts.push(
+          account(item.mint),
+          account(item.token, true),
+          account(getAssociatedTokenAddressSync(item.mint, tradeOrOffer, true, tokenProgram), true),
+          account(tokenProgram),
+          account(this.PDA.getMintRegistryEntry(item.mint).address),
+        )
+      } else if (item.type == 'nft') {
+        remainingAccounts.push(
+          account(item.mint),
+          account(getMetadata(item.mint)),
+          account(item.token, true),
+          account(getAssociatedTokenAddressSync(item.mint, tradeOrOffer, true), true),
+          account(this.PDA.getMintRegistryEntry(item.mint).address),
+        )
+      }
+    }
+
+    const builder = this.program.methods
+      .addAssetItems(
+        from_type,
+        assets.map(item => ({
+          assetType: this.getAssetTypeCode(item.type),
+          options: Buffer.from(this.serializeAssetOptions(item)),
+        })),
+      )
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        trade: offer ? tradeAddress : null,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: assets.some(item => item.type == 'nft') ? anchor.utils.token.TOKEN_PROGRAM_ID : null,
+        user: user || this.program.provider.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+      ])
+
+    return {
+      builder,
+      assetItems,
+      lookupAddresses: [
+        this.PDA.getAuthority().address,
+        anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        anchor.utils.token.TOKEN_PROGRAM_ID,
+        anchor.web3.SystemProgram.programId,
+        ComputeBudgetProgram.programId,
+      ],
+    }
+  }
   async addAssetsItem(
     tradeAddress: PublicKey,
     assets: AddAssetsToTradeAssets[],
     offerAddress?: PublicKey,
+    user?: PublicKey,
   ) {
     const trade = await this.getTrade(tradeAddress)
 
@@ -98,34 +691,11 @@ export class SolTradeSDK {
     let assetTypeIndex = {
       sol: !offer ? trade.assetSolCount : offer.assetSolCount,
       spl: !offer ? trade.assetSplCount : offer.assetSplCount,
+      nft: !offer ? trade.assetNftCount : offer.assetNftCount,
     }
     for (const item of assets) {
       // opts
-      let serialized: Uint8Array|undefined
-      if (item.type == 'sol') {
-        const data = new OptionAssetItemSOL({
-          amount: new anchor.BN(item.amount),
-        })
-        serialized = borsh.serialize(OptionAssetItemSOLSchema, data)
-        // console.log('serialized', serialized)
-        // const deserialized = borsh.deserialize(OptionAssetItemSOLSchema, OptionAssetItemSOL, Buffer.from(serialized))
-        // console.log('deserialized', typeof deserialized, deserialized)
-      } else if (item.type == 'spl') {
-        const data = new OptionAssetItemSPL({
-          mint: item.mint.toBase58(),
-          token: item.token.toBase58(),
-        })
-        serialized = borsh.serialize(OptionAssetItemSPLSchema, data)
-        // console.log('serialized', serialized)
-        // const deserialized = borsh.deserialize(OptionAssetItemSPLSchema, OptionAssetItemSPL, Buffer.from(serialized))
-        // console.log('deserialized', typeof deserialized, deserialized)
-      }
-      // return
-
-      // check
-      if (!serialized) {
-        throw new Error('Invalid asset type')
-      }
+      const serialized = this.serializeAssetOptions(item)
 
       // check
       const assetItemAddres = this.PDA.getTradeAssetItem(
@@ -133,6 +703,16 @@ export class SolTradeSDK {
         item.type, assetTypeIndex[item.type]
       ).address
 
+      // spl tokens go in the vault of the trade or offer, with the token program of the mint.
+      // nfts go in the same way, with the token program
+      const tokenProgram = item.type == 'spl'
+        ? (await this.program.provider.connection.getAccountInfo(item.mint))?.owner
+        : item.type == 'nft' ? anchor.utils.token.TOKEN_PROGRAM_ID : undefined
+      const tradeOrOffer = from_type == 1 ? tradeAddress : offerAddress
+
+      // wrapped SOL goes through an account of the asset item, unwrapped on the spot
+      const wsolToken = item.type == 'sol' ? item.wsolToken : undefined
+
       // build
       const assetTypeCode = this.getAssetTypeCode(item.type)
       const builder = this.program.methods
@@ -144,12 +724,30 @@ export class SolTradeSDK {
           Buffer.from(serialized),
         )
         .accounts({
-          tradeOrOffer: from_type == 1 ? tradeAddress : offerAddress,
+          authority: this.PDA.getAuthority().address,
+          tradeOrOffer,
 
           // assetItemBase: null,
           assetItemSol: item.type == 'sol' ? assetItemAddres : null,
           assetItemSpl: item.type == 'spl' ? assetItemAddres : null,
+          assetItemNft: item.type == 'nft' ? assetItemAddres : null,
+          nftMint: item.type == 'nft' ? item.mint : null,
+          nftMetadata: item.type == 'nft' ? getMetadata(item.mint) : null,
+          splMint: item.type == 'spl' ? item.mint : wsolToken ? NATIVE_MINT : null,
+          splToken: item.type == 'spl' || item.type == 'nft' ? item.token : wsolToken || null,
+          splVault: item.type == 'spl' || item.type == 'nft'
+            ? getAssociatedTokenAddressSync(item.mint, tradeOrOffer, true, tokenProgram)
+            : null,
+          tokenProgram: tokenProgram || (wsolToken ? anchor.utils.token.TOKEN_PROGRAM_ID : null),
+          associatedTokenProgram: item.type == 'spl' || item.type == 'nft'
+            ? anchor.utils.token.ASSOCIATED_PROGRAM_ID
+            : null,
+          mintRegistryEntry: item.type == 'spl' || item.type == 'nft' ? this.PDA.getMintRegistryEntry(item.mint).address : null,
+          wsolUnwrap: wsolToken ? this.PDA.getWsolUnwrap(assetItemAddres).address : null,
+          trade: from_type == 2 ? tradeAddress : null,
+          user: user || this.program.provider.publicKey,
         })
+        .remainingAccounts(item.type == 'spl' ? item.extraAccounts || [] : [])
 
       // push
       builders.push(builder)
@@ -169,6 +767,669 @@ export class SolTradeSDK {
       endIndex: assetIndex - 1,
     }
   }
+  // programmable nfts go in escrow, in the token account of the trade or offer
+  async addPnftAssetItem(
+    tradeAddress: PublicKey,
+    item: { mint: PublicKey, token: PublicKey },
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const tradeOrOffer = offerAddress || tradeAddress
+    const assetIndex = !offer ? trade.assetCount : offer.assetCount
+    const assetTypeIndex = !offer ? trade.assetNftCount : offer.assetNftCount
+    const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, 'nft', assetTypeIndex).address
+    const escrowToken = getAssociatedTokenAddressSync(item.mint, tradeOrOffer, true)
+    const builder = this.program.methods
+      .addAssetItemPnft(offer ? 2 : 1, assetIndex, assetTypeIndex)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        assetItemNft: assetItem,
+        nftMint: item.mint,
+        nftMetadata: getMetadata(item.mint),
+        nftEdition: getMasterEdition(item.mint),
+        userToken: item.token,
+        userTokenRecord: getTokenRecord(item.mint, item.token),
+        escrowToken,
+        escrowTokenRecord: getTokenRecord(item.mint, escrowToken),
+        authorizationRulesProgram: null,
+        authorizationRules: null,
+        mintRegistryEntry: this.PDA.getMintRegistryEntry(item.mint).address,
+        trade: offer ? tradeAddress : null,
+        user: user || this.program.provider.publicKey,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+    return { builder, assetItem, escrowToken }
+  }
+  // moves a released nft from its escrow to the receiver, anyone can push it
+  async claimNft(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemNft.fetch(assetItemAddress)
+    return this.program.methods
+      .claimNft()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemNft: assetItemAddress,
+        nftMint: item.mint,
+        escrowToken: item.token,
+        receiver: item.receiver,
+        receiverToken: getAssociatedTokenAddressSync(item.mint, item.receiver, true),
+        user: opts?.user || this.program.provider.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      })
+  }
+  // moves a released programmable nft from its escrow to the receiver, anyone can push it
+  async claimPnft(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemNft.fetch(assetItemAddress)
+    const receiverToken = getAssociatedTokenAddressSync(item.mint, item.receiver, true)
+    return this.program.methods
+      .claimPnft()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemNft: assetItemAddress,
+        nftMint: item.mint,
+        nftMetadata: getMetadata(item.mint),
+        nftEdition: getMasterEdition(item.mint),
+        escrowToken: item.token,
+        escrowTokenRecord: getTokenRecord(item.mint, item.token),
+        receiver: item.receiver,
+        receiverToken,
+        receiverTokenRecord: getTokenRecord(item.mint, receiverToken),
+        authorizationRulesProgram: null,
+        authorizationRules: null,
+        user: opts?.user || this.program.provider.publicKey,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+  }
+  // compressed nfts go in escrow, the leaf is transferred to the trade or offer with its proof
+  async addCnftAssetItem(
+    tradeAddress: PublicKey,
+    item: { tree: PublicKey, leafIndex: number, nonce: number, dataHash: Buffer, creatorHash: Buffer },
+    proof: { root: number[], proof: anchor.web3.AccountMeta[] },
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const tradeOrOffer = offerAddress || tradeAddress
+    const assetIndex = !offer ? trade.assetCount : offer.assetCount
+    const assetTypeIndex = !offer ? trade.assetCnftCount : offer.assetCnftCount
+    const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, 'cnft', assetTypeIndex).address
+    const builder = this.program.methods
+      .addAssetItemCnft(
+        offer ? 2 : 1,
+        assetIndex,
+        assetTypeIndex,
+        proof.root,
+        Array.from(item.dataHash),
+        Array.from(item.creatorHash),
+        new anchor.BN(item.nonce),
+        item.leafIndex,
+      )
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        assetItemCnft: assetItem,
+        treeAuthority: getTreeAuthority(item.tree),
+        merkleTree: item.tree,
+        mintRegistryEntry: this.PDA.getMintRegistryEntry(getAssetId(item.tree, item.nonce)).address,
+        trade: offer ? tradeAddress : null,
+        user: user || this.program.provider.publicKey,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+      })
+      .remainingAccounts(proof.proof)
+    return { builder, assetItem }
+  }
+  // moves a released compressed nft from its escrow to the receiver, anyone can push it
+  async claimCnft(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    proof: { root: number[], proof: anchor.web3.AccountMeta[] },
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemCnft.fetch(assetItemAddress)
+    return this.program.methods
+      .claimCnft(proof.root)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemCnft: assetItemAddress,
+        treeAuthority: getTreeAuthority(item.tree),
+        merkleTree: item.tree,
+        receiver: item.receiver,
+        user: opts?.user || this.program.provider.publicKey,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+      })
+      .remainingAccounts(proof.proof)
+  }
+  // core assets go in escrow, the asset is transferred to the trade or offer
+  async addCoreAssetItem(
+    tradeAddress: PublicKey,
+    item: { asset: PublicKey, collection?: PublicKey },
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const tradeOrOffer = offerAddress || tradeAddress
+    const assetIndex = !offer ? trade.assetCount : offer.assetCount
+    // core assets share the type indexes of the nfts
+    const assetTypeIndex = !offer ? trade.assetNftCount : offer.assetNftCount
+    const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, 'core', assetTypeIndex).address
+    const builder = this.program.methods
+      .addAssetItemCore(offer ? 2 : 1, assetIndex, assetTypeIndex)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        assetItemCore: assetItem,
+        coreAsset: item.asset,
+        coreCollection: item.collection || null,
+        mintRegistryEntry: this.PDA.getMintRegistryEntry(item.asset).address,
+        trade: offer ? tradeAddress : null,
+        user: user || this.program.provider.publicKey,
+        mplCoreProgram: MPL_CORE_PROGRAM_ID,
+      })
+    return { builder, assetItem }
+  }
+  // moves a released core asset from its escrow to the receiver, anyone can push it
+  async claimCore(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemCore.fetch(assetItemAddress)
+    return this.program.methods
+      .claimCore()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemCore: assetItemAddress,
+        coreAsset: item.asset,
+        coreCollection: item.collection.equals(PublicKey.default) ? null : item.collection,
+        receiver: item.receiver,
+        user: opts?.user || this.program.provider.publicKey,
+        mplCoreProgram: MPL_CORE_PROGRAM_ID,
+      })
+  }
+  // stake accounts go in escrow, the trade or offer becomes their staker and withdrawer
+  async addStakeAssetItem(
+    tradeAddress: PublicKey,
+    stakeAccount: PublicKey,
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const tradeOrOffer = offerAddress || tradeAddress
+    const assetIndex = !offer ? trade.assetCount : offer.assetCount
+    const assetTypeIndex = !offer ? trade.assetStakeCount : offer.assetStakeCount
+    const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, 'stake', assetTypeIndex).address
+    const builder = this.program.methods
+      .addAssetItemStake(offer ? 2 : 1, assetIndex, assetTypeIndex)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        assetItemStake: assetItem,
+        stakeAccount,
+        trade: offer ? tradeAddress : null,
+        user: user || this.program.provider.publicKey,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        stakeProgram: StakeProgram.programId,
+      })
+    return { builder, assetItem }
+  }
+  // hands the authorities of a released stake account to the receiver, anyone can push it
+  async claimStake(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemStake.fetch(assetItemAddress)
+    return this.program.methods
+      .claimStake()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemStake: assetItemAddress,
+        stakeAccount: item.stake,
+        receiver: item.receiver,
+        user: opts?.user || this.program.provider.publicKey,
+        clock: SYSVAR_CLOCK_PUBKEY,
+        stakeProgram: StakeProgram.programId,
+      })
+  }
+  // mint, freeze or upgrade authorities go in escrow, the trade or offer holds them
+  async addAuthorityAssetItem(
+    tradeAddress: PublicKey,
+    item: { kind: keyof typeof AUTHORITY_KIND, target: PublicKey },
+    offerAddress?: PublicKey,
+    user?: PublicKey,
+  ) {
+    const trade = await this.getTrade(tradeAddress)
+    const offer = offerAddress ? await this.getOffer(offerAddress) : undefined
+    const tradeOrOffer = offerAddress || tradeAddress
+    const assetIndex = !offer ? trade.assetCount : offer.assetCount
+    const assetTypeIndex = !offer ? trade.assetAuthorityCount : offer.assetAuthorityCount
+    const assetItem = this.PDA.getTradeAssetItem(tradeOrOffer, 'authority', assetTypeIndex).address
+    const builder = this.program.methods
+      .addAssetItemAuthority(offer ? 2 : 1, assetIndex, assetTypeIndex, AUTHORITY_KIND[item.kind])
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        tradeOrOffer,
+        assetItemAuthority: assetItem,
+        trade: offer ? tradeAddress : null,
+        user: user || this.program.provider.publicKey,
+        ...(await this.getAuthorityTargetAccounts(item.kind, item.target)),
+      })
+    return { builder, assetItem }
+  }
+  // hands a released authority to the receiver, anyone can push it
+  async claimAuthority(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey },
+  ) {
+    const item = await this.program.account.assetItemAuthority.fetch(assetItemAddress)
+    const kind = Object.keys(AUTHORITY_KIND).find(k => AUTHORITY_KIND[k] == item.kind) as keyof typeof AUTHORITY_KIND
+    return this.program.methods
+      .claimAuthority()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemAuthority: assetItemAddress,
+        receiver: item.receiver,
+        user: opts?.user || this.program.provider.publicKey,
+        ...(await this.getAuthorityTargetAccounts(kind, item.target)),
+      })
+  }
+  // the mint with its token program, or the program with its program data and the loader
+  async getAuthorityTargetAccounts(kind: keyof typeof AUTHORITY_KIND, target: PublicKey) {
+    if (kind == 'programUpgrade') {
+      return {
+        target,
+        programData: PublicKey.findProgramAddressSync([target.toBuffer()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID)[0],
+        tokenProgram: null,
+        bpfLoaderUpgradeableProgram: BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+      }
+    }
+    return {
+      target,
+      programData: null,
+      tokenProgram: (await this.program.provider.connection.getAccountInfo(target)).owner,
+      bpfLoaderUpgradeableProgram: null,
+    }
+  }
+  // moves released spl tokens from the vault to the receiver, anyone can push it
+  async claimSpl(
+    tradeAddress: PublicKey,
+    assetItemAddress: PublicKey,
+    opts?: { offer?: PublicKey, user?: PublicKey, extraAccounts?: anchor.web3.AccountMeta[] },
+  ) {
+    const item = await this.program.account.assetItemSpl.fetch(assetItemAddress)
+    const user = opts?.user || this.program.provider.publicKey
+    const receiverToken = getAssociatedTokenAddressSync(item.mint, item.receiver, true, item.tokenProgram)
+    return this.program.methods
+      .claimSpl()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: tradeAddress,
+        offer: opts?.offer || null,
+        assetItemSpl: assetItemAddress,
+        splMint: item.mint,
+        splVault: item.vault,
+        receiverToken,
+        user,
+        tokenProgram: item.tokenProgram,
+      })
+      .preInstructions([
+        createAssociatedTokenAccountIdempotentInstruction(user, receiverToken, item.receiver, item.mint, item.tokenProgram),
+      ])
+      .remainingAccounts(opts?.extraAccounts || [])
+  }
+  // ring between three or more participants, the user one of them
+  async createRing(participants: PublicKey[], user?: PublicKey) {
+    user = user || this.program.provider.publicKey
+    const profile = await this.getUserProfile(user)
+    const ring = this.PDA.getRing(user, profile?.ringCount || 0)
+    const builder = this.program.methods
+      .createRing(participants)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        userProfile: this.PDA.getUserProfile(user).address,
+        ring: ring.address,
+        user,
+      })
+    return {
+      pda: {
+        ring,
+      },
+      builder,
+    }
+  }
+  // sol or spl asset item escrowed by the user for the recipient
+  async addRingAssetItem(
+    ringAddress: PublicKey,
+    recipient: PublicKey,
+    item: AddAssetsToTradeAssetSOL | AddAssetsToTradeAssetSPL,
+    user?: PublicKey,
+  ) {
+    user = user || this.program.provider.publicKey
+    const ring = await this.program.account.ring.fetch(ringAddress)
+    const ringAssetItem = this.PDA.getRingAssetItem(ringAddress, ring.assetCount)
+    const tokenProgram = item.type == 'spl'
+      ? (await this.program.provider.connection.getAccountInfo(item.mint))?.owner
+      : null
+    const builder = this.program.methods
+      .addRingAssetItem(this.getAssetTypeCode(item.type), recipient, new anchor.BN(item.amount || 0))
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        ring: ringAddress,
+        ringAssetItem: ringAssetItem.address,
+        splMint: item.type == 'spl' ? item.mint : null,
+        splToken: item.type == 'spl' ? item.token : null,
+        splVault: item.type == 'spl' ? getAssociatedTokenAddressSync(item.mint, ringAddress, true, tokenProgram) : null,
+        tokenProgram,
+        associatedTokenProgram: item.type == 'spl' ? anchor.utils.token.ASSOCIATED_PROGRAM_ID : null,
+        mintRegistryEntry: item.type == 'spl' ? this.PDA.getMintRegistryEntry(item.mint).address : null,
+        user,
+      })
+      .remainingAccounts(item.type == 'spl' ? item.extraAccounts || [] : [])
+    return {
+      pda: {
+        ringAssetItem,
+      },
+      builder,
+    }
+  }
+  async confirmRing(ringAddress: PublicKey, user?: PublicKey) {
+    return this.program.methods
+      .confirmRing()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        ring: ringAddress,
+        user: user || this.program.provider.publicKey,
+      })
+  }
+  async withdrawRing(ringAddress: PublicKey, user?: PublicKey) {
+    return this.program.methods
+      .withdrawRing()
+      .accounts({
+        ring: ringAddress,
+        user: user || this.program.provider.publicKey,
+      })
+  }
+  // moves an asset item of a settled or cancelled ring to its receiver, anyone can push it
+  async claimRingAssetItem(
+    ringAssetItemAddress: PublicKey,
+    opts?: { user?: PublicKey, extraAccounts?: anchor.web3.AccountMeta[] },
+  ) {
+    const item = await this.program.account.ringAssetItem.fetch(ringAssetItemAddress)
+    const ring = await this.program.account.ring.fetch(item.ring)
+    const user = opts?.user || this.program.provider.publicKey
+    const receiver = ring.settled ? item.recipient : item.user
+    const isSpl = item.assetType == this.getAssetTypeCode('spl')
+    const receiverToken = isSpl ? getAssociatedTokenAddressSync(item.mint, receiver, true, item.tokenProgram) : null
+    return this.program.methods
+      .claimRingAssetItem()
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        ring: item.ring,
+        ringAssetItem: ringAssetItemAddress,
+        receiver,
+        user,
+        splMint: isSpl ? item.mint : null,
+        splVault: isSpl ? item.vault : null,
+        receiverToken,
+        tokenProgram: isSpl ? item.tokenProgram : null,
+      })
+      .preInstructions(isSpl ? [
+        createAssociatedTokenAccountIdempotentInstruction(user, receiverToken, receiver, item.mint, item.tokenProgram),
+      ] : [])
+      .remainingAccounts(opts?.extraAccounts || [])
+  }
+  async getRingAssetItems(ringAddress: PublicKey) {
+    return (await this.program.account.ringAssetItem.all([
+      { memcmp: { offset: 8 + 4, bytes: ringAddress.toBase58() } }
+    ]))
+      .sort((a, b) => a.account.index - b.account.index)
+  }
+  // assets of both sides swapped in one instruction signed by both, nothing is escrowed. the
+  // tokens go to the associated token accounts of the other side, created by the taker if needed
+  async directSwap(maker: PublicKey, taker: PublicKey, assets: DirectSwapAsset[]) {
+    const connection = this.program.provider.connection
+    const account = (pubkey: PublicKey, isWritable = false) => ({ pubkey, isWritable, isSigner: false })
+    const remainingAccounts: anchor.web3.AccountMeta[] = []
+    const preInstructions: anchor.web3.TransactionInstruction[] = [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+    ]
+    // creators of the nfts given by each side
+    const creators = { maker: [] as anchor.web3.AccountMeta[], taker: [] as anchor.web3.AccountMeta[] }
+    for (const { from, item } of assets) {
+      if (item.type == 'sol') {
+        continue
+      }
+      const to = from == 'maker' ? taker : maker
+      const tokenProgram = (await connection.getAccountInfo(item.mint))?.owner
+      const toToken = getAssociatedTokenAddressSync(item.mint, to, true, tokenProgram)
+      preInstructions.push(createAssociatedTokenAccountIdempotentInstruction(taker, toToken, to, item.mint, tokenProgram))
+      if (item.type == 'spl') {
+        remainingAccounts.push(
+          account(item.mint),
+          account(item.token, true),
+          account(toToken, true),
+          account(tokenProgram),
+          account(this.PDA.getMintRegistryEntry(item.mint).address),
+        )
+      } else {
+        const metadata = getMetadata(item.mint)
+        remainingAccounts.push(
+          account(item.mint),
+          account(metadata),
+          account(item.token, true),
+          account(toToken, true),
+          account(tokenProgram),
+          account(this.PDA.getMintRegistryEntry(item.mint).address),
+        )
+        const data = await Metadata.fromAccountAddress(connection, metadata)
+        creators[from].push(...(data.data.creators || [])
+          .filter(creator => creator.share > 0)
+          .map(creator => account(creator.address, true)))
+      }
+    }
+    // the lamports of a side pay the royalties of the nfts of the other side
+    const givesSol = (from: DirectSwapAsset['from']) => assets.some(asset => asset.from == from && asset.item.type == 'sol')
+    if (givesSol('maker')) {
+      remainingAccounts.push(...creators.taker)
+    }
+    if (givesSol('taker')) {
+      remainingAccounts.push(...creators.maker)
+    }
+
+    return this.program.methods
+      .directSwap(assets.map(({ from, item }) => ({
+        assetType: this.getAssetTypeCode(item.type),
+        fromMaker: from == 'maker',
+        amount: new anchor.BN(item.type == 'nft' ? 0 : item.amount || 0),
+      })))
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        maker,
+        taker,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions(preInstructions)
+  }
+  // the maker signs first, the transaction is paid by the taker and passed to it serialized. it
+  // must be completed before its blockhash expires
+  async signDirectSwap(maker: Keypair, taker: PublicKey, assets: DirectSwapAsset[]) {
+    const transaction = await (await this.directSwap(maker.publicKey, taker, assets)).transaction()
+    transaction.feePayer = taker
+    transaction.recentBlockhash = (await this.program.provider.connection.getLatestBlockhash()).blockhash
+    transaction.partialSign(maker)
+    return transaction.serialize({ requireAllSignatures: false }).toString('base64')
+  }
+  // what the taker is asked to sign: the transaction must hold only the swap and the creation of
+  // token accounts, and be signed by the maker
+  readDirectSwap(serialized: string) {
+    const transaction = Transaction.from(Buffer.from(serialized, 'base64'))
+    const allowed = [ComputeBudgetProgram.programId, anchor.utils.token.ASSOCIATED_PROGRAM_ID]
+    const swaps = transaction.instructions.filter(ix => !allowed.some(programId => programId.equals(ix.programId)))
+    if (swaps.length != 1 || !swaps[0].programId.equals(this.program.programId)) {
+      throw new Error('Transaction is not a direct swap')
+    }
+    const decoded = this.program.coder.instruction.decode(swaps[0].data)
+    if (decoded?.name != 'directSwap') {
+      throw new Error('Transaction is not a direct swap')
+    }
+    const maker = swaps[0].keys[1].pubkey
+    const taker = swaps[0].keys[2].pubkey
+    const makerSignature = transaction.signatures.find(signature => signature.publicKey.equals(maker))
+    if (!makerSignature?.signature || !transaction.feePayer?.equals(taker) || !transaction.verifySignatures(false)) {
+      throw new Error('Transaction is not signed by the maker')
+    }
+    return {
+      transaction,
+      maker,
+      taker,
+      assets: (decoded.data as { assets: { assetType: number, fromMaker: boolean, amount: anchor.BN }[] }).assets,
+      remainingAccounts: swaps[0].keys.slice(4),
+    }
+  }
+  // the taker signs and sends the swap
+  async completeDirectSwap(serialized: string, taker: Keypair) {
+    const { transaction } = this.readDirectSwap(serialized)
+    transaction.partialSign(taker)
+    const connection = this.program.provider.connection
+    const signature = await connection.sendRawTransaction(transaction.serialize())
+    await connection.confirmTransaction({ signature, ...(await connection.getLatestBlockhash()) })
+    return signature
+  }
+  // lets the program pull amount tokens of the token account for signed offers of its owner
+  async approveSignedOfferDelegate(token: PublicKey, amount: number | anchor.BN, owner?: PublicKey) {
+    const tokenProgram = (await this.program.provider.connection.getAccountInfo(token))?.owner
+    return createApproveInstruction(
+      token,
+      this.PDA.getSignedOfferDelegate().address,
+      owner || this.program.provider.publicKey,
+      BigInt(amount.toString()),
+      [],
+      tokenProgram,
+    )
+  }
+  // what the taker signs
+  getSignedOfferMessage(offer: SignedOffer) {
+    return Buffer.concat([SIGNED_OFFER_DOMAIN, this.program.coder.types.encode('SignedOffer', offer)])
+  }
+  // the taker signs an offer for the trade off-chain, the offer and the signature go to the maker.
+  // the tokens are pulled at fill time, approveSignedOfferDelegate must cover them
+  signOffer(
+    tradeAddress: PublicKey,
+    assets: { mint: PublicKey, amount: number | anchor.BN }[],
+    expiry: number,
+    nonce: number | anchor.BN,
+    taker: Keypair,
+  ) {
+    const offer: SignedOffer = {
+      program: this.program.programId,
+      trade: tradeAddress,
+      taker: taker.publicKey,
+      assets: assets.map(asset => ({ mint: asset.mint, amount: new anchor.BN(asset.amount) })),
+      expiry: new anchor.BN(expiry),
+      nonce: new anchor.BN(nonce),
+    }
+    // the ed25519 instruction holds the signature at the offset of its header
+    const data = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: taker.secretKey,
+      message: this.getSignedOfferMessage(offer),
+    }).data
+    const signatureOffset = data.readUInt16LE(2)
+    return {
+      offer,
+      signature: data.subarray(signatureOffset, signatureOffset + 64),
+    }
+  }
+  // the maker settles its trade with the offer, the ed25519 instruction goes right before. the
+  // tokens are pulled from the associated token accounts of the taker
+  async fillSignedOffer(offer: SignedOffer, signature: Uint8Array, maker?: PublicKey) {
+    maker = maker || this.program.provider.publicKey
+    const connection = this.program.provider.connection
+    const account = (pubkey: PublicKey, isWritable = false) => ({ pubkey, isWritable, isSigner: false })
+    const remainingAccounts = await this.getLotRemainingAccounts(offer.trade)
+    const preInstructions: anchor.web3.TransactionInstruction[] = []
+    for (const asset of offer.assets) {
+      const tokenProgram = (await connection.getAccountInfo(asset.mint))?.owner
+      const makerToken = getAssociatedTokenAddressSync(asset.mint, maker, true, tokenProgram)
+      preInstructions.push(createAssociatedTokenAccountIdempotentInstruction(maker, makerToken, maker, asset.mint, tokenProgram))
+      remainingAccounts.push(
+        account(asset.mint),
+        account(getAssociatedTokenAddressSync(asset.mint, offer.taker, true, tokenProgram), true),
+        account(makerToken, true),
+        account(tokenProgram),
+        account(this.PDA.getMintRegistryEntry(asset.mint).address),
+      )
+    }
+    preInstructions.push(Ed25519Program.createInstructionWithPublicKey({
+      publicKey: offer.taker.toBytes(),
+      message: this.getSignedOfferMessage(offer),
+      signature,
+    }))
+
+    return this.program.methods
+      .fillSignedOffer(offer)
+      .accounts({
+        authority: this.PDA.getAuthority().address,
+        trade: offer.trade,
+        makerProfile: this.PDA.getUserProfile(maker).address,
+        takerProfile: this.PDA.getUserProfile(offer.taker).address,
+        signedOfferNonce: this.PDA.getSignedOfferNonce(offer.taker, offer.nonce).address,
+        delegate: this.PDA.getSignedOfferDelegate().address,
+        maker,
+        taker: offer.taker,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        takerWsolAccount: await this.getSolPayoutAccount(offer.taker),
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions(preInstructions)
+  }
+  // the taker revokes its offers signed with the nonce
+  async cancelSignedOffer(nonce: number | anchor.BN, user?: PublicKey) {
+    user = user || this.program.provider.publicKey
+    return this.program.methods
+      .cancelSignedOffer(new anchor.BN(nonce))
+      .accounts({
+        signedOfferNonce: this.PDA.getSignedOfferNonce(user, nonce).address,
+        user,
+      })
+  }
   async getTradeOrOfferAssets(tradeOrOfferAddress: PublicKey) {
     const filters = [
       { memcmp: { offset: 8 + 4 + 4, bytes: tradeOrOfferAddress.toBase58() } }
@@ -176,6 +1437,11 @@ export class SolTradeSDK {
     const items = [
       ...(await this.program.account.assetItemSol.all(filters)),
       ...(await this.program.account.assetItemSpl.all(filters)),
+      ...(await this.program.account.assetItemNft.all(filters)),
+      ...(await this.program.account.assetItemCnft.all(filters)),
+      ...(await this.program.account.assetItemCore.all(filters)),
+      ...(await this.program.account.assetItemStake.all(filters)),
+      ...(await this.program.account.assetItemAuthority.all(filters)),
     ]
     return items
   }
@@ -215,7 +1481,7 @@ export class SolTradeSDK {
   }
   private generatePDA = function (p: PublicKey) {
     const $this = ({
-      build(programId: PublicKey, data: (string|number|PublicKey)[], schemas?: Map<number, 'u8'|'u32'>) {
+      build(programId: PublicKey, data: (string|number|PublicKey|anchor.BN)[], schemas?: Map<number, 'u8'|'u32'|'u64'>) {
         const seeds: Array<Buffer | Uint8Array> = []
         let i = 0
 
@@ -231,6 +1497,10 @@ export class SolTradeSDK {
                 seeds.push(_index)
                 schemas_used.push(`u32`)
                 continue
+              } else if (schema === 'u64') {
+                seeds.push(new anchor.BN(item as number | anchor.BN).toArrayLike(Buffer, 'le', 8))
+                schemas_used.push(`u64`)
+                continue
               } else if (schema === 'u8') {
                 seeds.push(Buffer.from([item as number]))
                 schemas_used.push(`u8`)
@@ -265,7 +1535,7 @@ export class SolTradeSDK {
       getTrade(index: number) {
         return $this.build(p, ["trade", index], new Map([[1, 'u32']]))
       },
-      getTradeAssetItem(trade: PublicKey, type: 'sol' | 'spl', asset_type_index: number, offer?: PublicKey) {
+      getTradeAssetItem(trade: PublicKey, type: AssetType, asset_type_index: number, offer?: PublicKey) {
         return $this.build(p, [trade, "asset_item_" + type, asset_type_index], new Map([[2, 'u32']]))
       },
       getTradeOffer(trade: PublicKey, offer_index: number) {
@@ -273,6 +1543,49 @@ export class SolTradeSDK {
       },
       getAuthority() {
         return $this.build(p, ["authority", p])
+      },
+      getUserProfile(user: PublicKey) {
+        return $this.build(p, ["user_profile", user])
+      },
+      getUserTrade(user: PublicKey, nonce: number) {
+        return $this.build(p, ["user_trade", user, nonce], new Map([[2, 'u32']]))
+      },
+      getUserOffer(user: PublicKey, nonce: number) {
+        return $this.build(p, ["user_offer", user, nonce], new Map([[2, 'u32']]))
+      },
+      getWsolUnwrap(assetItemSol: PublicKey) {
+        return $this.build(p, [assetItemSol, "wsol_unwrap"])
+      },
+      getCounterOffer(offer: PublicKey) {
+        return $this.build(p, [offer, "counter_offer"])
+      },
+      getBidVault(offer: PublicKey) {
+        return $this.build(p, [offer, "bid_vault"])
+      },
+      getRing(user: PublicKey, nonce: number) {
+        return $this.build(p, ["ring", user, nonce], new Map([[2, 'u32']]))
+      },
+      getRingAssetItem(ring: PublicKey, index: number) {
+        return $this.build(p, [ring, "ring_asset_item", index], new Map([[2, 'u32']]))
+      },
+      getCollectionOffer(user: PublicKey, nonce: number) {
+        return $this.build(p, ["collection_offer", user, nonce], new Map([[2, 'u32']]))
+      },
+      getSignedOfferNonce(taker: PublicKey, nonce: number | anchor.BN) {
+        return $this.build(p, ["signed_offer_nonce", taker, nonce], new Map([[2, 'u64']]))
+      },
+      getSignedOfferDelegate() {
+        return $this.build(p, ["signed_offer_delegate"])
+      },
+      getMintRegistryEntry(mint: PublicKey) {
+        return $this.build(p, ["mint_registry", mint])
+      },
+      getProgramData() {
+        const res = PublicKey.findProgramAddressSync([p.toBuffer()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID)
+        return {
+          address: res[0],
+          nonce: res[1],
+        }
       }
     })
     return $this
@@ -14,12 +14,21 @@ export class OptionAssetItemSOL {
   }
 }
 export class OptionAssetItemSPL {
-  public mint: string;
-  public token: string;
+  public token: Uint8Array;
+  public mint: Uint8Array;
+  public amount: anchor.BN;
+
+  constructor(args: { token: PublicKey; mint: PublicKey; amount: anchor.BN }) {
+    this.token = args.token.toBytes();
+    this.mint = args.mint.toBytes();
+    this.amount = args.amount;
+  }
+}
+export class OptionAssetItemNFT {
+  public token: Uint8Array;
 
-  constructor(args: { mint: string; token: string }) {
-    this.mint = args.mint;
-    this.token = args.token;
+  constructor(args: { token: PublicKey }) {
+    this.token = args.token.toBytes();
   }
 }
 export const OptionAssetItemSOLSchema = new Map([
@@ -31,12 +40,16 @@ export const OptionAssetItemSPLSchema = new Map([
     {
       kind: "struct",
       fields: [
-        ["mint", "string"],
-        ["token", "string"],
+        ["token", [32]],
+        ["mint", [32]],
+        ["amount", "u64"],
       ],
     },
   ],
 ]);
+export const OptionAssetItemNFTSchema = new Map([
+  [OptionAssetItemNFT, { kind: "struct", fields: [["token", [32]]] }],
+]);
 
 // === SDK =================
 // export class AssetItemBase {
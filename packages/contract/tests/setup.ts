@@ -2,13 +2,96 @@ import path from 'path';
 import fs from 'fs';
 import * as borsh from 'borsh'
 
-import { PublicKey } from "@solana/web3.js";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, Transaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMint2Instruction,
+  createInitializeNonTransferableMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { createConsola, ConsolaReporter } from 'consola'
 import * as anchor from "@coral-xyz/anchor";
 import { Soltrade } from "../target/types/soltrade";
 
 import { SolTradeSDK } from './sdk';
 
+// HELPERS
+// ? new keypair funded from the local validator faucet
+export const fundedKeypair = async (sol = 10) => {
+  const keypair = Keypair.generate();
+  const connection = anchor.getProvider().connection;
+  const sig = await connection.requestAirdrop(keypair.publicKey, sol * LAMPORTS_PER_SOL);
+  await connection.confirmTransaction({ signature: sig, ...(await connection.getLatestBlockhash()) });
+  return keypair;
+}
+
+// ? new mint, without decimals, and its associated token account of the owner holding the amount.
+// ? token 2022 is used for the extensions, a transfer fee or a non transferable mint
+export const createToken = async (owner: PublicKey, amount = 0, opts?: {
+  transferFeeBasisPoints?: number,
+  nonTransferable?: boolean,
+}) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const connection = provider.connection;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  const extensions: ExtensionType[] = [];
+  if (opts?.transferFeeBasisPoints !== undefined) extensions.push(ExtensionType.TransferFeeConfig);
+  if (opts?.nonTransferable) extensions.push(ExtensionType.NonTransferable);
+  const programId = extensions.length ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID;
+
+  const mint = Keypair.generate();
+  const space = getMintLen(extensions);
+  const tx = new Transaction().add(SystemProgram.createAccount({
+    fromPubkey: payer.publicKey,
+    newAccountPubkey: mint.publicKey,
+    space,
+    lamports: await connection.getMinimumBalanceForRentExemption(space),
+    programId,
+  }));
+  if (opts?.transferFeeBasisPoints !== undefined) {
+    tx.add(createInitializeTransferFeeConfigInstruction(
+      mint.publicKey, payer.publicKey, payer.publicKey, opts.transferFeeBasisPoints, BigInt(1_000_000_000), programId,
+    ));
+  }
+  if (opts?.nonTransferable) {
+    tx.add(createInitializeNonTransferableMintInstruction(mint.publicKey, programId));
+  }
+  tx.add(createInitializeMint2Instruction(mint.publicKey, 0, payer.publicKey, null, programId));
+  await provider.sendAndConfirm(tx, [payer, mint]);
+
+  const tokenAccount = await getOrCreateAssociatedTokenAccount(
+    connection, payer, mint.publicKey, owner, false, undefined, undefined, programId,
+  );
+  if (amount > 0) {
+    await mintTo(connection, payer, mint.publicKey, tokenAccount.address, payer, amount, [], undefined, programId);
+  }
+
+  return {
+    mint: mint.publicKey,
+    tokenAccount: tokenAccount.address,
+    programId,
+  };
+}
+
+// ? expect the promise to fail with the given anchor error code, e.g. "ProgramPaused"
+export const assertAnchorError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+  } catch (e) {
+    const received = e?.error?.errorCode?.code;
+    if (received !== code) {
+      throw new Error(`expected error ${code}, received ${received ?? e}`);
+    }
+    return;
+  }
+  throw new Error(`expected error ${code}, but it succeeded`);
+}
 
 
 // SETUP
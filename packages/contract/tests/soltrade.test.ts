@@ -1,12 +1,17 @@
 import * as borsh from 'borsh'
 
 import { describe, it } from "mocha"
-// import { assert } from "chai"
+import { assert } from "chai"
 
 import * as anchor from "@coral-xyz/anchor";
-import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { Authorized, Keypair, LAMPORTS_PER_SOL, PublicKey, StakeProgram, SystemProgram, Transaction } from "@solana/web3.js";
+import { NATIVE_MINT, closeAccount, createAssociatedTokenAccountIdempotentInstruction, createMint, createSyncNativeInstruction, getAccount, getAssociatedTokenAddressSync, getMint, getOrCreateAssociatedTokenAccount, mintTo } from "@solana/spl-token";
 
-import { setup } from "./setup";
+import { assertAnchorError, createToken, fundedKeypair, setup } from "./setup";
+import { AUTHORITY_KIND, MINT_REGISTRY_MODE, MINT_REGISTRY_STATUS, toWantedAssets } from "./sdk";
+import { createCollection, createNft, createPnft } from "./metaplex";
+import { createTree } from "./bubblegum";
+import { createCoreAsset, createCoreCollection, getCoreOwner } from "./core";
 import { Soltrade } from '../target/types/soltrade';
 
 
@@ -15,6 +20,8 @@ describe("soltrade", async () => {
   let $global = {
     tradeAddress: null as PublicKey,
     offerAddress: null as PublicKey,
+    pauser: null as Keypair,
+    profileBefore: null as Awaited<ReturnType<typeof solTradeSdk.getUserProfile>>,
   }
 
   it("is initialized", async () => {
@@ -27,6 +34,8 @@ describe("soltrade", async () => {
         .initialize()
         .accounts({
           authority: authorityAddress,
+          program: program.programId,
+          programData: solTradeSdk.PDA.getProgramData().address,
         })
         .rpc();
 
@@ -35,7 +44,188 @@ describe("soltrade", async () => {
 
   });
 
+  it("only the upgrade authority can update the authority config", async () => {
+    const admin = anchor.getProvider().publicKey;
+    const stranger = Keypair.generate();
+
+    await assertAnchorError(
+      program.methods
+        .updateAuthorityConfig(stranger.publicKey, stranger.publicKey, stranger.publicKey)
+        .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
+          program: program.programId,
+          programData: solTradeSdk.PDA.getProgramData().address,
+          user: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "UpgradeAuthorityUnauthorized",
+    );
+
+    // the provider wallet deployed the program, so it is the upgrade authority
+    const sig = await program.methods
+      .updateAuthorityConfig(admin, admin, admin)
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        program: program.programId,
+        programData: solTradeSdk.PDA.getProgramData().address,
+      })
+      .rpc();
+    log.debug("sig", sig);
+
+    const authority = await solTradeSdk.getAuthority();
+    assert.ok(authority.user.equals(admin));
+    assert.ok(authority.pauser.equals(admin));
+    assert.ok(authority.feeManager.equals(admin));
+  });
+
+  it("assign pauser and fee manager roles", async () => {
+    const admin = anchor.getProvider().publicKey;
+    const pauser = Keypair.generate();
+    const feeManager = Keypair.generate();
+
+    // only the admin can assign roles
+    await assertAnchorError(
+      program.methods
+        .setRoles(pauser.publicKey, feeManager.publicKey)
+        .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
+          user: pauser.publicKey,
+        })
+        .signers([pauser])
+        .rpc(),
+      "AdminUnauthorized",
+    );
+
+    const sig = await program.methods
+      .setRoles(pauser.publicKey, feeManager.publicKey)
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        user: admin,
+      })
+      .rpc();
+    log.debug("sig", sig);
+
+    const authority = await solTradeSdk.getAuthority();
+    assert.ok(authority.user.equals(admin));
+    assert.ok(authority.pauser.equals(pauser.publicKey));
+    assert.ok(authority.feeManager.equals(feeManager.publicKey));
+
+    $global.pauser = pauser;
+  });
+
+  it("pause blocks state-changing instructions", async () => {
+    const pauser = $global.pauser;
+
+    // the admin is not the pauser anymore
+    await assertAnchorError(
+      program.methods
+        .setPaused(true)
+        .accounts({ authority: solTradeSdk.PDA.getAuthority().address })
+        .rpc(),
+      "PauserUnauthorized",
+    );
+
+    await program.methods
+      .setPaused(true)
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        user: pauser.publicKey,
+      })
+      .signers([pauser])
+      .rpc();
+    assert.isTrue((await solTradeSdk.getAuthority()).paused);
+
+    const trade = await solTradeSdk.createTrade();
+    await assertAnchorError(trade.builder.rpc(), "ProgramPaused");
+
+    await program.methods
+      .setPaused(false)
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        user: pauser.publicKey,
+      })
+      .signers([pauser])
+      .rpc();
+    assert.isFalse((await solTradeSdk.getAuthority()).paused);
+  });
+
+  it("two-step admin transfer", async () => {
+    const admin = anchor.getProvider().publicKey;
+    const newAdmin = Keypair.generate();
+    const stranger = Keypair.generate();
+
+    // propose
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ authority: solTradeSdk.PDA.getAuthority().address })
+      .rpc();
+    {
+      const authority = await solTradeSdk.getAuthority();
+      assert.ok(authority.user.equals(admin));
+      assert.ok(authority.pendingUser.equals(newAdmin.publicKey));
+    }
+
+    // only the proposed admin can accept
+    await assertAnchorError(
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
+          user: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "AdminInvalidPendingUser",
+    );
+
+    // accept
+    await program.methods
+      .acceptAdmin()
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        user: newAdmin.publicKey,
+      })
+      .signers([newAdmin])
+      .rpc();
+    {
+      const authority = await solTradeSdk.getAuthority();
+      assert.ok(authority.user.equals(newAdmin.publicKey));
+      assert.ok(authority.pendingUser.equals(PublicKey.default));
+    }
+
+    // the old admin lost its rights
+    await assertAnchorError(
+      program.methods
+        .proposeAdmin(admin)
+        .accounts({ authority: solTradeSdk.PDA.getAuthority().address })
+        .rpc(),
+      "AdminUnauthorized",
+    );
+
+    // hand it back, so the rest of the suite keeps running as admin
+    await program.methods
+      .proposeAdmin(admin)
+      .accounts({
+        authority: solTradeSdk.PDA.getAuthority().address,
+        user: newAdmin.publicKey,
+      })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accounts({ authority: solTradeSdk.PDA.getAuthority().address })
+      .rpc();
+    await program.methods
+      .setRoles(admin, admin)
+      .accounts({ authority: solTradeSdk.PDA.getAuthority().address })
+      .rpc();
+    assert.ok((await solTradeSdk.getAuthority()).user.equals(admin));
+  });
+
   it("create trade", async () => {
+    const user = anchor.getProvider().publicKey;
+    const profileBefore = await solTradeSdk.getUserProfile(user);
     const trade = await solTradeSdk.createTrade();
     log.debug("trade", trade.pda.trade.address.toBase58());
 
@@ -45,6 +235,14 @@ describe("soltrade", async () => {
       log.debug("sig", tx);
       $global.tradeAddress = trade.pda.trade.address;
     }
+
+    // the profile is created lazily and the trade can be enumerated from it
+    const profile = await solTradeSdk.getUserProfile(user);
+    assert.ok(profile.user.equals(user));
+    assert.equal(profile.tradeCount, (profileBefore?.tradeCount || 0) + 1);
+    const userTrades = await solTradeSdk.getUserTrades(user);
+    assert.ok(userTrades[userTrades.length - 1].equals($global.tradeAddress));
+    $global.profileBefore = profileBefore;
   });
 
   it("add assets to trade", async () => {
@@ -85,25 +283,94 @@ describe("soltrade", async () => {
     }
   })
 
+  it("mint registry rejects spl mints", async () => {
+    const authorityAddress = solTradeSdk.PDA.getAuthority().address;
+    const tokens = [
+      await createToken(anchor.getProvider().publicKey),
+      await createToken(anchor.getProvider().publicKey),
+      await createToken(anchor.getProvider().publicKey),
+    ];
+    const [allowedMint, deniedMint, unknownMint] = tokens.map(token => token.mint);
+
+    // separate trade, so the main flow keeps its assets
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const addSpl = async (mint: PublicKey) => {
+      const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+        { type: 'spl', mint, token: tokens.find(token => token.mint.equals(mint)).tokenAccount },
+      ]);
+      return assets.builders[0].rpc();
+    };
+
+    // registry entries
+    for (const [mint, status] of [
+      [allowedMint, MINT_REGISTRY_STATUS.allowed],
+      [deniedMint, MINT_REGISTRY_STATUS.denied],
+    ] as [PublicKey, number][]) {
+      await program.methods
+        .setMintRegistryEntry(mint, status)
+        .accounts({
+          authority: authorityAddress,
+          mintRegistryEntry: solTradeSdk.PDA.getMintRegistryEntry(mint).address,
+        })
+        .rpc();
+    }
+
+    // allowlist: only allowed mints
+    await program.methods
+      .setMintRegistryMode(MINT_REGISTRY_MODE.allowlist)
+      .accounts({ authority: authorityAddress })
+      .rpc();
+    await assertAnchorError(addSpl(unknownMint), "MintNotAllowlisted");
+    await assertAnchorError(addSpl(deniedMint), "MintNotAllowlisted");
+    await addSpl(allowedMint);
+
+    // denylist: everything except denied mints
+    await program.methods
+      .setMintRegistryMode(MINT_REGISTRY_MODE.denylist)
+      .accounts({ authority: authorityAddress })
+      .rpc();
+    await assertAnchorError(addSpl(deniedMint), "MintDenylisted");
+    await addSpl(unknownMint);
+
+    // removing the entry lifts the deny
+    await program.methods
+      .removeMintRegistryEntry()
+      .accounts({
+        authority: authorityAddress,
+        mintRegistryEntry: solTradeSdk.PDA.getMintRegistryEntry(deniedMint).address,
+      })
+      .rpc();
+    await addSpl(deniedMint);
+
+    // back to open for the rest of the suite
+    await program.methods
+      .setMintRegistryMode(MINT_REGISTRY_MODE.open)
+      .accounts({ authority: authorityAddress })
+      .rpc();
+    assert.equal((await solTradeSdk.getAuthority()).mintRegistryMode, MINT_REGISTRY_MODE.open);
+  });
+
   it("create offer", async () => {
-    const trade = await solTradeSdk.getTrade($global.tradeAddress);
+    const user = anchor.getProvider().publicKey;
 
     // create offer
-    const offer = solTradeSdk.PDA.getTradeOffer($global.tradeAddress, trade.offerCount);
-    $global.offerAddress = offer.address;
+    const offer = await solTradeSdk.createOffer($global.tradeAddress);
+    $global.offerAddress = offer.pda.offer.address;
     {
-      const sig = await solTradeSdk.program
-        .methods.createOffer(trade.offerCount)
-        .accounts({
-          trade: $global.tradeAddress,
-          offer: offer.address,
-        })
-        .rpc({
-          // skipPreflight: true,
-        })
+      const sig = await offer.builder.rpc({
+        // skipPreflight: true,
+      })
 
       log.debug("sig", sig);
     }
+
+    // the offer can be enumerated from the profile
+    const userOffers = await solTradeSdk.getUserOffers(user);
+    const last = userOffers[userOffers.length - 1];
+    assert.ok(last.trade.equals($global.tradeAddress));
+    assert.ok(last.offer.equals($global.offerAddress));
   });
 
   it("add assets to offer", async () => {
@@ -137,11 +404,14 @@ describe("soltrade", async () => {
   });
 
   it("accept offer", async () => {
+    const offer = await solTradeSdk.getOffer($global.offerAddress);
+
     // accept offer
     {
       const sig = await solTradeSdk.program.methods
-        .acceptOffer()
+        .acceptOffer(offer.round, offer.contentHash)
         .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
           trade: $global.tradeAddress,
           offer: $global.offerAddress,
         })
@@ -174,13 +444,23 @@ describe("soltrade", async () => {
           asset.account.typeIndex,
         )
         .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
           trade: $global.tradeAddress,
           offer: trade.acceptedOffer,
 
           assetItemSol: asset.account.assetType == 1 ? asset.publicKey : null,
           assetItemSpl: asset.account.assetType == 2 ? asset.publicKey : null,
+          assetItemNft: asset.account.assetType == 4 ? asset.publicKey : null,
+          assetItemCnft: asset.account.assetType == 3 ? asset.publicKey : null,
+          assetItemCore: asset.account.assetType == 5 ? asset.publicKey : null,
+          assetItemStake: asset.account.assetType == 6 ? asset.publicKey : null,
+          assetItemAuthority: asset.account.assetType == 7 ? asset.publicKey : null,
 
+          makerProfile: solTradeSdk.PDA.getUserProfile(trade.user).address,
+          takerProfile: solTradeSdk.PDA.getUserProfile(offer.user).address,
           userFrom: offer.user,
+          userWsolAccount: null,
+          tokenProgram: null,
         })
         .rpc({
           // skipPreflight: true,
@@ -212,13 +492,23 @@ describe("soltrade", async () => {
           asset.account.typeIndex,
         )
         .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
           trade: $global.tradeAddress,
           offer: trade.acceptedOffer,
 
           assetItemSol: asset.account.assetType == 1 ? asset.publicKey : null,
           assetItemSpl: asset.account.assetType == 2 ? asset.publicKey : null,
+          assetItemNft: asset.account.assetType == 4 ? asset.publicKey : null,
+          assetItemCnft: asset.account.assetType == 3 ? asset.publicKey : null,
+          assetItemCore: asset.account.assetType == 5 ? asset.publicKey : null,
+          assetItemStake: asset.account.assetType == 6 ? asset.publicKey : null,
+          assetItemAuthority: asset.account.assetType == 7 ? asset.publicKey : null,
 
+          makerProfile: solTradeSdk.PDA.getUserProfile(trade.user).address,
+          takerProfile: solTradeSdk.PDA.getUserProfile(offer.user).address,
           userFrom: offer.user,
+          userWsolAccount: null,
+          tokenProgram: null,
         })
         .rpc({
           // skipPreflight: true,
@@ -227,5 +517,1500 @@ describe("soltrade", async () => {
     }
   });
 
+  it("profile tracks the completed trade", async () => {
+    // maker and taker are the same wallet in this suite, so the trade is counted once
+    const user = anchor.getProvider().publicKey;
+    const profileBefore = $global.profileBefore;
+    const profile = await solTradeSdk.getUserProfile(user);
+    assert.equal(profile.tradesCompleted, (profileBefore?.tradesCompleted || 0) + 1);
+    assert.equal(
+      profile.solVolume.sub(profileBefore?.solVolume || new anchor.BN(0)).toNumber(),
+      LAMPORTS_PER_SOL * 1.5,
+    );
+  });
+
+  it("cancel trade and refund assets", async () => {
+    const user = anchor.getProvider().publicKey;
+    const connection = anchor.getProvider().connection;
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * 1 },
+    ]);
+    await assets.builders[0].rpc();
+
+    // nothing to refund before the cancel
+    const refund = () => program.methods
+      .refundAssetItem(1, 1)
+      .accounts({
+        trade: tradeAddress,
+        offer: null,
+        assetItemSol: assets.assetItems[0],
+        assetItemSpl: null,
+        assetItemNft: null,
+        assetItemCnft: null,
+        assetItemCore: null,
+        assetItemStake: null,
+        assetItemAuthority: null,
+        receiver: user,
+      })
+      .rpc();
+    await assertAnchorError(refund(), "RefundAssetItemNotRefundable");
+
+    // cancel
+    const profileBefore = await solTradeSdk.getUserProfile(user);
+    await program.methods
+      .cancelTrade()
+      .accounts({
+        trade: tradeAddress,
+        userProfile: solTradeSdk.PDA.getUserProfile(user).address,
+      })
+      .rpc();
+    assert.isTrue((await solTradeSdk.getTrade(tradeAddress)).cancelled);
+    assert.equal(
+      (await solTradeSdk.getUserProfile(user)).tradesCancelled,
+      profileBefore.tradesCancelled + 1,
+    );
+
+    // refund, the trade account keeps only its rent
+    await refund();
+    const tradeBalance = await connection.getBalance(tradeAddress);
+    assert.equal(tradeBalance, await connection.getMinimumBalanceForRentExemption(program.account.trade.size));
+    assert.isTrue((await program.account.assetItemSol.fetch(assets.assetItems[0])).exchanged);
+
+    // and only once
+    await assertAnchorError(refund(), "RefundAssetItemAlreadyReleased");
+  });
+
+  it("counter offer round trip", async () => {
+    const authorityAddress = solTradeSdk.PDA.getAuthority().address;
+    const maker = anchor.getProvider().publicKey;
+    const taker = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // trade
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    {
+      const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+        { type: 'sol', amount: LAMPORTS_PER_SOL * 1 },
+      ]);
+      await assets.builders[0].rpc();
+    }
+
+    // offer of the taker with two asset items
+    const offer = await solTradeSdk.createOffer(tradeAddress, { user: taker.publicKey });
+    await offer.builder.signers([taker]).rpc();
+    const offerAddress = offer.pda.offer.address;
+    const offerAssets = await solTradeSdk.addAssetsItem(
+      tradeAddress,
+      [
+        { type: 'sol', amount: LAMPORTS_PER_SOL * .5 },
+        { type: 'sol', amount: LAMPORTS_PER_SOL * .2 },
+      ],
+      offerAddress,
+      taker.publicKey,
+    );
+    for (const builder of offerAssets.builders) {
+      await builder.signers([taker]).rpc();
+    }
+    const droppedItem = offerAssets.assetItems[1];
+
+    // maker: drop the .2 SOL item and ask .3 SOL more
+    const counterOfferAddress = solTradeSdk.PDA.getCounterOffer(offerAddress).address;
+    await assertAnchorError(
+      program.methods
+        .counterOffer(new anchor.BN(LAMPORTS_PER_SOL * .3), [droppedItem], [])
+        .accounts({
+          authority: authorityAddress,
+          trade: tradeAddress,
+          offer: offerAddress,
+          counterOffer: counterOfferAddress,
+          user: taker.publicKey,
+        })
+        .signers([taker])
+        .rpc(),
+      "CounterOfferUnauthorized",
+    );
+    // an asset item can only be dropped once
+    await assertAnchorError(
+      program.methods
+        .counterOffer(new anchor.BN(LAMPORTS_PER_SOL * .3), [droppedItem, droppedItem], [])
+        .accounts({
+          authority: authorityAddress,
+          trade: tradeAddress,
+          offer: offerAddress,
+          counterOffer: counterOfferAddress,
+        })
+        .rpc(),
+      "CounterOfferDuplicateDropAssetItem",
+    );
+    await program.methods
+      .counterOffer(new anchor.BN(LAMPORTS_PER_SOL * .3), [droppedItem], [])
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+        counterOffer: counterOfferAddress,
+      })
+      .rpc();
+    {
+      const offer = await solTradeSdk.getOffer(offerAddress);
+      assert.equal(offer.round, 1);
+      assert.isTrue(offer.counterPending);
+    }
+
+    // maker: can't accept an old round, nor while the counter offer is pending
+    const acceptOffer = async (round: number) => program.methods
+      .acceptOffer(round, (await solTradeSdk.getOffer(offerAddress)).contentHash)
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+      })
+      .rpc();
+    await assertAnchorError(acceptOffer(0), "AcceptOfferInvalidRound");
+    await assertAnchorError(acceptOffer(1), "AcceptOfferCounterPending");
+
+    // taker: accept the counter offer of round 1
+    const takerBalanceBefore = await connection.getBalance(taker.publicKey);
+    const offerBefore = await solTradeSdk.getOffer(offerAddress);
+    const newItem = solTradeSdk.PDA.getTradeAssetItem(offerAddress, 'sol', offerBefore.assetSolCount).address;
+    const acceptCounterOffer = (round: number) => program.methods
+      .acceptCounterOffer(round)
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+        counterOffer: counterOfferAddress,
+        assetItemSol: newItem,
+        maker,
+        user: taker.publicKey,
+      })
+      .remainingAccounts([{ pubkey: droppedItem, isSigner: false, isWritable: true }])
+      .signers([taker])
+      .rpc();
+    await assertAnchorError(acceptCounterOffer(0), "CounterOfferInvalidRound");
+    await acceptCounterOffer(1);
+
+    {
+      const offer = await solTradeSdk.getOffer(offerAddress);
+      assert.equal(offer.round, 1);
+      assert.isFalse(offer.counterPending);
+      assert.equal(offer.assetCount, 3);
+      assert.equal(offer.exchangedCount, 1);
+      assert.isTrue((await program.account.assetItemSol.fetch(droppedItem)).exchanged);
+      assert.equal(
+        (await program.account.assetItemSol.fetch(newItem)).amount.toNumber(),
+        LAMPORTS_PER_SOL * .3,
+      );
+      assert.isNull(await connection.getAccountInfo(counterOfferAddress));
+
+      // .2 SOL back, .3 SOL in, plus the rent of the new asset item
+      const rent = await connection.getMinimumBalanceForRentExemption(program.account.assetItemSol.size);
+      assert.equal(
+        await connection.getBalance(taker.publicKey),
+        takerBalanceBefore + LAMPORTS_PER_SOL * .2 - LAMPORTS_PER_SOL * .3 - rent,
+      );
+    }
+
+    // maker: accept round 1
+    await acceptOffer(1);
+    assert.ok((await solTradeSdk.getTrade(tradeAddress)).acceptedOffer.equals(offerAddress));
+  });
+
+  it("counter offer requests assets the taker adds", async () => {
+    const authorityAddress = solTradeSdk.PDA.getAuthority().address;
+    const maker = anchor.getProvider().publicKey;
+    const taker = await fundedKeypair();
+    const token = await createToken(taker.publicKey, 10);
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const offer = await solTradeSdk.createOffer(tradeAddress, { user: taker.publicKey });
+    await offer.builder.signers([taker]).rpc();
+    const offerAddress = offer.pda.offer.address;
+    const addToOffer = async (amount: number) => {
+      const assets = await solTradeSdk.addAssetsItem(
+        tradeAddress,
+        [{ type: 'spl', mint: token.mint, token: token.tokenAccount, amount }],
+        offerAddress,
+        taker.publicKey,
+      );
+      return assets.builders[0].signers([taker]).rpc();
+    };
+    await addToOffer(4);
+
+    // maker: the offer must hold 10 tokens of the mint
+    const counterOfferAddress = solTradeSdk.PDA.getCounterOffer(offerAddress).address;
+    const counterOffer = (requested: Parameters<typeof toWantedAssets>[0]) => program.methods
+      .counterOffer(new anchor.BN(0), [], toWantedAssets(requested))
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+        counterOffer: counterOfferAddress,
+      })
+      .rpc();
+    await assertAnchorError(
+      counterOffer([{ type: 'spl', mint: PublicKey.default, amount: 10 }]),
+      "CounterOfferInvalidRequestedAsset",
+    );
+    await counterOffer([{ type: 'spl', mint: token.mint, amount: 10 }]);
+
+    // taker: the requested amount is checked against every asset item of the offer
+    const acceptCounterOffer = async () => program.methods
+      .acceptCounterOffer(1)
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+        counterOffer: counterOfferAddress,
+        assetItemSol: null,
+        maker,
+        user: taker.publicKey,
+      })
+      .remainingAccounts(await solTradeSdk.getLotRemainingAccounts(offerAddress))
+      .signers([taker])
+      .rpc();
+    await assertAnchorError(acceptCounterOffer(), "CounterOfferRequestedAssetsNotMet");
+    await addToOffer(6);
+    await acceptCounterOffer();
+
+    const settled = await solTradeSdk.getOffer(offerAddress);
+    assert.isFalse(settled.counterPending);
+    assert.equal(settled.assetCount, 2);
+  });
+
+  it("accept binds to the offer content hash", async () => {
+    const authorityAddress = solTradeSdk.PDA.getAuthority().address;
+    const taker = await fundedKeypair();
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const offer = await solTradeSdk.createOffer(tradeAddress, { user: taker.publicKey });
+    await offer.builder.signers([taker]).rpc();
+    const offerAddress = offer.pda.offer.address;
+    const addToOffer = async (amount: number) => {
+      const assets = await solTradeSdk.addAssetsItem(
+        tradeAddress,
+        [{ type: 'sol', amount }],
+        offerAddress,
+        taker.publicKey,
+      );
+      return assets.builders[0].signers([taker]).rpc();
+    };
+    const acceptOffer = (contentHash: number[]) => program.methods
+      .acceptOffer(0, contentHash)
+      .accounts({
+        authority: authorityAddress,
+        trade: tradeAddress,
+        offer: offerAddress,
+      })
+      .rpc();
+
+    // the maker reviews the offer
+    await addToOffer(LAMPORTS_PER_SOL * 1);
+    const reviewed = await solTradeSdk.getOffer(offerAddress);
+    assert.notDeepEqual(reviewed.contentHash, new Array(32).fill(0));
+
+    // the taker changes it before the accept lands
+    await addToOffer(1);
+    const changed = await solTradeSdk.getOffer(offerAddress);
+    assert.notDeepEqual(changed.contentHash, reviewed.contentHash);
+    await assertAnchorError(acceptOffer(reviewed.contentHash), "AcceptOfferContentMismatch");
+
+    // only the owner of the offer or trade adds to it, nobody else can move the content hash
+    const intruder = await solTradeSdk.addAssetsItem(tradeAddress, [{ type: 'sol', amount: 1 }], offerAddress);
+    await assertAnchorError(intruder.builders[0].rpc(), "AddAssetItemUnauthorized");
+    const intruderTrade = await solTradeSdk.addAssetsItem(
+      tradeAddress,
+      [{ type: 'sol', amount: 1 }],
+      undefined,
+      taker.publicKey,
+    );
+    await assertAnchorError(intruderTrade.builders[0].signers([taker]).rpc(), "AddAssetItemUnauthorized");
+
+    // accepting what is on chain works, and freezes the assets
+    await acceptOffer(changed.contentHash);
+    await assertAnchorError(addToOffer(1), "AddAssetItemFrozen");
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: 1 },
+    ]);
+    await assertAnchorError(assets.builders[0].rpc(), "AddAssetItemFrozen");
+  });
+
+  it("english auction with outbid refunds", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const alice = await fundedKeypair();
+    const bob = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // trade with 1 SOL as the lot
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * 1 },
+    ]);
+    await assets.builders[0].rpc();
+
+    // auction ends in a few seconds, reserve .5 SOL, steps of .1 SOL
+    const endTime = Math.floor(Date.now() / 1000) + 8;
+    await (await solTradeSdk.startAuction(tradeAddress, {
+      endTime,
+      reservePrice: LAMPORTS_PER_SOL * .5,
+      minIncrement: LAMPORTS_PER_SOL * .1,
+    })).rpc();
+    await assertAnchorError(
+      (await solTradeSdk.addAssetsItem(tradeAddress, [{ type: 'sol', amount: 1 }])).builders[0].rpc(),
+      "AddAssetItemFrozen",
+    );
+
+    // below the reserve price
+    const lowBid = await solTradeSdk.createOffer(tradeAddress, { user: alice.publicKey, bid: LAMPORTS_PER_SOL * .4 });
+    await assertAnchorError(lowBid.builder.signers([alice]).rpc(), "AuctionBidTooLow");
+
+    // alice bids .5 SOL, bob outbids her with .7 SOL
+    const aliceBid = await solTradeSdk.createOffer(tradeAddress, { user: alice.publicKey, bid: LAMPORTS_PER_SOL * .5 });
+    await aliceBid.builder.signers([alice]).rpc();
+    const tooLowBid = await solTradeSdk.createOffer(tradeAddress, { user: bob.publicKey, bid: LAMPORTS_PER_SOL * .55 });
+    await assertAnchorError(tooLowBid.builder.signers([bob]).rpc(), "AuctionBidTooLow");
+
+    const aliceBefore = await connection.getBalance(alice.publicKey);
+    const bobBid = await solTradeSdk.createOffer(tradeAddress, { user: bob.publicKey, bid: LAMPORTS_PER_SOL * .7 });
+    await bobBid.builder.signers([bob]).rpc();
+    assert.equal(await connection.getBalance(alice.publicKey), aliceBefore + LAMPORTS_PER_SOL * .5);
+    assert.isTrue((await solTradeSdk.getOffer(aliceBid.pda.offer.address)).bidRefunded);
+    const running = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(running.bestOffer.equals(bobBid.pda.offer.address));
+    assert.equal(running.bestBid.toNumber(), LAMPORTS_PER_SOL * .7);
+
+    // not before the end
+    await assertAnchorError((await solTradeSdk.finalizeAuction(tradeAddress)).rpc(), "AuctionNotEnded");
+    while (Math.floor(Date.now() / 1000) <= endTime + 1) {
+      await new Promise(resolve => setTimeout(resolve, 1000));
+    }
+
+    // the maker gets the bid, bob gets the lot
+    const makerBefore = await connection.getBalance(maker);
+    const bobBefore = await connection.getBalance(bob.publicKey);
+    await (await solTradeSdk.finalizeAuction(tradeAddress)).rpc();
+    // the maker also pays the transaction fee
+    assert.isAbove(await connection.getBalance(maker), makerBefore + LAMPORTS_PER_SOL * .69);
+    assert.equal(await connection.getBalance(bob.publicKey), bobBefore + LAMPORTS_PER_SOL * 1);
+
+    const settled = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(settled.acceptedOffer.equals(bobBid.pda.offer.address));
+    assert.equal(settled.exchangedCount, settled.assetCount);
+  });
+
+  it("token auction bids are withdrawn by the outbid bidder", async () => {
+    const connection = anchor.getProvider().connection;
+    const payer = (anchor.getProvider() as anchor.AnchorProvider).wallet as anchor.Wallet;
+    const alice = await fundedKeypair();
+    const bob = await fundedKeypair();
+
+    // both bid in the same token
+    const mint = await createMint(connection, payer.payer, payer.publicKey, null, 0);
+    const aliceToken = await getOrCreateAssociatedTokenAccount(connection, payer.payer, mint, alice.publicKey);
+    const bobToken = await getOrCreateAssociatedTokenAccount(connection, payer.payer, mint, bob.publicKey);
+    await mintTo(connection, payer.payer, mint, aliceToken.address, payer.payer, 100);
+    await mintTo(connection, payer.payer, mint, bobToken.address, payer.payer, 200);
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    await (await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .1 },
+    ])).builders[0].rpc();
+    await (await solTradeSdk.startAuction(tradeAddress, {
+      endTime: Math.floor(Date.now() / 1000) + 60,
+      auctionMint: mint,
+      minIncrement: 10,
+    })).rpc();
+
+    // alice bids everything and closes her token account
+    const aliceBid = await solTradeSdk.createOffer(tradeAddress, {
+      user: alice.publicKey,
+      bid: 100,
+      userTokenAccount: aliceToken.address,
+    });
+    await aliceBid.builder.signers([alice]).rpc();
+    await closeAccount(connection, alice, aliceToken.address, alice.publicKey, alice);
+
+    // bob still outbids her, her bid stays in its vault
+    const bobBid = await solTradeSdk.createOffer(tradeAddress, {
+      user: bob.publicKey,
+      bid: 150,
+      userTokenAccount: bobToken.address,
+    });
+    await bobBid.builder.signers([bob]).rpc();
+    assert.isTrue((await solTradeSdk.getTrade(tradeAddress)).bestOffer.equals(bobBid.pda.offer.address));
+    assert.isFalse((await solTradeSdk.getOffer(aliceBid.pda.offer.address)).bidRefunded);
+
+    // the leading bid can't be withdrawn
+    await assertAnchorError(
+      (await solTradeSdk.withdrawBid(tradeAddress, bobBid.pda.offer.address, bobToken.address)).signers([bob]).rpc(),
+      "WithdrawBidLeading",
+    );
+
+    // alice takes her bid back, once
+    const aliceNewToken = await getOrCreateAssociatedTokenAccount(connection, payer.payer, mint, alice.publicKey);
+    const withdraw = async () => (await solTradeSdk.withdrawBid(
+      tradeAddress,
+      aliceBid.pda.offer.address,
+      aliceNewToken.address,
+    )).signers([alice]).rpc();
+    await withdraw();
+    assert.equal(Number((await getAccount(connection, aliceNewToken.address)).amount), 100);
+    assert.isTrue((await solTradeSdk.getOffer(aliceBid.pda.offer.address)).bidRefunded);
+    await assertAnchorError(withdraw(), "WithdrawBidNothingToWithdraw");
+  });
+
+  it("dutch auction settles at the current price", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // trade with 1 SOL as the lot
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * 1 },
+    ]);
+    await assets.builders[0].rpc();
+
+    // from .9 SOL down to .1 SOL over an hour
+    const now = Math.floor(Date.now() / 1000);
+    await assertAnchorError(
+      (await solTradeSdk.startDutchAuction(tradeAddress, {
+        startPrice: LAMPORTS_PER_SOL * .1,
+        endPrice: LAMPORTS_PER_SOL * .9,
+        startTime: now,
+        endTime: now + 3600,
+      })).rpc(),
+      "DutchAuctionInvalidPrices",
+    );
+    await (await solTradeSdk.startDutchAuction(tradeAddress, {
+      startPrice: LAMPORTS_PER_SOL * .9,
+      endPrice: LAMPORTS_PER_SOL * .1,
+      startTime: now,
+      endTime: now + 3600,
+    })).rpc();
+
+    // no offers on a dutch auction
+    const offer = await solTradeSdk.createOffer(tradeAddress, { user: buyer.publicKey });
+    await assertAnchorError(offer.builder.signers([buyer]).rpc(), "TradeInvalidMode");
+
+    // the price has barely moved, the floor price is not enough
+    await assertAnchorError(
+      (await solTradeSdk.buyDutchAuction(tradeAddress, LAMPORTS_PER_SOL * .1, { user: buyer.publicKey }))
+        .signers([buyer]).rpc(),
+      "DutchAuctionPriceAboveMax",
+    );
+
+    // the buyer pays the current price and gets the lot in the same instruction
+    const makerBefore = await connection.getBalance(maker);
+    await (await solTradeSdk.buyDutchAuction(tradeAddress, LAMPORTS_PER_SOL * .9, { user: buyer.publicKey }))
+      .signers([buyer]).rpc();
+    const paid = await connection.getBalance(maker) - makerBefore;
+    assert.isAbove(paid, LAMPORTS_PER_SOL * .8);
+    assert.isAtMost(paid, LAMPORTS_PER_SOL * .9);
+
+    const sold = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(sold.buyer.equals(buyer.publicKey));
+    assert.equal(sold.exchangedCount, sold.assetCount);
+    const buyerProfile = await solTradeSdk.getUserProfile(buyer.publicKey);
+    assert.equal(buyerProfile.tradesCompleted, 1);
+
+    await assertAnchorError(
+      (await solTradeSdk.buyDutchAuction(tradeAddress, LAMPORTS_PER_SOL * .9, { user: buyer.publicKey }))
+        .signers([buyer]).rpc(),
+      "TradeSold",
+    );
+  });
+
+  it("fixed price listing settles in a single buy", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const stranger = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // private trade with two asset items
+    const trade = await solTradeSdk.createTrade({ allowedUsers: [buyer.publicKey] });
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .3 },
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .2 },
+    ]);
+    for (const builder of assets.builders) {
+      await builder.rpc();
+    }
+
+    await assertAnchorError((await solTradeSdk.listFixedPrice(tradeAddress, 0)).rpc(), "FixedPriceInvalidPrice");
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL * .4)).rpc();
+    await assertAnchorError(
+      (await solTradeSdk.addAssetsItem(tradeAddress, [{ type: 'sol', amount: 1 }])).builders[0].rpc(),
+      "AddAssetItemFrozen",
+    );
+
+    // only the allowed users can buy
+    await assertAnchorError(
+      (await solTradeSdk.buy(tradeAddress, { user: stranger.publicKey })).signers([stranger]).rpc(),
+      "TradeUserNotAllowed",
+    );
+
+    const makerBefore = await connection.getBalance(maker);
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    // the maker is the provider wallet, it also pays the fees of the transaction
+    assert.closeTo(await connection.getBalance(maker) - makerBefore, LAMPORTS_PER_SOL * .4, 20_000);
+
+    const sold = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(sold.buyer.equals(buyer.publicKey));
+    assert.equal(sold.exchangedCount, 2);
+    for (const asset of await solTradeSdk.getTradeOrOfferAssets(tradeAddress)) {
+      assert.isTrue(asset.account.exchanged);
+    }
+    await assertAnchorError(
+      (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc(),
+      "TradeSold",
+    );
+  });
+
+  it("strict trade only takes wanted assets", async () => {
+    const taker = await fundedKeypair();
+    const wanted = await createToken(taker.publicKey, 10);
+    const other = await createToken(taker.publicKey, 10);
+    const wantedMint = wanted.mint;
+
+    await assertAnchorError(
+      solTradeSdk.createTrade({ strict: true }).then(trade => trade.builder.rpc()),
+      "CreateTradeStrictWithoutWantedAssets",
+    );
+    await assertAnchorError(
+      solTradeSdk.createTrade({ wantedAssets: [{ type: 'sol', amount: 0 }] }).then(trade => trade.builder.rpc()),
+      "CreateTradeInvalidWantedAsset",
+    );
+
+    // the maker wants 10 tokens of a mint, nothing else
+    const trade = await solTradeSdk.createTrade({
+      wantedAssets: [{ type: 'spl', mint: wantedMint, amount: 10 }],
+      strict: true,
+    });
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const created = await solTradeSdk.getTrade(tradeAddress);
+    assert.equal(created.wantedAssets.length, 1);
+    assert.isTrue(created.wantedAssets[0].mint.equals(wantedMint));
+    assert.isTrue(created.strict);
+
+    const offer = await solTradeSdk.createOffer(tradeAddress, { user: taker.publicKey });
+    await offer.builder.signers([taker]).rpc();
+    const offerAddress = offer.pda.offer.address;
+    const addToOffer = async (asset: Parameters<typeof solTradeSdk.addAssetsItem>[1][number]) => {
+      const assets = await solTradeSdk.addAssetsItem(tradeAddress, [asset], offerAddress, taker.publicKey);
+      return assets.builders[0].signers([taker]).rpc();
+    };
+
+    await assertAnchorError(addToOffer({ type: 'sol', amount: LAMPORTS_PER_SOL * .1 }), "AddAssetItemNotWanted");
+    await assertAnchorError(
+      addToOffer({ type: 'spl', mint: other.mint, token: other.tokenAccount, amount: 10 }),
+      "AddAssetItemNotWanted",
+    );
+    // every asset item is wanted, the amount is only met by both
+    const acceptOffer = async () => {
+      const { round, contentHash } = await solTradeSdk.getOffer(offerAddress);
+      return solTradeSdk.program.methods
+        .acceptOffer(round, contentHash)
+        .accounts({
+          authority: solTradeSdk.PDA.getAuthority().address,
+          trade: tradeAddress,
+          offer: offerAddress,
+        })
+        .remainingAccounts(await solTradeSdk.getLotRemainingAccounts(offerAddress))
+        .rpc();
+    };
+    await addToOffer({ type: 'spl', mint: wantedMint, token: wanted.tokenAccount, amount: 4 });
+    await assertAnchorError(acceptOffer(), "AcceptOfferWantedAssetsNotMet");
+    await addToOffer({ type: 'spl', mint: wantedMint, token: wanted.tokenAccount, amount: 6 });
+    assert.equal((await solTradeSdk.getOffer(offerAddress)).assetCount, 2);
+    await acceptOffer();
+    assert.isTrue((await solTradeSdk.getTrade(tradeAddress)).acceptedOffer.equals(offerAddress));
+  });
+
+  it("auto accept settles the first matching offer", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const cheap = await fundedKeypair();
+    const taker = await fundedKeypair();
+    const keeper = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // trade with 1 SOL, auto accept any offer of at least .5 SOL
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * 1 },
+    ]);
+    await assets.builders[0].rpc();
+    await assertAnchorError(
+      (await solTradeSdk.setAutoAccept(tradeAddress, 'minLamports')).rpc(),
+      "AutoAcceptInvalidRule",
+    );
+    await (await solTradeSdk.setAutoAccept(tradeAddress, 'minLamports', { amount: LAMPORTS_PER_SOL * .5 })).rpc();
+
+    const makeOffer = async (user: Keypair, amount: number) => {
+      const offer = await solTradeSdk.createOffer(tradeAddress, { user: user.publicKey });
+      await offer.builder.signers([user]).rpc();
+      const offerAssets = await solTradeSdk.addAssetsItem(
+        tradeAddress,
+        [{ type: 'sol', amount }],
+        offer.pda.offer.address,
+        user.publicKey,
+      );
+      await offerAssets.builders[0].signers([user]).rpc();
+      return offer.pda.offer.address;
+    };
+
+    // anyone can try, a too low offer does not match
+    const cheapOffer = await makeOffer(cheap, LAMPORTS_PER_SOL * .3);
+    await assertAnchorError(
+      (await solTradeSdk.tryAutoAccept(tradeAddress, cheapOffer)).accounts({ user: keeper.publicKey }).signers([keeper]).rpc(),
+      "AutoAcceptNotMatched",
+    );
+
+    const offerAddress = await makeOffer(taker, LAMPORTS_PER_SOL * .6);
+    const makerBefore = await connection.getBalance(maker);
+    const takerBefore = await connection.getBalance(taker.publicKey);
+    await (await solTradeSdk.tryAutoAccept(tradeAddress, offerAddress)).accounts({ user: keeper.publicKey }).signers([keeper]).rpc();
+    // the maker is the provider wallet, it also pays the fees of the transaction
+    assert.closeTo(await connection.getBalance(maker) - makerBefore, LAMPORTS_PER_SOL * .6, 20_000);
+    assert.equal(await connection.getBalance(taker.publicKey), takerBefore + LAMPORTS_PER_SOL * 1);
+
+    const settled = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(settled.acceptedOffer.equals(offerAddress));
+    assert.equal(settled.exchangedCount, settled.assetCount);
+    assert.isTrue((await solTradeSdk.getOffer(offerAddress)).accepted);
+    await assertAnchorError(
+      (await solTradeSdk.tryAutoAccept(tradeAddress, cheapOffer)).rpc(),
+      "OfferAlreadyAccepted",
+    );
+  });
+
+  it("collection offer is filled by verified items only", async () => {
+    const buyer = await fundedKeypair();
+    const seller = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    const collection = await createCollection(anchor.getProvider().publicKey);
+    const otherCollection = await createCollection(anchor.getProvider().publicKey);
+    const items = [
+      await collection.createItem(seller.publicKey),
+      await collection.createItem(seller.publicKey),
+      await collection.createItem(seller.publicKey),
+    ];
+    const foreignItem = await otherCollection.createItem(seller.publicKey);
+    const unverifiedItem = await createNft({ owner: seller.publicKey });
+
+    // .5 SOL each for 2 items of the collection
+    const offer = await solTradeSdk.createCollectionOffer(collection.mint, LAMPORTS_PER_SOL * .5, 2, buyer.publicKey);
+    await offer.builder.signers([buyer]).rpc();
+    const collectionOfferAddress = offer.pda.collectionOffer.address;
+    const fill = async (nft: Awaited<ReturnType<typeof createNft>>) =>
+      (await solTradeSdk.fillCollectionOffer(collectionOfferAddress, nft, seller.publicKey)).signers([seller]).rpc();
+
+    await assertAnchorError(fill(foreignItem), "NftCollectionMismatch");
+    await assertAnchorError(fill(unverifiedItem), "NftCollectionMismatch");
+
+    // partial fill, then the last one
+    const sellerBefore = await connection.getBalance(seller.publicKey);
+    await fill(items[0]);
+    let collectionOffer = await program.account.collectionOffer.fetch(collectionOfferAddress);
+    assert.equal(collectionOffer.quantity, 1);
+    assert.equal(collectionOffer.filled, 1);
+    await fill(items[1]);
+    collectionOffer = await program.account.collectionOffer.fetch(collectionOfferAddress);
+    assert.equal(collectionOffer.quantity, 0);
+    assert.equal(collectionOffer.filled, 2);
+    await assertAnchorError(fill(items[2]), "CollectionOfferFilled");
+
+    // the seller also paid the rent of the buyer token accounts
+    assert.isAbove(await connection.getBalance(seller.publicKey), sellerBefore + LAMPORTS_PER_SOL * .99);
+    for (const item of items.slice(0, 2)) {
+      const buyerTokenAccount = anchor.utils.token.associatedAddress({ mint: item.mint, owner: buyer.publicKey });
+      assert.equal((await connection.getTokenAccountBalance(buyerTokenAccount)).value.amount, "1");
+    }
+
+    // the buyer closes the offer
+    await program.methods
+      .cancelCollectionOffer()
+      .accounts({ collectionOffer: collectionOfferAddress, user: buyer.publicKey })
+      .signers([buyer])
+      .rpc();
+    assert.isNull(await program.account.collectionOffer.fetchNullable(collectionOfferAddress));
+  });
+
+  it("nft sale pays creator royalties", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const creators = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+
+    // 5% royalties, shared 70 / 30
+    const nft = await createNft({
+      owner: maker,
+      sellerFeeBasisPoints: 500,
+      creators: [
+        { address: creators[0], verified: false, share: 70 },
+        { address: creators[1], verified: false, share: 30 },
+      ],
+    });
+    const otherNft = await createNft({ owner: maker });
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // the metadata must be the one of the mint
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'nft', mint: nft.mint, token: nft.tokenAccount },
+    ]);
+    await assertAnchorError(
+      assets.builders[0].accounts({ nftMetadata: otherNft.metadata }).rpc(),
+      "NftInvalidMetadata",
+    );
+    await assets.builders[0].accounts({ nftMetadata: nft.metadata }).rpc();
+    const assetItemNft = await program.account.assetItemNft.fetch(assets.assetItems[0]);
+    assert.equal(assetItemNft.sellerFeeBasisPoints, 500);
+    assert.equal(assetItemNft.creators.length, 2);
+    // the nft is held by the trade until the buyer claims it
+    assert.isTrue(assetItemNft.escrowed);
+    assert.equal((await connection.getTokenAccountBalance(assetItemNft.token)).value.amount, "1");
+    assert.equal((await connection.getTokenAccountBalance(nft.tokenAccount)).value.amount, "0");
+
+    // 1 SOL, 0.05 SOL of royalties
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    const makerBefore = await connection.getBalance(maker);
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+
+    assert.equal(await connection.getBalance(creators[0]), LAMPORTS_PER_SOL * .035);
+    assert.equal(await connection.getBalance(creators[1]), LAMPORTS_PER_SOL * .015);
+    // the maker also pays the fees of the transaction
+    assert.closeTo(await connection.getBalance(maker) - makerBefore, LAMPORTS_PER_SOL * .95, 20_000);
+    const sold = await program.account.assetItemNft.fetch(assets.assetItems[0]);
+    assert.isTrue(sold.exchanged);
+    assert.equal(sold.royaltyPaid.toNumber(), LAMPORTS_PER_SOL * .05);
+
+    // the buyer gets the nft
+    await (await solTradeSdk.claimNft(tradeAddress, assets.assetItems[0])).rpc();
+    const buyerToken = anchor.utils.token.associatedAddress({ mint: nft.mint, owner: buyer.publicKey });
+    assert.equal((await connection.getTokenAccountBalance(buyerToken)).value.amount, "1");
+    assert.isFalse((await program.account.assetItemNft.fetch(assets.assetItems[0])).escrowed);
+    await assertAnchorError(
+      (await solTradeSdk.claimNft(tradeAddress, assets.assetItems[0])).rpc(),
+      "NftNotEscrowed",
+    );
+  });
+
+  it("pnft is escrowed and claimed by the buyer", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const pnft = await createPnft({ owner: maker });
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // a pnft can't be added as a plain nft
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'nft', mint: pnft.mint, token: pnft.tokenAccount },
+    ]);
+    await assertAnchorError(assets.builders[0].rpc(), "PnftInvalidDeposit");
+
+    // the pnft moves to the escrow of the trade
+    const deposit = await solTradeSdk.addPnftAssetItem(tradeAddress, {
+      mint: pnft.mint,
+      token: pnft.tokenAccount,
+    });
+    await deposit.builder.preInstructions([
+      anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+    ]).rpc();
+    assert.equal((await connection.getTokenAccountBalance(pnft.tokenAccount)).value.amount, "0");
+    assert.equal((await connection.getTokenAccountBalance(deposit.escrowToken)).value.amount, "1");
+    const escrowed = await program.account.assetItemNft.fetch(deposit.assetItem);
+    assert.isTrue(escrowed.programmable && escrowed.escrowed);
+
+    // not released yet
+    await assertAnchorError(
+      (await solTradeSdk.claimPnft(tradeAddress, deposit.assetItem)).rpc(),
+      "PnftNotReleased",
+    );
+
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    const sold = await program.account.assetItemNft.fetch(deposit.assetItem);
+    assert.isTrue(sold.receiver.equals(buyer.publicKey));
+
+    // anyone pushes the claim, the pnft only goes to the buyer
+    await (await solTradeSdk.claimPnft(tradeAddress, deposit.assetItem))
+      .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+      .rpc();
+    const buyerToken = getAssociatedTokenAddressSync(pnft.mint, buyer.publicKey);
+    assert.equal((await connection.getTokenAccountBalance(buyerToken)).value.amount, "1");
+    assert.isFalse((await program.account.assetItemNft.fetch(deposit.assetItem)).escrowed);
+  });
+
+  it("cnft is escrowed and claimed by the buyer", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const tree = await createTree();
+    const cnft = await tree.mint(maker);
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // the proof must be the one of the leaf
+    const otherCnft = await tree.mint(maker);
+    const invalid = await solTradeSdk.addCnftAssetItem(tradeAddress, cnft, tree.getProof(otherCnft.leafIndex));
+    try {
+      await invalid.builder.rpc();
+      assert.fail("deposit with an invalid proof");
+    } catch (e) {
+      assert.notEqual(e.message, "deposit with an invalid proof");
+    }
+
+    // the leaf moves to the trade
+    const deposit = await solTradeSdk.addCnftAssetItem(tradeAddress, cnft, tree.getProof(cnft.leafIndex));
+    await deposit.builder.rpc();
+    tree.setOwner(cnft.leafIndex, tradeAddress);
+    const escrowed = await program.account.assetItemCnft.fetch(deposit.assetItem);
+    assert.isTrue(escrowed.escrowed);
+    assert.isTrue(escrowed.tree.equals(tree.merkleTree));
+    assert.equal(escrowed.leafIndex, cnft.leafIndex);
+
+    // not released yet
+    await assertAnchorError(
+      (await solTradeSdk.claimCnft(tradeAddress, deposit.assetItem, tree.getProof(cnft.leafIndex))).rpc(),
+      "CnftNotReleased",
+    );
+
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    assert.isTrue((await program.account.assetItemCnft.fetch(deposit.assetItem)).receiver.equals(buyer.publicKey));
+
+    // anyone pushes the claim, the leaf only goes to the buyer
+    await (await solTradeSdk.claimCnft(tradeAddress, deposit.assetItem, tree.getProof(cnft.leafIndex))).rpc();
+    tree.setOwner(cnft.leafIndex, buyer.publicKey);
+    assert.isFalse((await program.account.assetItemCnft.fetch(deposit.assetItem)).escrowed);
+
+    // the buyer owns the leaf, they can trade it again
+    const buyerTrade = await solTradeSdk.createTrade({ user: buyer.publicKey });
+    await buyerTrade.builder.signers([buyer]).rpc();
+    await (await solTradeSdk.addCnftAssetItem(
+      buyerTrade.pda.trade.address,
+      cnft,
+      tree.getProof(cnft.leafIndex),
+      undefined,
+      buyer.publicKey,
+    )).builder.signers([buyer]).rpc();
+  });
+
+  it("token 2022 deposits record the amount received after fees", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // non transferable tokens can't go in escrow
+    const soulbound = await createToken(maker, 1, { nonTransferable: true });
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    await assertAnchorError(
+      (await solTradeSdk.addAssetsItem(tradeAddress, [
+        { type: 'spl', mint: soulbound.mint, token: soulbound.tokenAccount, amount: 1 },
+      ])).builders[0].rpc(),
+      "SplNonTransferableUnsupported",
+    );
+
+    // 1% transfer fee, the vault gets 990 of 1000
+    const token = await createToken(maker, 1000, { transferFeeBasisPoints: 100 });
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'spl', mint: token.mint, token: token.tokenAccount, amount: 1000 },
+    ]);
+    await assets.builders[0].rpc();
+    const deposited = await program.account.assetItemSpl.fetch(assets.assetItems[0]);
+    assert.equal(deposited.amount.toNumber(), 990);
+    assert.equal(deposited.decimals, 0);
+    assert.isTrue(deposited.tokenProgram.equals(token.programId));
+    assert.equal((await connection.getTokenAccountBalance(deposited.vault)).value.amount, "990");
+
+    await assertAnchorError((await solTradeSdk.claimSpl(tradeAddress, assets.assetItems[0])).rpc(), "SplNotReleased");
+
+    // the buyer claims the 990 tokens, less the fee of the claim
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    await (await solTradeSdk.claimSpl(tradeAddress, assets.assetItems[0])).rpc();
+    const buyerToken = getAssociatedTokenAddressSync(token.mint, buyer.publicKey, false, token.programId);
+    assert.equal((await connection.getTokenAccountBalance(buyerToken)).value.amount, "980");
+    assert.equal((await connection.getTokenAccountBalance(deposited.vault)).value.amount, "0");
+    assert.isFalse((await program.account.assetItemSpl.fetch(assets.assetItems[0])).escrowed);
+  });
+
+  it("core asset is escrowed and claimed by the buyer, creators get royalties", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const creator = Keypair.generate().publicKey;
+    const connection = anchor.getProvider().connection;
+
+    // royalties come from the collection, 5% to the creator
+    const collection = await createCoreCollection({
+      plugins: [{ type: 'royalties', basisPoints: 500, creators: [{ address: creator, percentage: 100 }] }],
+    });
+    const asset = await createCoreAsset({ owner: maker, collection });
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // a permanent delegate could take the asset out of escrow
+    const delegated = await createCoreAsset({
+      owner: maker,
+      plugins: [{ type: 'permanentFreezeDelegate', frozen: false }],
+    });
+    await assertAnchorError(
+      (await solTradeSdk.addCoreAssetItem(tradeAddress, { asset: delegated })).builder.rpc(),
+      "CorePermanentDelegateUnsupported",
+    );
+    // the collection of the asset must be passed
+    await assertAnchorError(
+      (await solTradeSdk.addCoreAssetItem(tradeAddress, { asset })).builder.rpc(),
+      "CoreInvalidCollection",
+    );
+
+    // the asset moves to the trade
+    const deposit = await solTradeSdk.addCoreAssetItem(tradeAddress, { asset, collection });
+    await deposit.builder.rpc();
+    assert.isTrue((await getCoreOwner(asset)).equals(tradeAddress));
+    const escrowed = await program.account.assetItemCore.fetch(deposit.assetItem);
+    assert.isTrue(escrowed.escrowed);
+    assert.isTrue(escrowed.collection.equals(collection));
+    assert.equal(escrowed.sellerFeeBasisPoints, 500);
+    assert.isTrue(escrowed.creators[0].address.equals(creator));
+
+    // not released yet
+    await assertAnchorError(
+      (await solTradeSdk.claimCore(tradeAddress, deposit.assetItem)).rpc(),
+      "CoreNotReleased",
+    );
+
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    assert.equal(await connection.getBalance(creator), LAMPORTS_PER_SOL * .05);
+    const sold = await program.account.assetItemCore.fetch(deposit.assetItem);
+    assert.isTrue(sold.receiver.equals(buyer.publicKey));
+    assert.equal(sold.royaltyPaid.toNumber(), LAMPORTS_PER_SOL * .05);
+
+    // anyone pushes the claim, the asset only goes to the buyer
+    await (await solTradeSdk.claimCore(tradeAddress, deposit.assetItem)).rpc();
+    assert.isTrue((await getCoreOwner(asset)).equals(buyer.publicKey));
+    assert.isFalse((await program.account.assetItemCore.fetch(deposit.assetItem)).escrowed);
+  });
+
+  it("stake account is escrowed through its authorities and claimed by the buyer", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+    // a stake account of the maker, delegated to the local validator
+    const createStake = async (withdrawer = maker) => {
+      const stake = Keypair.generate();
+      const { current } = await connection.getVoteAccounts();
+      const tx = new Transaction()
+        .add(StakeProgram.createAccount({
+          fromPubkey: maker,
+          stakePubkey: stake.publicKey,
+          authorized: new Authorized(maker, withdrawer),
+          lamports: 2 * LAMPORTS_PER_SOL,
+        }))
+        .add(StakeProgram.delegate({
+          stakePubkey: stake.publicKey,
+          authorizedPubkey: maker,
+          votePubkey: new PublicKey(current[0].votePubkey),
+        }));
+      await provider.sendAndConfirm(tx, [stake]);
+      return { stake: stake.publicKey, voter: new PublicKey(current[0].votePubkey) };
+    };
+    const getAuthorities = async (stake: PublicKey) => {
+      const account = await connection.getParsedAccountInfo(stake);
+      const { staker, withdrawer } = (account.value.data as any).parsed.info.meta.authorized;
+      return { staker: new PublicKey(staker), withdrawer: new PublicKey(withdrawer) };
+    };
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // the user must hold both authorities
+    const foreign = await createStake(Keypair.generate().publicKey);
+    await assertAnchorError(
+      (await solTradeSdk.addStakeAssetItem(tradeAddress, foreign.stake)).builder.rpc(),
+      "StakeInvalidAuthority",
+    );
+
+    // the trade becomes the staker and the withdrawer, the stake stays delegated
+    const { stake, voter } = await createStake();
+    const deposit = await solTradeSdk.addStakeAssetItem(tradeAddress, stake);
+    await deposit.builder.rpc();
+    const escrowed = await getAuthorities(stake);
+    assert.isTrue(escrowed.staker.equals(tradeAddress));
+    assert.isTrue(escrowed.withdrawer.equals(tradeAddress));
+    const item = await program.account.assetItemStake.fetch(deposit.assetItem);
+    assert.isTrue(item.escrowed);
+    assert.isTrue(item.voter.equals(voter));
+    assert.equal(item.lamports.toNumber(), await connection.getBalance(stake));
+    assert.isAbove(item.delegatedAmount.toNumber(), 0);
+
+    // not released yet
+    await assertAnchorError(
+      (await solTradeSdk.claimStake(tradeAddress, deposit.assetItem)).rpc(),
+      "StakeNotReleased",
+    );
+
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    assert.isTrue((await program.account.assetItemStake.fetch(deposit.assetItem)).receiver.equals(buyer.publicKey));
+
+    // anyone pushes the claim, the authorities only go to the buyer
+    await (await solTradeSdk.claimStake(tradeAddress, deposit.assetItem)).rpc();
+    const claimed = await getAuthorities(stake);
+    assert.isTrue(claimed.staker.equals(buyer.publicKey));
+    assert.isTrue(claimed.withdrawer.equals(buyer.publicKey));
+    assert.isFalse((await program.account.assetItemStake.fetch(deposit.assetItem)).escrowed);
+  });
+
+  it("mint and upgrade authorities are escrowed and handed over", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // the mint authority is sold
+    const { mint, programId } = await createToken(maker);
+    const sale = await solTradeSdk.createTrade();
+    await sale.builder.rpc();
+    const saleAddress = sale.pda.trade.address;
+
+    // the mint has no freeze authority to hand over
+    await assertAnchorError(
+      (await solTradeSdk.addAuthorityAssetItem(saleAddress, { kind: 'freezeAccount', target: mint })).builder.rpc(),
+      "AuthorityNotHeld",
+    );
+
+    const deposit = await solTradeSdk.addAuthorityAssetItem(saleAddress, { kind: 'mintTokens', target: mint });
+    await deposit.builder.rpc();
+    assert.isTrue((await getMint(connection, mint, undefined, programId)).mintAuthority.equals(saleAddress));
+    const item = await program.account.assetItemAuthority.fetch(deposit.assetItem);
+    assert.isTrue(item.target.equals(mint));
+    assert.equal(item.kind, AUTHORITY_KIND.mintTokens);
+
+    // not released yet
+    await assertAnchorError(
+      (await solTradeSdk.claimAuthority(saleAddress, deposit.assetItem)).rpc(),
+      "AuthorityNotReleased",
+    );
+
+    await (await solTradeSdk.listFixedPrice(saleAddress, LAMPORTS_PER_SOL)).rpc();
+    await (await solTradeSdk.buy(saleAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+    await (await solTradeSdk.claimAuthority(saleAddress, deposit.assetItem)).rpc();
+    assert.isTrue((await getMint(connection, mint, undefined, programId)).mintAuthority.equals(buyer.publicKey));
+    assert.isFalse((await program.account.assetItemAuthority.fetch(deposit.assetItem)).escrowed);
+
+    // the upgrade authority of this program goes back to the maker when the trade is cancelled
+    const getUpgradeAuthority = async () => {
+      const account = await connection.getParsedAccountInfo(solTradeSdk.PDA.getProgramData().address);
+      return new PublicKey((account.value.data as any).parsed.info.authority);
+    };
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const upgrade = await solTradeSdk.addAuthorityAssetItem(tradeAddress, { kind: 'programUpgrade', target: program.programId });
+    await upgrade.builder.rpc();
+    assert.isTrue((await getUpgradeAuthority()).equals(tradeAddress));
+
+    await program.methods
+      .cancelTrade()
+      .accounts({
+        trade: tradeAddress,
+        userProfile: solTradeSdk.PDA.getUserProfile(maker).address,
+      })
+      .rpc();
+    await program.methods
+      .refundAssetItem(1, 7)
+      .accounts({
+        trade: tradeAddress,
+        offer: null,
+        assetItemSol: null,
+        assetItemSpl: null,
+        assetItemNft: null,
+        assetItemCnft: null,
+        assetItemCore: null,
+        assetItemStake: null,
+        assetItemAuthority: upgrade.assetItem,
+        receiver: maker,
+      })
+      .rpc();
+    await (await solTradeSdk.claimAuthority(tradeAddress, upgrade.assetItem)).rpc();
+    assert.isTrue((await getUpgradeAuthority()).equals(maker));
+  });
+
+  it("spl deposits check the token account and record the decimals", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const other = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    const token = await createToken(maker, 1000);
+    const otherToken = await createToken(maker, 1000);
+    const foreign = await createToken(other.publicKey, 1000);
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // the token account must hold the declared mint
+    const mismatch = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'spl', mint: token.mint, token: otherToken.tokenAccount, amount: 10 },
+    ]);
+    await assertAnchorError(mismatch.builders[0].rpc(), "AddAssetItemSplMintMismatch");
+
+    // and belong to the user
+    const stolen = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'spl', mint: foreign.mint, token: foreign.tokenAccount, amount: 10 },
+    ]);
+    await assertAnchorError(stolen.builders[0].rpc(), "AddAssetItemSplInvalidOwner");
+
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'spl', mint: token.mint, token: token.tokenAccount, amount: 250 },
+    ]);
+    await assets.builders[0].rpc();
+    const item = await program.account.assetItemSpl.fetch(assets.assetItems[0]);
+    const mint = await connection.getParsedAccountInfo(token.mint);
+    assert.isTrue(item.mint.equals(token.mint));
+    assert.isTrue(item.token.equals(token.tokenAccount));
+    assert.equal(item.amount.toNumber(), 250);
+    assert.equal(item.decimals, (mint.value.data as any).parsed.info.decimals);
+  });
+
+  it("wrapped SOL is unwrapped on deposit and paid out on request", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const buyer = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const makerWsol = getAssociatedTokenAddressSync(NATIVE_MINT, maker);
+    const buyerWsol = getAssociatedTokenAddressSync(NATIVE_MINT, buyer.publicKey);
+    const wsolBalance = async (account: PublicKey) =>
+      Number((await connection.getTokenAccountBalance(account)).value.amount);
+
+    // 0.5 SOL wrapped by the maker
+    await anchor.getProvider().sendAndConfirm(new Transaction().add(
+      createAssociatedTokenAccountIdempotentInstruction(maker, makerWsol, maker, NATIVE_MINT),
+      SystemProgram.transfer({ fromPubkey: maker, toPubkey: makerWsol, lamports: LAMPORTS_PER_SOL * .5 }),
+      createSyncNativeInstruction(makerWsol),
+    ));
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // 0.2 SOL deposited from wrapped SOL, held as lamports by the trade
+    const tradeBefore = await connection.getBalance(tradeAddress);
+    const wsolBefore = await wsolBalance(makerWsol);
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .2, wsolToken: makerWsol },
+    ]);
+    await assets.builders[0].rpc();
+    assert.equal(wsolBefore - await wsolBalance(makerWsol), LAMPORTS_PER_SOL * .2);
+    assert.equal(await connection.getBalance(tradeAddress) - tradeBefore, LAMPORTS_PER_SOL * .2);
+    assert.isNull(await connection.getAccountInfo(solTradeSdk.PDA.getWsolUnwrap(assets.assetItems[0]).address));
+    const item = await program.account.assetItemSol.fetch(assets.assetItems[0]);
+    assert.equal(item.amount.toNumber(), LAMPORTS_PER_SOL * .2);
+
+    // both sides take wrapped SOL
+    await (await solTradeSdk.setSolPayout(true)).rpc();
+    await (await solTradeSdk.setSolPayout(true, buyer.publicKey)).signers([buyer]).rpc();
+    assert.isTrue((await solTradeSdk.getUserProfile(buyer.publicKey)).wsolPayout);
+
+    // 1 SOL, the wrapped SOL account of the maker must be passed
+    await (await solTradeSdk.listFixedPrice(tradeAddress, LAMPORTS_PER_SOL)).rpc();
+    await assertAnchorError(
+      (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey }))
+        .accounts({ makerWsolAccount: null })
+        .signers([buyer])
+        .rpc(),
+      "SolPayoutInvalidWsolAccount",
+    );
+    const makerWsolBefore = await wsolBalance(makerWsol);
+    await (await solTradeSdk.buy(tradeAddress, { user: buyer.publicKey })).signers([buyer]).rpc();
+
+    assert.equal(await wsolBalance(makerWsol) - makerWsolBefore, LAMPORTS_PER_SOL);
+    assert.equal(await wsolBalance(buyerWsol), LAMPORTS_PER_SOL * .2);
+
+    // back to native SOL for the other tests of the maker
+    await (await solTradeSdk.setSolPayout(false)).rpc();
+    assert.isFalse((await solTradeSdk.getUserProfile(maker)).wsolPayout);
+  });
+
+  it("ring trade settles once every participant confirmed, or goes back on withdraw", async () => {
+    const a = anchor.getProvider().publicKey;
+    const b = await fundedKeypair();
+    const c = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    // a -> b tokens, b -> c 1 SOL, c -> a tokens
+    const tokenA = await createToken(a, 100);
+    const tokenC = await createToken(c.publicKey, 100);
+    await assertAnchorError(
+      (await solTradeSdk.createRing([a, b.publicKey])).builder.rpc(),
+      "RingInvalidParticipants",
+    );
+    const ring = await solTradeSdk.createRing([a, b.publicKey, c.publicKey]);
+    await ring.builder.rpc();
+    const ringAddress = ring.pda.ring.address;
+
+    await assertAnchorError(
+      (await solTradeSdk.addRingAssetItem(ringAddress, a, { type: 'sol', amount: LAMPORTS_PER_SOL })).builder.rpc(),
+      "RingInvalidRecipient",
+    );
+    await (await solTradeSdk.addRingAssetItem(ringAddress, b.publicKey,
+      { type: 'spl', mint: tokenA.mint, token: tokenA.tokenAccount, amount: 40 })).builder.rpc();
+    await (await solTradeSdk.addRingAssetItem(ringAddress, c.publicKey,
+      { type: 'sol', amount: LAMPORTS_PER_SOL }, b.publicKey)).builder.signers([b]).rpc();
+
+    // c has nothing in escrow yet
+    await (await solTradeSdk.confirmRing(ringAddress)).rpc();
+    await assertAnchorError(
+      (await solTradeSdk.confirmRing(ringAddress, c.publicKey)).signers([c]).rpc(),
+      "RingNothingDeposited",
+    );
+    // a new asset item clears the confirmation of a
+    await (await solTradeSdk.addRingAssetItem(ringAddress, a,
+      { type: 'spl', mint: tokenC.mint, token: tokenC.tokenAccount, amount: 25 }, c.publicKey)).builder.signers([c]).rpc();
+    assert.equal((await program.account.ring.fetch(ringAddress)).confirmed, 0);
+    await (await solTradeSdk.confirmRing(ringAddress, b.publicKey)).signers([b]).rpc();
+    await (await solTradeSdk.confirmRing(ringAddress, c.publicKey)).signers([c]).rpc();
+    const items = await solTradeSdk.getRingAssetItems(ringAddress);
+    await assertAnchorError(
+      (await solTradeSdk.claimRingAssetItem(items[1].publicKey)).rpc(),
+      "RingNotReleased",
+    );
+    await (await solTradeSdk.confirmRing(ringAddress)).rpc();
+    assert.isTrue((await program.account.ring.fetch(ringAddress)).settled);
+    await assertAnchorError(
+      (await solTradeSdk.withdrawRing(ringAddress, b.publicKey)).signers([b]).rpc(),
+      "RingClosed",
+    );
+
+    const cBefore = await connection.getBalance(c.publicKey);
+    for (const item of items) {
+      await (await solTradeSdk.claimRingAssetItem(item.publicKey)).rpc();
+    }
+    assert.equal(await connection.getBalance(c.publicKey) - cBefore, LAMPORTS_PER_SOL);
+    const tokenBalance = async (mint: PublicKey, owner: PublicKey) =>
+      Number((await connection.getTokenAccountBalance(getAssociatedTokenAddressSync(mint, owner))).value.amount);
+    assert.equal(await tokenBalance(tokenA.mint, b.publicKey), 40);
+    assert.equal(await tokenBalance(tokenC.mint, a), 25);
+    await assertAnchorError(
+      (await solTradeSdk.claimRingAssetItem(items[0].publicKey)).rpc(),
+      "RingNotEscrowed",
+    );
+
+    // a withdraws from a second ring, its asset item goes back
+    const other = await solTradeSdk.createRing([a, b.publicKey, c.publicKey]);
+    await other.builder.rpc();
+    const otherAddress = other.pda.ring.address;
+    const deposit = await solTradeSdk.addRingAssetItem(otherAddress, c.publicKey,
+      { type: 'spl', mint: tokenA.mint, token: tokenA.tokenAccount, amount: 10 });
+    await deposit.builder.rpc();
+    await (await solTradeSdk.withdrawRing(otherAddress)).rpc();
+    await (await solTradeSdk.claimRingAssetItem(deposit.pda.ringAssetItem.address)).rpc();
+    assert.equal(await tokenBalance(tokenA.mint, a), 60);
+  });
+
+  it("batch of asset items is added in one instruction, all or nothing", async () => {
+    const maker = anchor.getProvider().publicKey;
+    const other = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+
+    const nfts = await Promise.all([0, 1, 2].map(() => createNft({ owner: maker })));
+    const token = await createToken(maker, 100);
+    const foreign = await createToken(other.publicKey, 100);
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+
+    // the last token account is not the maker's, nothing is added
+    const failing = await solTradeSdk.addAssetItems(tradeAddress, [
+      ...nfts.map(nft => ({ type: 'nft' as const, mint: nft.mint, token: nft.tokenAccount })),
+      { type: 'spl', mint: foreign.mint, token: foreign.tokenAccount, amount: 10 },
+    ]);
+    await assertAnchorError(failing.builder.rpc(), "AddAssetItemSplInvalidOwner");
+    assert.equal((await solTradeSdk.getTrade(tradeAddress)).assetCount, 0);
+    assert.isNull(await connection.getAccountInfo(failing.assetItems[0]));
+
+    const assets = await solTradeSdk.addAssetItems(tradeAddress, [
+      ...nfts.map(nft => ({ type: 'nft' as const, mint: nft.mint, token: nft.tokenAccount })),
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .1 },
+      { type: 'spl', mint: token.mint, token: token.tokenAccount, amount: 30 },
+    ]);
+    // lamports sent to an asset item address before it is created don't block it
+    await anchor.getProvider().sendAndConfirm(new Transaction().add(SystemProgram.transfer({
+      fromPubkey: other.publicKey,
+      toPubkey: assets.assetItems[0],
+      lamports: 1_000,
+    })), [other]);
+    await assets.builder.rpc();
+
+    const saved = await solTradeSdk.getTrade(tradeAddress);
+    assert.equal(saved.assetCount, 5);
+    assert.equal(saved.assetNftCount, 3);
+    assert.equal(saved.assetSolCount, 1);
+    assert.equal(saved.assetSplCount, 1);
+    const items = (await solTradeSdk.getTradeOrOfferAssets(tradeAddress))
+      .sort((a, b) => a.account.index - b.account.index);
+    assert.deepEqual(items.map(item => item.publicKey.toBase58()), assets.assetItems.map(item => item.toBase58()));
+    const nftItem = await program.account.assetItemNft.fetch(assets.assetItems[2]);
+    assert.isTrue(nftItem.mint.equals(nfts[2].mint));
+    assert.equal(nftItem.typeIndex, 2);
+    const splItem = await program.account.assetItemSpl.fetch(assets.assetItems[4]);
+    assert.equal(splItem.amount.toNumber(), 30);
+    assert.equal(splItem.index, 4);
+  });
+
+
+  it("direct swap moves the assets of both signers without escrow", async () => {
+    const maker = await fundedKeypair();
+    const taker = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const creator = Keypair.generate().publicKey;
+
+    const nft = await createNft({
+      owner: maker.publicKey,
+      sellerFeeBasisPoints: 500,
+      creators: [{ address: creator, verified: false, share: 100 }],
+    });
+    const token = await createToken(maker.publicKey, 100);
+    const assets = [
+      { from: 'maker' as const, item: { type: 'nft' as const, mint: nft.mint, token: nft.tokenAccount } },
+      { from: 'maker' as const, item: { type: 'spl' as const, mint: token.mint, token: token.tokenAccount, amount: 40 } },
+      { from: 'taker' as const, item: { type: 'sol' as const, amount: LAMPORTS_PER_SOL } },
+    ];
+
+    // both sides must give something
+    await assertAnchorError(
+      (await solTradeSdk.directSwap(maker.publicKey, taker.publicKey, assets.slice(0, 2)))
+        .signers([maker, taker])
+        .rpc(),
+      "DirectSwapOneSided",
+    );
+
+    // the taker refuses anything else than the swap, here a transfer of its lamports
+    const sneaky = await (await solTradeSdk.directSwap(maker.publicKey, taker.publicKey, assets)).transaction();
+    sneaky.add(SystemProgram.transfer({ fromPubkey: taker.publicKey, toPubkey: maker.publicKey, lamports: LAMPORTS_PER_SOL }));
+    sneaky.feePayer = taker.publicKey;
+    sneaky.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
+    sneaky.partialSign(maker);
+    assert.throws(
+      () => solTradeSdk.readDirectSwap(sneaky.serialize({ requireAllSignatures: false }).toString('base64')),
+      'Transaction is not a direct swap',
+    );
+
+    // the maker signs first, the taker reads, signs and sends
+    const serialized = await solTradeSdk.signDirectSwap(maker, taker.publicKey, assets);
+    const swap = solTradeSdk.readDirectSwap(serialized);
+    assert.isTrue(swap.maker.equals(maker.publicKey));
+    assert.equal(swap.assets.length, 3);
+    assert.equal(swap.assets[2].amount.toNumber(), LAMPORTS_PER_SOL);
+
+    const makerBefore = await connection.getBalance(maker.publicKey);
+    await solTradeSdk.completeDirectSwap(serialized, taker);
+
+    const takerNft = getAssociatedTokenAddressSync(nft.mint, taker.publicKey);
+    const takerToken = getAssociatedTokenAddressSync(token.mint, taker.publicKey);
+    assert.equal((await connection.getTokenAccountBalance(takerNft)).value.amount, "1");
+    assert.equal((await connection.getTokenAccountBalance(nft.tokenAccount)).value.amount, "0");
+    assert.equal((await connection.getTokenAccountBalance(takerToken)).value.amount, "40");
+    assert.equal((await connection.getTokenAccountBalance(token.tokenAccount)).value.amount, "60");
+    // the lamports of the taker pay the royalties of the nft, the taker pays the fees
+    assert.equal(await connection.getBalance(creator), LAMPORTS_PER_SOL * .05);
+    assert.equal(await connection.getBalance(maker.publicKey) - makerBefore, LAMPORTS_PER_SOL * .95);
+  });
+
+  it("offer signed off-chain by the taker is filled by the maker once", async () => {
+    const taker = await fundedKeypair();
+    const connection = anchor.getProvider().connection;
+    const now = Math.floor(Date.now() / 1000);
+
+    const trade = await solTradeSdk.createTrade();
+    await trade.builder.rpc();
+    const tradeAddress = trade.pda.trade.address;
+    const assets = await solTradeSdk.addAssetsItem(tradeAddress, [
+      { type: 'sol', amount: LAMPORTS_PER_SOL * .5 },
+    ]);
+    await assets.builders[0].rpc();
+
+    // the taker lets the program pull 40 of its tokens, no transaction of its own after that
+    const token = await createToken(taker.publicKey, 100);
+    await anchor.getProvider().sendAndConfirm(new Transaction().add(
+      await solTradeSdk.approveSignedOfferDelegate(token.tokenAccount, 40, taker.publicKey),
+    ), [taker]);
+    const wanted = [{ mint: token.mint, amount: 40 }];
+
+    // expired
+    const expired = solTradeSdk.signOffer(tradeAddress, wanted, now - 60, 0, taker);
+    await assertAnchorError(
+      (await solTradeSdk.fillSignedOffer(expired.offer, expired.signature)).rpc(),
+      "SignedOfferExpired",
+    );
+
+    // cancelled by the taker, the nonce is taken
+    const cancelled = solTradeSdk.signOffer(tradeAddress, wanted, now + 3600, 1, taker);
+    await (await solTradeSdk.cancelSignedOffer(1, taker.publicKey)).signers([taker]).rpc();
+    try {
+      await (await solTradeSdk.fillSignedOffer(cancelled.offer, cancelled.signature)).rpc();
+      assert.fail("fill of a cancelled offer");
+    } catch (e) {
+      assert.notEqual(e.message, "fill of a cancelled offer");
+    }
+
+    const signed = solTradeSdk.signOffer(tradeAddress, wanted, now + 3600, 2, taker);
+    const takerBefore = await connection.getBalance(taker.publicKey);
+    await (await solTradeSdk.fillSignedOffer(signed.offer, signed.signature)).rpc();
+
+    const maker = anchor.getProvider().publicKey;
+    const makerToken = getAssociatedTokenAddressSync(token.mint, maker);
+    assert.equal((await connection.getTokenAccountBalance(makerToken)).value.amount, "40");
+    assert.equal((await connection.getTokenAccountBalance(token.tokenAccount)).value.amount, "60");
+    assert.equal(await connection.getBalance(taker.publicKey) - takerBefore, LAMPORTS_PER_SOL * .5);
+    const settled = await solTradeSdk.getTrade(tradeAddress);
+    assert.isTrue(settled.buyer.equals(taker.publicKey));
+    const nonce = await program.account.signedOfferNonce.fetch(solTradeSdk.PDA.getSignedOfferNonce(taker.publicKey, 2).address);
+    assert.isTrue(nonce.trade.equals(tradeAddress));
+
+    // the same offer can't be filled twice
+    try {
+      await (await solTradeSdk.fillSignedOffer(signed.offer, signed.signature)).rpc();
+      assert.fail("replay of a filled offer");
+    } catch (e) {
+      assert.notEqual(e.message, "replay of a filled offer");
+    }
+  });
+
   return;
 });